@@ -1058,6 +1058,7 @@ fn parse_ovp_frame_fast(frame: &[u8], my_id: DroneId) -> Option<&[u8]> {
 const BENCHMARK_ITERATIONS: usize = 10_000;
 const PAYLOAD_SIZE: usize = 64; // Typical drone telemetry size
 const TARGET_COUNT: usize = 5; // Typical swarm neighbors
+const SCAN_TARGET_COUNTS: &[usize] = &[1, 4, 16, 64, 185]; // 185 = full 1500-byte frame
 
 fn main() {
     println!("🚁 OVP Protocol Performance Benchmark");
@@ -1075,6 +1076,9 @@ fn main() {
     // Benchmark frame building/parsing (without actual network I/O)
     benchmark_frame_operations(&targets, &payload, test_drone_id);
 
+    // Benchmark target list scanning across list sizes
+    benchmark_target_scanning();
+
     // If you want to test actual network I/O (requires interface)
     // benchmark_network_io(&targets, &payload, test_drone_id);
 
@@ -1161,6 +1165,36 @@ fn benchmark_memory_usage(targets: &[u64], payload: &[u8]) {
     print_comparison("Memory Efficiency", old_alloc_time, new_alloc_time);
 }
 
+fn benchmark_target_scanning() {
+    println!("\n🎯 Target Scanning Benchmark (scalar vs SIMD)");
+    println!("─────────────────────────────────────");
+    println!("SIMD path: {}", simd_path_name());
+    println!();
+
+    // Worst case for an early-exit scan: our ID is last in the list
+    for &count in SCAN_TARGET_COUNTS {
+        let targets: Vec<u64> = (1..=count as u64).collect();
+        let section: Vec<u8> = targets.iter().flat_map(|id| id.to_le_bytes()).collect();
+        let my_id = count as u64;
+
+        let start = Instant::now();
+        for _ in 0..BENCHMARK_ITERATIONS {
+            let found = contains_id_scalar(std::hint::black_box(&section), my_id);
+            std::hint::black_box(found);
+        }
+        let scalar_time = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..BENCHMARK_ITERATIONS {
+            let found = contains_id_simd(std::hint::black_box(&section), my_id);
+            std::hint::black_box(found);
+        }
+        let simd_time = start.elapsed();
+
+        print_comparison(&format!("Scan {} targets", count), scalar_time, simd_time);
+    }
+}
+
 fn print_comparison(operation: &str, old_time: Duration, new_time: Duration) {
     let old_ns = old_time.as_nanos() as f64;
     let new_ns = new_time.as_nanos() as f64;
@@ -1328,3 +1362,99 @@ fn parse_ovp_frame_fast(frame: &[u8], my_id: u64) -> Option<&[u8]> {
         None
     }
 }
+
+// TARGET SCANNING IMPLEMENTATIONS
+// Scalar reference: one ID per compare, as parse_ovp_frame_fast did
+fn contains_id_scalar(targets: &[u8], my_id: u64) -> bool {
+    targets.chunks_exact(8).any(|t| u64::from_le_bytes(t.try_into().unwrap()) == my_id)
+}
+
+// SIMD version (mirrors src/scan.rs): AVX2 or SSE2 on x86_64, NEON on aarch64
+fn contains_id_simd(targets: &[u8], my_id: u64) -> bool {
+    if targets.len() < 32 {
+        return contains_id_scalar(targets, my_id); // short lists: scalar wins
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return unsafe { contains_id_avx2(targets, my_id) };
+        }
+        unsafe { contains_id_sse2(targets, my_id) }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return unsafe { contains_id_neon(targets, my_id) };
+        }
+        contains_id_scalar(targets, my_id)
+    }
+}
+
+fn simd_path_name() -> &'static str {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") { "AVX2" } else { "SSE2" }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") { "NEON" } else { "scalar" }
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        "scalar"
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn contains_id_avx2(targets: &[u8], my_id: u64) -> bool {
+    use std::arch::x86_64::*;
+
+    let needle = _mm256_set1_epi64x(my_id as i64);
+    let mut chunks = targets.chunks_exact(64);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_ptr() as *const __m256i;
+        let (low, high) = unsafe { (_mm256_loadu_si256(ptr), _mm256_loadu_si256(ptr.add(1))) };
+        let hits = _mm256_or_si256(_mm256_cmpeq_epi64(low, needle), _mm256_cmpeq_epi64(high, needle));
+        if _mm256_movemask_epi8(hits) != 0 {
+            return true;
+        }
+    }
+    unsafe { contains_id_sse2(chunks.remainder(), my_id) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn contains_id_sse2(targets: &[u8], my_id: u64) -> bool {
+    use std::arch::x86_64::*;
+
+    let needle = _mm_set1_epi64x(my_id as i64);
+    let mut chunks = targets.chunks_exact(16);
+    for chunk in &mut chunks {
+        let lanes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+        // 64-bit equality from 32-bit compares: both halves must match
+        let halves = _mm_cmpeq_epi32(lanes, needle);
+        let hits = _mm_and_si128(halves, _mm_shuffle_epi32::<0b10_11_00_01>(halves));
+        if _mm_movemask_epi8(hits) != 0 {
+            return true;
+        }
+    }
+    contains_id_scalar(chunks.remainder(), my_id)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn contains_id_neon(targets: &[u8], my_id: u64) -> bool {
+    use std::arch::aarch64::*;
+
+    let needle = vdupq_n_u64(my_id);
+    let mut chunks = targets.chunks_exact(16);
+    for chunk in &mut chunks {
+        let lanes = vreinterpretq_u64_u8(unsafe { vld1q_u8(chunk.as_ptr()) });
+        if vmaxvq_u32(vreinterpretq_u32_u64(vceqq_u64(lanes, needle))) != 0 {
+            return true;
+        }
+    }
+    contains_id_scalar(chunks.remainder(), my_id)
+}
@@ -0,0 +1,186 @@
+//! # Congestion Control
+//!
+//! Decentralized congestion control in the style of ETSI ITS DCC.
+//!
+//! Every frame the promiscuous socket hears - from any source, OVP or not -
+//! counts toward the channel load, as do loss indicators (corrupted frames,
+//! NACKs, retransmissions). Load is sampled per window as a fraction of the
+//! channel's capacity and smoothed across windows. Periodic beacons sent with
+//! `OVP::emit_beacon` go out at `min_interval` while the channel is relaxed,
+//! stretch linearly toward `max_interval` as load rises, and stay there once
+//! the channel is restrictive - so two hundred drones on one channel thin
+//! their status beacons out on their own.
+
+use std::time::{Duration, Instant};
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Windows rolled at once before the estimate is simply reset to idle
+const MAX_IDLE_WINDOWS: u32 = 16;
+
+/// Channel load estimation and beacon rate adaptation
+#[derive(Debug, Copy, Clone)]
+pub struct CongestionConfig {
+    /// Frames per second the channel carries at saturation
+    pub channel_capacity: u32,
+
+    /// Length of one load sample
+    pub window: Duration,
+
+    /// Beacon interval on a relaxed channel
+    pub min_interval: Duration,
+
+    /// Beacon interval on a restrictive channel
+    pub max_interval: Duration,
+
+    /// Load (fraction of capacity) up to which beacons use `min_interval`
+    pub relaxed_load: f64,
+
+    /// Load from which beacons use `max_interval`
+    pub restrictive_load: f64,
+}
+
+impl Default for CongestionConfig {
+    fn default() -> Self {
+        CongestionConfig {
+            channel_capacity: 2000,
+            window: Duration::from_millis(100),
+            min_interval: Duration::from_millis(100),
+            max_interval: Duration::from_secs(1),
+            relaxed_load: 0.3,
+            restrictive_load: 0.6,
+        }
+    }
+}
+
+impl CongestionConfig {
+    /// Beacon interval for a channel load
+    pub(crate) fn interval(&self, load: f64) -> Duration {
+        let span = self.restrictive_load - self.relaxed_load;
+        let position = if span > 0.0 {
+            ((load - self.relaxed_load) / span).clamp(0.0, 1.0)
+        } else if load >= self.restrictive_load {
+            1.0
+        } else {
+            0.0
+        };
+        let extra = self.max_interval.saturating_sub(self.min_interval).mul_f64(position);
+        self.min_interval + extra
+    }
+}
+
+//==============================================================================
+// CHANNEL MONITOR
+//==============================================================================
+
+/// Smoothed channel load estimate
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ChannelMonitor {
+    /// Start of the current window
+    window_start: Instant,
+
+    /// Frames heard in the current window
+    frames: u32,
+
+    /// Loss indicators seen in the current window
+    losses: u32,
+
+    /// Smoothed load, as a fraction of capacity (0 = idle, 1 = saturated)
+    load: f64,
+}
+
+impl ChannelMonitor {
+    /// Idle channel
+    pub fn new(now: Instant) -> Self {
+        ChannelMonitor { window_start: now, frames: 0, losses: 0, load: 0.0 }
+    }
+
+    /// Count a frame heard on the channel
+    pub fn observe_frame(&mut self, now: Instant, config: &CongestionConfig) {
+        self.roll(now, config);
+        self.frames += 1;
+    }
+
+    /// Count a sign of loss (corruption, a NACK, a retransmission)
+    pub fn observe_loss(&mut self, now: Instant, config: &CongestionConfig) {
+        self.roll(now, config);
+        self.losses += 1;
+    }
+
+    /// Smoothed load as of `now`
+    pub fn load(&self, now: Instant, config: &CongestionConfig) -> f64 {
+        let mut current = *self;
+        current.roll(now, config);
+        current.load
+    }
+
+    /// Close every window that ended before `now`
+    fn roll(&mut self, now: Instant, config: &CongestionConfig) {
+        if config.window.is_zero() {
+            return;
+        }
+        let mut rolled = 0;
+        while now.duration_since(self.window_start) >= config.window {
+            if rolled == MAX_IDLE_WINDOWS {
+                // Long silence: nothing of the old estimate is left anyway
+                self.load = 0.0;
+                self.window_start = now;
+                return;
+            }
+            let busy = self.frames as f64 / (config.channel_capacity.max(1) as f64 * config.window.as_secs_f64());
+            let lossy = self.losses as f64 / self.frames.max(1) as f64;
+            let sample = (busy + lossy).min(1.0);
+            // Average with the previous estimate, as DCC does for CBR
+            self.load = (self.load + sample) / 2.0;
+            self.frames = 0;
+            self.losses = 0;
+            self.window_start += config.window;
+            rolled += 1;
+        }
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Beacon intervals stay at the minimum when relaxed and stretch with load
+    #[test]
+    fn test_interval_follows_load() {
+        let config = CongestionConfig::default();
+        assert_eq!(config.interval(0.0), Duration::from_millis(100));
+        assert_eq!(config.interval(0.3), Duration::from_millis(100));
+        assert_eq!(config.interval(0.45), Duration::from_millis(550));
+        assert_eq!(config.interval(0.9), Duration::from_secs(1));
+    }
+
+    /// A busy channel raises the estimate, losses add to it, silence decays it
+    #[test]
+    fn test_channel_monitor() {
+        let config = CongestionConfig { channel_capacity: 100, ..CongestionConfig::default() };
+        let start = Instant::now();
+        let mut monitor = ChannelMonitor::new(start);
+
+        // 10 frames in a 100 ms window at capacity 100/s: saturated
+        for _ in 0..10 {
+            monitor.observe_frame(start, &config);
+        }
+        assert_eq!(monitor.load(start + config.window, &config), 0.5);
+        assert_eq!(monitor.load(start, &config), 0.0);
+
+        // 2 frames, one lost: 0.2 busy + 0.5 lossy
+        monitor.observe_frame(start + config.window, &config);
+        monitor.observe_frame(start + config.window, &config);
+        monitor.observe_loss(start + config.window, &config);
+        assert!((monitor.load(start + config.window * 2, &config) - 0.6).abs() < 1e-9);
+
+        assert!(monitor.load(start + config.window * 4, &config) < 0.2);
+        assert_eq!(monitor.load(start + config.window * 40, &config), 0.0);
+    }
+}
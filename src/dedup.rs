@@ -0,0 +1,142 @@
+//! # Duplicate Suppression
+//!
+//! Bounded, time-windowed memory of recently delivered frames.
+//!
+//! The same frame can reach a drone several times: sender retransmissions
+//! whose ACK was lost, relays, multiple interfaces. Frames carrying an origin
+//! and sequence number are remembered by `(source, sequence)`; all others by
+//! a hash of their header, targets and payload. Keys are hashed with a
+//! per-process random key, so peers cannot craft colliding frames.
+//!
+//! Without a sequence number, identical frames sent on purpose within the TTL
+//! also count as repeats - keep the TTL short or sequence such traffic.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant};
+
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size and time window of the duplicate cache
+#[derive(Debug, Copy, Clone)]
+pub struct DedupConfig {
+    /// Most frames remembered (oldest forgotten first)
+    pub capacity: usize,
+
+    /// How long a frame is remembered
+    pub ttl: Duration,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        DedupConfig { capacity: 1024, ttl: Duration::from_secs(2) }
+    }
+}
+
+//==============================================================================
+// DUPLICATE CACHE
+//==============================================================================
+
+/// Recently seen frame keys with their arrival time
+#[derive(Debug, Clone)]
+pub(crate) struct DuplicateCache {
+    /// Size and time window
+    config: DedupConfig,
+
+    /// Keyed hasher, random per process
+    hasher: RandomState,
+
+    /// Key -> most recent arrival
+    seen: HashMap<u64, Instant>,
+
+    /// Arrivals in order, for expiry and capacity eviction
+    order: VecDeque<(u64, Instant)>,
+}
+
+impl DuplicateCache {
+    /// Create an empty cache
+    pub fn new(config: DedupConfig) -> Self {
+        DuplicateCache { config, hasher: RandomState::new(), seen: HashMap::new(), order: VecDeque::new() }
+    }
+
+    /// Key of a sequenced frame; `stream` separates a sender's sequence spaces
+    pub fn sequenced_key(&self, source: DroneId, sequence: u32, stream: u32) -> u64 {
+        self.hasher.hash_one((source, sequence, stream))
+    }
+
+    /// Key of an unsequenced frame
+    pub fn content_key(&self, frame: &[u8]) -> u64 {
+        self.hasher.hash_one(frame)
+    }
+
+    /// Record `key`, returning true if it was already seen within the TTL
+    pub fn check(&mut self, key: u64, now: Instant) -> bool {
+        while self.order.front().is_some_and(|&(_, at)| now.duration_since(at) >= self.config.ttl) {
+            self.forget_oldest();
+        }
+        if self.seen.contains_key(&key) {
+            return true;
+        }
+
+        if self.config.capacity > 0 {
+            while self.order.len() >= self.config.capacity {
+                self.forget_oldest();
+            }
+            self.seen.insert(key, now);
+            self.order.push_back((key, now));
+        }
+        false
+    }
+
+    /// Drop the oldest arrival
+    fn forget_oldest(&mut self) {
+        if let Some((key, at)) = self.order.pop_front() {
+            // Only forget the key if this was its latest arrival
+            if self.seen.get(&key) == Some(&at) {
+                self.seen.remove(&key);
+            }
+        }
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repeats are caught within the TTL and forgotten after it
+    #[test]
+    fn test_repeats_within_ttl() {
+        let mut cache = DuplicateCache::new(DedupConfig { capacity: 8, ttl: Duration::from_millis(100) });
+        let now = Instant::now();
+        let key = cache.sequenced_key(7, 1, 0);
+
+        assert!(!cache.check(key, now));
+        assert!(cache.check(key, now + Duration::from_millis(50)));
+        assert!(!cache.check(cache.sequenced_key(7, 1, 1), now));
+        assert!(!cache.check(cache.content_key(b"frame"), now));
+        assert!(cache.check(cache.content_key(b"frame"), now));
+        assert!(!cache.check(key, now + Duration::from_millis(100)));
+    }
+
+    /// The oldest entries are forgotten once the cache is full
+    #[test]
+    fn test_capacity_bound() {
+        let mut cache = DuplicateCache::new(DedupConfig { capacity: 2, ttl: Duration::from_secs(60) });
+        let now = Instant::now();
+        for key in [1, 2, 3] {
+            assert!(!cache.check(key, now));
+        }
+        assert!(!cache.check(1, now));
+        assert!(cache.check(3, now));
+        assert_eq!(cache.seen.len(), 2);
+    }
+}
@@ -0,0 +1,263 @@
+//! # Delay-Tolerant Store-and-Forward
+//!
+//! Drones scouting beyond radio range miss whatever the swarm sends while
+//! they are away. Messages sent with `OVP::emit_stored` are bundles: every
+//! drone with `OvpConfig::dtn` keeps a copy until the bundle's lifetime runs
+//! out and hands it to the drones it meets later, so updates spread as drones
+//! move even if the sender and a receiver are never in range together.
+//!
+//! Bundles are exchanged as in epidemic routing. A drone passes on each new
+//! bundle once when it stores it. When it discovers a neighbour (see
+//! `OvpConfig::neighbors`) it broadcasts a summary vector listing the bundles
+//! it holds; drones that hear it request the ones they lack, and the holder
+//! broadcasts them.
+//!
+//! ```text
+//! BUNDLE body:  [lifetime_ms:4][inner frame]
+//! SUMMARY body: [count:1] ([source:8][sequence:4]) * count
+//! REQUEST body: [count:1] ([source:8][sequence:4]) * count
+//! ```
+//!
+//! Bundles are complete multi-hop frames (TTL 0, so they are never flooded)
+//! identified by their origin and sequence number, and are delivered like any
+//! other frame, so tags, signatures and targets work end to end. Storage is
+//! bounded by count and bytes; when it is full the lowest-priority bundle
+//! nearest its expiry makes room, or the new bundle is refused if it ranks
+//! lower still.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::priority::Priority;
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of the `BUNDLE` header in front of the inner frame
+pub(crate) const BUNDLE_HEADER_SIZE: usize = 4;
+
+/// Size of one summary or request entry
+const ENTRY_SIZE: usize = 12;
+
+/// Most bundles listed in one summary or request
+pub(crate) const MAX_ENTRIES: usize = 100;
+
+/// A bundle broadcast this recently is not sent again - one broadcast
+/// answers every drone that asked at the same time
+const OFFER_HOLDOFF: Duration = Duration::from_millis(50);
+
+/// Storage limits of delay-tolerant bundles
+#[derive(Debug, Copy, Clone)]
+pub struct DtnConfig {
+    /// Most bundles stored
+    pub max_bundles: usize,
+
+    /// Most bytes of bundles stored
+    pub max_bytes: usize,
+
+    /// Longest lifetime honoured (longer ones are cut to it)
+    pub max_lifetime: Duration,
+}
+
+impl Default for DtnConfig {
+    fn default() -> Self {
+        DtnConfig { max_bundles: 256, max_bytes: 256 * 1024, max_lifetime: Duration::from_secs(3600) }
+    }
+}
+
+//==============================================================================
+// WIRE FORMAT
+//==============================================================================
+
+/// Origin and sequence number of a bundle's inner frame
+pub(crate) type BundleId = (DroneId, u32);
+
+/// Encode a summary or request body listing up to `MAX_ENTRIES` bundles
+pub(crate) fn ids_to_bytes(ids: &[BundleId]) -> Vec<u8> {
+    let ids = &ids[..ids.len().min(MAX_ENTRIES)];
+    let mut body = Vec::with_capacity(1 + ids.len() * ENTRY_SIZE);
+    body.push(ids.len() as u8);
+    for &(source, sequence) in ids {
+        body.extend_from_slice(&source.to_le_bytes());
+        body.extend_from_slice(&sequence.to_le_bytes());
+    }
+    body
+}
+
+/// Decode a summary or request body
+pub(crate) fn parse_ids(body: &[u8]) -> Option<Vec<BundleId>> {
+    let (&count, entries) = body.split_first()?;
+    if count as usize > MAX_ENTRIES || entries.len() != count as usize * ENTRY_SIZE {
+        return None;
+    }
+    Some(entries.chunks_exact(ENTRY_SIZE)
+        .map(|entry| {
+            let source = DroneId::from_le_bytes(entry[0..8].try_into().unwrap());
+            (source, u32::from_le_bytes(entry[8..12].try_into().unwrap()))
+        })
+        .collect())
+}
+
+//==============================================================================
+// BUNDLE STORE
+//==============================================================================
+
+/// A stored bundle
+#[derive(Debug, Clone)]
+struct Bundle {
+    /// Inner frame as we transmit it, our hop counted
+    frame: Vec<u8>,
+
+    /// Traffic class, deciding eviction
+    priority: Priority,
+
+    /// End of the bundle's lifetime
+    expires: Instant,
+
+    /// Our last broadcast of it
+    last_sent: Option<Instant>,
+}
+
+/// Bundles carried for the swarm, bounded by count and bytes
+#[derive(Debug, Clone)]
+pub(crate) struct BundleStore {
+    /// Storage limits
+    config: DtnConfig,
+
+    /// Bundles by origin and sequence number
+    bundles: HashMap<BundleId, Bundle>,
+
+    /// Total size of the stored frames
+    bytes: usize,
+}
+
+impl BundleStore {
+    /// Create an empty store
+    pub fn new(config: DtnConfig) -> Self {
+        BundleStore { config, bundles: HashMap::new(), bytes: 0 }
+    }
+
+    /// True if `id` is stored
+    pub fn contains(&self, id: BundleId) -> bool {
+        self.bundles.contains_key(&id)
+    }
+
+    /// Store a bundle living for `lifetime`, making room if needed
+    ///
+    /// Returns the number of bundles evicted, or None if the bundle was
+    /// refused (it ranks below everything stored, or exceeds the byte limit).
+    pub fn insert(&mut self, id: BundleId, frame: Vec<u8>, priority: Priority, lifetime: Duration, now: Instant) -> Option<usize> {
+        if self.contains(id) || frame.len() > self.config.max_bytes || self.config.max_bundles == 0 {
+            return None;
+        }
+        let expires = now + lifetime.min(self.config.max_lifetime);
+        let mut evicted = 0;
+        while self.bundles.len() >= self.config.max_bundles || self.bytes + frame.len() > self.config.max_bytes {
+            let (&victim, lowest) = self.bundles.iter().min_by_key(|(_, bundle)| (bundle.priority, bundle.expires))?;
+            if (lowest.priority, lowest.expires) >= (priority, expires) {
+                return None;
+            }
+            self.remove(victim);
+            evicted += 1;
+        }
+        self.bytes += frame.len();
+        self.bundles.insert(id, Bundle { frame, priority, expires, last_sent: None });
+        Some(evicted)
+    }
+
+    /// Drop bundles whose lifetime ran out, returning how many
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let expired: Vec<BundleId> = self.bundles.iter()
+            .filter(|(_, bundle)| bundle.expires <= now)
+            .map(|(&id, _)| id)
+            .collect();
+        for &id in &expired {
+            self.remove(id);
+        }
+        expired.len()
+    }
+
+    /// Identifiers of every stored bundle, highest priority first
+    pub fn summary(&self) -> Vec<BundleId> {
+        let mut ids: Vec<(BundleId, Priority)> = self.bundles.iter().map(|(&id, bundle)| (id, bundle.priority)).collect();
+        ids.sort_by_key(|&(id, priority)| (std::cmp::Reverse(priority), id));
+        ids.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Bundle `id` ready to broadcast: its frame, remaining lifetime and
+    /// priority, or None if it is not stored or was sent very recently
+    pub fn take_offer(&mut self, id: BundleId, now: Instant) -> Option<(Vec<u8>, Duration, Priority)> {
+        let bundle = self.bundles.get_mut(&id).filter(|bundle| bundle.expires > now)?;
+        if bundle.last_sent.is_some_and(|at| now.duration_since(at) < OFFER_HOLDOFF) {
+            return None;
+        }
+        bundle.last_sent = Some(now);
+        Some((bundle.frame.clone(), bundle.expires - now, bundle.priority))
+    }
+
+    /// Remove a bundle and release its bytes
+    fn remove(&mut self, id: BundleId) {
+        if let Some(bundle) = self.bundles.remove(&id) {
+            self.bytes -= bundle.frame.len();
+        }
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Summary and request bodies round-trip and reject bad lengths
+    #[test]
+    fn test_ids_round_trip() {
+        let body = ids_to_bytes(&[(7, 3), (8, 1)]);
+        assert_eq!(parse_ids(&body), Some(vec![(7, 3), (8, 1)]));
+        assert_eq!(parse_ids(&body[..body.len() - 1]), None);
+        assert_eq!(parse_ids(&ids_to_bytes(&vec![(1, 1); 150])).map(|ids| ids.len()), Some(MAX_ENTRIES));
+    }
+
+    /// Full storage evicts the lowest priority nearest expiry, or refuses
+    /// bundles that rank lower still
+    #[test]
+    fn test_priority_eviction() {
+        let config = DtnConfig { max_bundles: 2, max_bytes: 100, ..DtnConfig::default() };
+        let mut store = BundleStore::new(config);
+        let now = Instant::now();
+        let minute = Duration::from_secs(60);
+        assert_eq!(store.insert((1, 1), vec![0; 10], Priority::Normal, minute, now), Some(0));
+        assert_eq!(store.insert((1, 2), vec![0; 10], Priority::Normal, minute * 2, now), Some(0));
+        assert_eq!(store.insert((1, 2), vec![0; 10], Priority::Normal, minute * 2, now), None);
+        assert_eq!(store.insert((2, 1), vec![0; 10], Priority::Bulk, minute * 5, now), None);
+
+        assert_eq!(store.insert((3, 1), vec![0; 10], Priority::Emergency, minute, now), Some(1));
+        assert!(!store.contains((1, 1)));
+        assert_eq!(store.summary(), vec![(3, 1), (1, 2)]);
+
+        // Byte limit
+        assert_eq!(store.insert((4, 1), vec![0; 95], Priority::Emergency, minute * 3, now), Some(2));
+        assert_eq!(store.summary(), vec![(4, 1)]);
+        assert_eq!(store.insert((5, 1), vec![0; 101], Priority::Emergency, minute, now), None);
+    }
+
+    /// Bundles expire, and each is offered once per holdoff
+    #[test]
+    fn test_offers_and_expiry() {
+        let mut store = BundleStore::new(DtnConfig::default());
+        let now = Instant::now();
+        store.insert((1, 1), vec![1, 2, 3], Priority::High, Duration::from_secs(10), now);
+        assert_eq!(store.take_offer((1, 1), now), Some((vec![1, 2, 3], Duration::from_secs(10), Priority::High)));
+        assert_eq!(store.take_offer((1, 1), now + Duration::from_millis(10)), None);
+        assert!(store.take_offer((1, 1), now + Duration::from_secs(1)).is_some());
+        assert_eq!(store.take_offer((9, 9), now), None);
+
+        assert_eq!(store.expire(now + Duration::from_secs(5)), 0);
+        assert_eq!(store.expire(now + Duration::from_secs(10)), 1);
+        assert!(store.summary().is_empty());
+    }
+}
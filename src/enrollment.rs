@@ -0,0 +1,460 @@
+//! # Swarm Enrollment
+//!
+//! Authenticated join handshake, swarm membership tags and ID revocation.
+//!
+//! Without enrollment any process calling `OVP::new` with an arbitrary drone ID
+//! is a full participant. With it, a drone must prove possession of a
+//! [`DeviceCredential`] provisioned at manufacture before the
+//! [`EnrollmentAuthority`] hands it the swarm key:
+//!
+//! ```text
+//! drone                                          authority
+//!   │ JOIN_REQUEST  [nonce_d:16][proof:32]          │  proof = HMAC(credential, id ‖ nonce_d)
+//!   │ ────────────────────────────────────────────▶ │
+//!   │ JOIN_ACCEPT   [nonce_d:16][nonce_a:16]        │  wrapped = swarm_key ⊕ HMAC(credential, id ‖ nonces ‖ generation)
+//!   │               [generation:4][wrapped:32]      │
+//!   │               [proof:32]                      │
+//!   │ ◀──────────────────────────────────────────── │
+//! ```
+//!
+//! Once enrolled, every emitted frame carries its source ID and a membership tag
+//! keyed by a per-ID key derived from the swarm key, binding the frame to the
+//! claimed ID. Members can still derive each other's keys - use signed frames
+//! where non-repudiation is required.
+//!
+//! Revoked IDs are announced in signed `REVOKE` frames and all of their frames
+//! are dropped from then on. Because the revoked device still knows the swarm
+//! key, the authority also rotates it: a `REKEY` frame carries the next key
+//! generation wrapped under each remaining drone's credential, so only drones
+//! still provisioned can unwrap it.
+//!
+//! ```text
+//! REKEY  [generation:4][count:1] count × [id:8][wrapped:32][proof:32]
+//!        wrapped = swarm_key ⊕ HMAC(credential, id ‖ generation)
+//! ```
+//!
+//! Tags under the previous key are accepted for `REKEY_GRACE` so frames in
+//! flight during the rotation are not lost. Drones that miss the `REKEY` fall
+//! out of the swarm and must enroll again.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use hmac_sha256::HMAC;
+
+use crate::DroneId;
+use crate::frame::FrameView;
+
+//==============================================================================
+// ENROLLMENT CONSTANTS
+//==============================================================================
+
+/// Size of a provisioned device credential in bytes
+pub const CREDENTIAL_SIZE: usize = 32;
+
+/// Size of the shared swarm key in bytes
+pub const SWARM_KEY_SIZE: usize = 32;
+
+/// Truncated HMAC-SHA256 membership tag carried by enrolled frames
+pub(crate) const MEMBERSHIP_TAG_SIZE: usize = 16;
+
+/// Size of the handshake nonces
+pub(crate) const NONCE_SIZE: usize = 16;
+
+/// JOIN_REQUEST body: `[nonce_d:16][proof:32]`
+pub(crate) const JOIN_REQUEST_SIZE: usize = NONCE_SIZE + 32;
+
+/// JOIN_ACCEPT body: `[nonce_d:16][nonce_a:16][generation:4][wrapped:32][proof:32]`
+pub(crate) const JOIN_ACCEPT_SIZE: usize = ACCEPT_HEADER_SIZE + SWARM_KEY_SIZE + 32;
+
+/// JOIN_ACCEPT material covered by the key wrap: both nonces and the generation
+const ACCEPT_HEADER_SIZE: usize = NONCE_SIZE * 2 + 4;
+
+/// One REKEY entry: `[id:8][wrapped:32][proof:32]`
+const REKEY_ENTRY_SIZE: usize = 8 + SWARM_KEY_SIZE + 32;
+
+/// Entries per REKEY frame, keeping bodies well inside one frame
+pub(crate) const MAX_REKEY_ENTRIES: usize = 16;
+
+/// How long tags under the previous swarm key stay valid after a rotation
+pub(crate) const REKEY_GRACE: Duration = Duration::from_millis(500);
+
+//==============================================================================
+// CREDENTIALS
+//==============================================================================
+
+/// Secret provisioned into a drone before deployment
+///
+/// Known only to the drone and the enrollment authority. Never transmitted -
+/// the handshake only proves possession of it.
+#[derive(Clone)]
+pub struct DeviceCredential([u8; CREDENTIAL_SIZE]);
+
+impl DeviceCredential {
+    /// Wrap raw credential bytes
+    pub fn new(bytes: [u8; CREDENTIAL_SIZE]) -> Self {
+        DeviceCredential(bytes)
+    }
+
+    /// HMAC over a domain label, the drone ID and handshake material
+    fn mac(&self, label: &[u8], id: DroneId, parts: &[&[u8]]) -> [u8; 32] {
+        let mut mac = HMAC::new(self.0);
+        mac.update(label);
+        mac.update(id.to_le_bytes());
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize()
+    }
+}
+
+/// Constant-time equality for MACs
+#[inline]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Build the JOIN_REQUEST body for `id`
+pub(crate) fn join_request(credential: &DeviceCredential, id: DroneId, nonce: &[u8; NONCE_SIZE]) -> [u8; JOIN_REQUEST_SIZE] {
+    let mut body = [0u8; JOIN_REQUEST_SIZE];
+    body[..NONCE_SIZE].copy_from_slice(nonce);
+    body[NONCE_SIZE..].copy_from_slice(&credential.mac(b"ovp-join", id, &[nonce]));
+    body
+}
+
+/// Verify a JOIN_ACCEPT body answering our request and unwrap the swarm key
+/// and its generation
+pub(crate) fn open_accept(
+    credential: &DeviceCredential,
+    id: DroneId,
+    nonce: &[u8; NONCE_SIZE],
+    body: &[u8],
+) -> Option<([u8; SWARM_KEY_SIZE], u32)> {
+    if body.len() != JOIN_ACCEPT_SIZE || !ct_eq(&body[..NONCE_SIZE], nonce) {
+        return None;
+    }
+    let (header, rest) = body.split_at(ACCEPT_HEADER_SIZE);
+    let (wrapped, proof) = rest.split_at(SWARM_KEY_SIZE);
+    if !ct_eq(proof, &credential.mac(b"ovp-accept", id, &[header, wrapped])) {
+        return None;
+    }
+
+    let pad = credential.mac(b"ovp-wrap", id, &[header]);
+    let generation = u32::from_le_bytes(header[NONCE_SIZE * 2..].try_into().unwrap());
+    Some((unwrap_key(wrapped, &pad), generation))
+}
+
+/// Find our entry in a REKEY body and unwrap the next swarm key
+///
+/// Returns the key and its generation, or None if the body holds no valid
+/// entry for `id` or the generation is not newer than `current`.
+pub(crate) fn open_rekey(credential: &DeviceCredential, id: DroneId, current: u32, body: &[u8]) -> Option<([u8; SWARM_KEY_SIZE], u32)> {
+    let (header, entries) = body.split_at_checked(5)?;
+    let generation = u32::from_le_bytes(header[..4].try_into().unwrap());
+    if generation <= current || entries.len() != header[4] as usize * REKEY_ENTRY_SIZE {
+        return None;
+    }
+    let generation_bytes = generation.to_le_bytes();
+    let entry = entries.chunks_exact(REKEY_ENTRY_SIZE)
+        .find(|entry| entry[..8] == id.to_le_bytes())?;
+    let (wrapped, proof) = entry[8..].split_at(SWARM_KEY_SIZE);
+    if !ct_eq(proof, &credential.mac(b"ovp-rekey", id, &[&generation_bytes, wrapped])) {
+        return None;
+    }
+
+    let pad = credential.mac(b"ovp-rekey-wrap", id, &[&generation_bytes]);
+    Some((unwrap_key(wrapped, &pad), generation))
+}
+
+/// XOR a swarm key with a credential-derived pad (wraps and unwraps)
+fn unwrap_key(wrapped: &[u8], pad: &[u8; 32]) -> [u8; SWARM_KEY_SIZE] {
+    let mut swarm_key = [0u8; SWARM_KEY_SIZE];
+    for (i, byte) in swarm_key.iter_mut().enumerate() {
+        *byte = wrapped[i] ^ pad[i];
+    }
+    swarm_key
+}
+
+//==============================================================================
+// ENROLLMENT AUTHORITY
+//==============================================================================
+
+/// Registry of provisioned drones and holder of the swarm key
+///
+/// Placed in `OvpConfig::enrollment_authority` on the node that admits new
+/// drones (typically the ground station or swarm leader).
+#[derive(Clone)]
+pub struct EnrollmentAuthority {
+    /// Key handed to every enrolled drone
+    swarm_key: [u8; SWARM_KEY_SIZE],
+
+    /// Number of rotations of the swarm key so far
+    generation: u32,
+
+    /// Provisioned credentials indexed by the drone ID they are bound to
+    registry: HashMap<DroneId, DeviceCredential>,
+}
+
+impl EnrollmentAuthority {
+    /// Create an authority distributing `swarm_key` to enrolled drones
+    pub fn new(swarm_key: [u8; SWARM_KEY_SIZE]) -> Self {
+        EnrollmentAuthority { swarm_key, generation: 0, registry: HashMap::new() }
+    }
+
+    /// Bind a provisioned credential to a drone ID
+    pub fn provision(&mut self, id: DroneId, credential: DeviceCredential) {
+        self.registry.insert(id, credential);
+    }
+
+    /// Forget a drone's credential so it can never enroll again
+    pub fn deprovision(&mut self, id: DroneId) -> bool {
+        self.registry.remove(&id).is_some()
+    }
+
+    /// Check whether a drone ID has a provisioned credential
+    pub fn is_provisioned(&self, id: DroneId) -> bool {
+        self.registry.contains_key(&id)
+    }
+
+    /// Swarm key distributed to enrolled drones
+    pub(crate) fn swarm_key(&self) -> &[u8; SWARM_KEY_SIZE] {
+        &self.swarm_key
+    }
+
+    /// Generation of the current swarm key
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Replace the swarm key and build the REKEY bodies distributing it
+    ///
+    /// Every provisioned drone gets one entry; deprovision revoked drones
+    /// first so they are left out.
+    pub(crate) fn rekey(&mut self, swarm_key: [u8; SWARM_KEY_SIZE]) -> Vec<Vec<u8>> {
+        self.swarm_key = swarm_key;
+        self.generation += 1;
+        let generation = self.generation.to_le_bytes();
+
+        let mut ids: Vec<DroneId> = self.registry.keys().copied().collect();
+        ids.sort_unstable();
+        ids.chunks(MAX_REKEY_ENTRIES).map(|chunk| {
+            let mut body = Vec::with_capacity(5 + chunk.len() * REKEY_ENTRY_SIZE);
+            body.extend_from_slice(&generation);
+            body.push(chunk.len() as u8);
+            for &id in chunk {
+                let credential = &self.registry[&id];
+                let pad = credential.mac(b"ovp-rekey-wrap", id, &[&generation]);
+                let wrapped = unwrap_key(&swarm_key, &pad);
+                body.extend_from_slice(&id.to_le_bytes());
+                body.extend_from_slice(&wrapped);
+                body.extend_from_slice(&credential.mac(b"ovp-rekey", id, &[&generation, &wrapped]));
+            }
+            body
+        }).collect()
+    }
+
+    /// Verify a JOIN_REQUEST from `id` and build the matching JOIN_ACCEPT body
+    ///
+    /// Returns None if the ID is not provisioned or the proof is wrong.
+    pub(crate) fn accept(&self, id: DroneId, body: &[u8], nonce: &[u8; NONCE_SIZE]) -> Option<[u8; JOIN_ACCEPT_SIZE]> {
+        let credential = self.registry.get(&id)?;
+        if body.len() != JOIN_REQUEST_SIZE {
+            return None;
+        }
+        let (drone_nonce, proof) = body.split_at(NONCE_SIZE);
+        if !ct_eq(proof, &credential.mac(b"ovp-join", id, &[drone_nonce])) {
+            return None;
+        }
+
+        let mut accept = [0u8; JOIN_ACCEPT_SIZE];
+        accept[..NONCE_SIZE].copy_from_slice(drone_nonce);
+        accept[NONCE_SIZE..NONCE_SIZE * 2].copy_from_slice(nonce);
+        accept[NONCE_SIZE * 2..ACCEPT_HEADER_SIZE].copy_from_slice(&self.generation.to_le_bytes());
+
+        let pad = credential.mac(b"ovp-wrap", id, &[&accept[..ACCEPT_HEADER_SIZE]]);
+        let wrapped = unwrap_key(&self.swarm_key, &pad);
+        accept[ACCEPT_HEADER_SIZE..ACCEPT_HEADER_SIZE + SWARM_KEY_SIZE].copy_from_slice(&wrapped);
+
+        let proof = credential.mac(b"ovp-accept", id, &[&accept[..ACCEPT_HEADER_SIZE], &wrapped]);
+        accept[ACCEPT_HEADER_SIZE + SWARM_KEY_SIZE..].copy_from_slice(&proof);
+        Some(accept)
+    }
+}
+
+//==============================================================================
+// MEMBERSHIP
+//==============================================================================
+
+/// Session keys held by an enrolled drone
+#[derive(Clone)]
+pub(crate) struct Membership {
+    /// Shared swarm key received during enrollment
+    swarm_key: [u8; SWARM_KEY_SIZE],
+
+    /// This drone's real ID
+    own_id: DroneId,
+
+    /// Per-ID frame key of this drone, cached for the emit path
+    own_key: [u8; 32],
+
+    /// Generation of `swarm_key`; only newer REKEYs are accepted
+    generation: u32,
+
+    /// Key replaced by the last REKEY and when it stops being accepted
+    previous: Option<([u8; SWARM_KEY_SIZE], Instant)>,
+}
+
+impl Membership {
+    /// Derive session keys for `id` from generation `generation` of the swarm key
+    pub fn new(swarm_key: [u8; SWARM_KEY_SIZE], id: DroneId, generation: u32) -> Self {
+        let own_key = member_key(&swarm_key, id);
+        Membership { swarm_key, own_id: id, own_key, generation, previous: None }
+    }
+
+    /// Generation of the swarm key in use
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Switch to a rotated swarm key, still accepting the old one for `REKEY_GRACE`
+    pub fn rekey(&mut self, swarm_key: [u8; SWARM_KEY_SIZE], generation: u32, now: Instant) {
+        let old = std::mem::replace(self, Membership::new(swarm_key, self.own_id, generation));
+        self.previous = Some((old.swarm_key, now + REKEY_GRACE));
+    }
+
+    /// Append the membership tag for `source` to the frame body in `buf[..len]`
+    ///
+    /// `source` is the value written in the origin section - our real ID, or
+    /// our current pseudonym in privacy mode.
+    pub fn tag_frame(&self, buf: &mut [u8], len: usize, source: DroneId) -> usize {
+        let key = if source == self.own_id { self.own_key } else { member_key(&self.swarm_key, source) };
+        let tag = HMAC::mac(&buf[..len], key);
+        buf[len..len + MEMBERSHIP_TAG_SIZE].copy_from_slice(&tag[..MEMBERSHIP_TAG_SIZE]);
+        len + MEMBERSHIP_TAG_SIZE
+    }
+
+    /// Verify that a frame's membership tag matches its claimed source ID
+    pub fn verify(&self, view: &FrameView<'_>) -> bool {
+        let (Some(source), Some(tag)) = (view.source, view.membership_tag) else {
+            return false;
+        };
+        let matches = |swarm_key| {
+            let expected = HMAC::mac(view.membership_region, member_key(swarm_key, source));
+            ct_eq(tag, &expected[..MEMBERSHIP_TAG_SIZE])
+        };
+        matches(&self.swarm_key) || self.previous.as_ref()
+            .is_some_and(|(previous, until)| Instant::now() < *until && matches(previous))
+    }
+}
+
+/// Per-ID frame key: binds membership tags to the claimed source ID
+fn member_key(swarm_key: &[u8; SWARM_KEY_SIZE], id: DroneId) -> [u8; 32] {
+    let mut mac = HMAC::new(swarm_key);
+    mac.update(b"ovp-member");
+    mac.update(id.to_le_bytes());
+    mac.finalize()
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{parse_frame, write_frame, FrameMeta, FLAG_MEMBERSHIP, FLAG_ORIGIN};
+
+    /// A provisioned drone completes the handshake and recovers the swarm key
+    #[test]
+    fn test_handshake_roundtrip() {
+        let credential = DeviceCredential::new([3; CREDENTIAL_SIZE]);
+        let mut authority = EnrollmentAuthority::new([9; SWARM_KEY_SIZE]);
+        authority.provision(42, credential.clone());
+
+        let request = join_request(&credential, 42, &[1; NONCE_SIZE]);
+        let accept = authority.accept(42, &request, &[2; NONCE_SIZE]).unwrap();
+        assert_eq!(open_accept(&credential, 42, &[1; NONCE_SIZE], &accept), Some(([9; SWARM_KEY_SIZE], 0)));
+
+        // Replayed accept for a different request nonce is refused
+        assert_eq!(open_accept(&credential, 42, &[5; NONCE_SIZE], &accept), None);
+    }
+
+    /// Impersonating another ID or using an unprovisioned credential fails
+    #[test]
+    fn test_handshake_rejects_wrong_credentials() {
+        let mut authority = EnrollmentAuthority::new([9; SWARM_KEY_SIZE]);
+        authority.provision(42, DeviceCredential::new([3; CREDENTIAL_SIZE]));
+
+        let forged = join_request(&DeviceCredential::new([4; CREDENTIAL_SIZE]), 42, &[1; NONCE_SIZE]);
+        assert!(authority.accept(42, &forged, &[2; NONCE_SIZE]).is_none());
+
+        let unknown = join_request(&DeviceCredential::new([3; CREDENTIAL_SIZE]), 43, &[1; NONCE_SIZE]);
+        assert!(authority.accept(43, &unknown, &[2; NONCE_SIZE]).is_none());
+    }
+
+    /// Membership tags bind a frame to its source ID
+    #[test]
+    fn test_membership_tag_binds_source() {
+        let alice = Membership::new([9; SWARM_KEY_SIZE], 1, 0);
+        let bob = Membership::new([9; SWARM_KEY_SIZE], 2, 0);
+
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_MEMBERSHIP, source: 1, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[], b"hello").unwrap();
+        let len = alice.tag_frame(&mut buf, len, 1);
+        assert!(bob.verify(&parse_frame(&buf[..len]).unwrap()));
+
+        // Rewriting the source ID invalidates the tag
+        buf[12] = 3;
+        assert!(!bob.verify(&parse_frame(&buf[..len]).unwrap()));
+    }
+
+    /// A rotated key reaches provisioned drones only and is not replayable
+    #[test]
+    fn test_rekey_excludes_deprovisioned() {
+        let credential = |byte| DeviceCredential::new([byte; CREDENTIAL_SIZE]);
+        let mut authority = EnrollmentAuthority::new([9; SWARM_KEY_SIZE]);
+        for id in 0..20 {
+            authority.provision(id, credential(id as u8));
+        }
+        authority.deprovision(7);
+
+        let bodies = authority.rekey([10; SWARM_KEY_SIZE]);
+        assert_eq!(bodies.len(), 2);
+        let open = |id, current| bodies.iter().find_map(|body| open_rekey(&credential(id as u8), id, current, body));
+        assert_eq!(open(3, 0), Some(([10; SWARM_KEY_SIZE], 1)));
+        assert_eq!(open(19, 0), Some(([10; SWARM_KEY_SIZE], 1)));
+
+        // The revoked drone has no entry, and nobody can open another's
+        assert_eq!(open(7, 0), None);
+        assert!(bodies.iter().all(|body| open_rekey(&credential(7), 3, 0, body).is_none()));
+
+        // A replayed REKEY does not roll a drone back
+        assert_eq!(open(3, 1), None);
+
+        // New enrollments receive the current generation
+        let request = join_request(&credential(3), 3, &[1; NONCE_SIZE]);
+        let accept = authority.accept(3, &request, &[2; NONCE_SIZE]).unwrap();
+        assert_eq!(open_accept(&credential(3), 3, &[1; NONCE_SIZE], &accept), Some(([10; SWARM_KEY_SIZE], 1)));
+    }
+
+    /// Tags under the old key are accepted only during the grace period
+    #[test]
+    fn test_rekey_grace_period() {
+        let stale = Membership::new([9; SWARM_KEY_SIZE], 1, 0);
+        let mut member = Membership::new([9; SWARM_KEY_SIZE], 2, 0);
+
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_MEMBERSHIP, source: 1, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[], b"hello").unwrap();
+        let len = stale.tag_frame(&mut buf, len, 1);
+
+        member.rekey([10; SWARM_KEY_SIZE], 1, Instant::now());
+        assert_eq!(member.generation(), 1);
+        assert!(member.verify(&parse_frame(&buf[..len]).unwrap()));
+
+        let mut expired = Membership::new([9; SWARM_KEY_SIZE], 2, 0);
+        expired.rekey([10; SWARM_KEY_SIZE], 1, Instant::now() - REKEY_GRACE);
+        assert!(!expired.verify(&parse_frame(&buf[..len]).unwrap()));
+    }
+}
@@ -0,0 +1,422 @@
+//! # Forward Error Correction
+//!
+//! Systematic Reed–Solomon erasure coding across a group of frames.
+//!
+//! A message is prefixed with its length, padded and split into `K` data
+//! shards, and `M` repair shards are computed over GF(2^8). Each shard travels
+//! in its own frame with an FEC section:
+//!
+//! ```text
+//! [group:4][index:1][data_shards:1][repair_shards:1][reserved:1]
+//! ```
+//!
+//! Any `K` of the `K + M` shards reconstruct the message, so bursts of up to
+//! `M` lost frames cost no round trip. Repair rows come from a Cauchy matrix,
+//! which keeps every `K`-row selection of `[I; C]` invertible.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::DroneId;
+
+//==============================================================================
+// PARAMETERS
+//==============================================================================
+
+/// Size of the FEC section in bytes
+pub(crate) const FEC_SECTION_SIZE: usize = 8;
+
+/// Length prefix prepended to the message before splitting
+const LENGTH_PREFIX: usize = 4;
+
+/// Most partially received groups held at once (oldest evicted)
+const MAX_GROUPS: usize = 32;
+
+/// How long a receiver waits for the rest of a group
+pub(crate) const GROUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Erasure code shape for one message class
+///
+/// Pick per class of traffic: e.g. `FecParams::new(4, 2)` for map tiles on a
+/// lossy link (survives any 2 of 6 frames lost, 50% overhead), `(8, 1)` for
+/// bulk data on a clean one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FecParams {
+    /// Frames carrying message data (K, at least 1)
+    pub data_shards: u8,
+
+    /// Extra frames carrying parity (M)
+    pub repair_shards: u8,
+}
+
+impl FecParams {
+    /// Code with `data_shards` data and `repair_shards` repair frames
+    pub const fn new(data_shards: u8, repair_shards: u8) -> Self {
+        FecParams { data_shards, repair_shards }
+    }
+
+    /// Total frames emitted per message
+    pub fn total_shards(&self) -> usize {
+        self.data_shards as usize + self.repair_shards as usize
+    }
+
+    /// K >= 1 and K + M <= 255 (shard indices fit one byte)
+    fn is_valid(&self) -> bool {
+        self.data_shards >= 1 && self.total_shards() <= 255
+    }
+}
+
+/// FEC section fields carried by each shard frame
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ShardHeader {
+    /// Sender-chosen identifier shared by all shards of a message
+    pub group: u32,
+
+    /// Position of this shard: `< K` data, `>= K` repair
+    pub index: u8,
+
+    /// Data shards (K) in the group
+    pub data_shards: u8,
+
+    /// Repair shards (M) in the group
+    pub repair_shards: u8,
+}
+
+impl ShardHeader {
+    /// Encode as the FEC section
+    pub fn to_bytes(self) -> [u8; FEC_SECTION_SIZE] {
+        let mut section = [0u8; FEC_SECTION_SIZE];
+        section[..4].copy_from_slice(&self.group.to_le_bytes());
+        section[4] = self.index;
+        section[5] = self.data_shards;
+        section[6] = self.repair_shards;
+        section
+    }
+
+    /// Decode the FEC section
+    pub fn from_bytes(section: &[u8]) -> Self {
+        ShardHeader {
+            group: u32::from_le_bytes(section[..4].try_into().unwrap()),
+            index: section[4],
+            data_shards: section[5],
+            repair_shards: section[6],
+        }
+    }
+
+    /// Code shape announced by the header
+    fn params(&self) -> FecParams {
+        FecParams::new(self.data_shards, self.repair_shards)
+    }
+}
+
+//==============================================================================
+// GF(2^8) ARITHMETIC
+//==============================================================================
+
+/// Field polynomial x^8 + x^4 + x^3 + x^2 + 1
+const FIELD_POLY: u16 = 0x11D;
+
+/// Exponent table (doubled so products need no modulo) and logarithm table
+const TABLES: ([u8; 512], [u8; 256]) = {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= FIELD_POLY;
+        }
+        i += 1;
+    }
+    (exp, log)
+};
+
+#[inline(always)]
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    TABLES.0[TABLES.1[a as usize] as usize + TABLES.1[b as usize] as usize]
+}
+
+#[inline(always)]
+fn gf_inv(a: u8) -> u8 {
+    debug_assert!(a != 0);
+    TABLES.0[255 - TABLES.1[a as usize] as usize]
+}
+
+/// `dst ^= c * src`, element-wise
+#[inline]
+fn gf_mul_add(dst: &mut [u8], src: &[u8], c: u8) {
+    if c == 0 {
+        return;
+    }
+    let log_c = TABLES.1[c as usize] as usize;
+    for (d, &s) in dst.iter_mut().zip(src) {
+        if s != 0 {
+            *d ^= TABLES.0[log_c + TABLES.1[s as usize] as usize];
+        }
+    }
+}
+
+/// Coefficient of data shard `column` in repair shard `row`
+///
+/// Cauchy matrix `1 / (x_row + y_column)` with `x = K + row`, `y = column`;
+/// the two sets are disjoint, so the sum is never zero.
+#[inline]
+fn cauchy(data_shards: usize, row: usize, column: usize) -> u8 {
+    gf_inv(((data_shards + row) ^ column) as u8)
+}
+
+//==============================================================================
+// ENCODING
+//==============================================================================
+
+/// Split `message` into `K` data shards and append `M` repair shards
+///
+/// All shards have the same length.
+pub(crate) fn encode(message: &[u8], params: FecParams) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+    if !params.is_valid() {
+        return Err("FEC needs at least one data shard and at most 255 shards".into());
+    }
+    let k = params.data_shards as usize;
+    let total = LENGTH_PREFIX + message.len();
+    let shard_len = total.div_ceil(k);
+
+    let mut padded = Vec::with_capacity(shard_len * k);
+    padded.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    padded.extend_from_slice(message);
+    padded.resize(shard_len * k, 0);
+
+    let mut shards: Vec<Vec<u8>> = padded.chunks_exact(shard_len).map(<[u8]>::to_vec).collect();
+    for row in 0..params.repair_shards as usize {
+        let mut repair = vec![0u8; shard_len];
+        for (column, data) in shards[..k].iter().enumerate() {
+            gf_mul_add(&mut repair, data, cauchy(k, row, column));
+        }
+        shards.push(repair);
+    }
+    Ok(shards)
+}
+
+/// Rebuild the message from exactly `K` shards given as `(index, bytes)`
+fn decode(params: FecParams, received: &[(usize, &[u8])]) -> Option<Vec<u8>> {
+    let k = params.data_shards as usize;
+    let shard_len = received.first()?.1.len();
+
+    // Rows of the generator matrix for the shards we hold
+    let mut matrix: Vec<Vec<u8>> = received
+        .iter()
+        .map(|&(index, _)| {
+            (0..k)
+                .map(|column| if index < k { (index == column) as u8 } else { cauchy(k, index - k, column) })
+                .collect()
+        })
+        .collect();
+    let inverse = invert(&mut matrix)?;
+
+    let mut message = vec![0u8; shard_len * k];
+    for (row, out) in message.chunks_exact_mut(shard_len).enumerate() {
+        for (column, &(_, shard)) in received.iter().enumerate() {
+            gf_mul_add(out, shard, inverse[row][column]);
+        }
+    }
+
+    let len = u32::from_le_bytes(message[..LENGTH_PREFIX].try_into().unwrap()) as usize;
+    if LENGTH_PREFIX + len > message.len() {
+        return None;
+    }
+    message.truncate(LENGTH_PREFIX + len);
+    message.drain(..LENGTH_PREFIX);
+    Some(message)
+}
+
+/// Gauss-Jordan inversion of a square matrix over GF(2^8)
+fn invert(matrix: &mut [Vec<u8>]) -> Option<Vec<Vec<u8>>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<u8>> = (0..n).map(|i| (0..n).map(|j| (i == j) as u8).collect()).collect();
+
+    for column in 0..n {
+        let pivot = (column..n).find(|&row| matrix[row][column] != 0)?;
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = gf_inv(matrix[column][column]);
+        for j in 0..n {
+            matrix[column][j] = gf_mul(matrix[column][j], scale);
+            inverse[column][j] = gf_mul(inverse[column][j], scale);
+        }
+        for row in 0..n {
+            let factor = matrix[row][column];
+            if row == column || factor == 0 {
+                continue;
+            }
+            for j in 0..n {
+                matrix[row][j] ^= gf_mul(factor, matrix[column][j]);
+                inverse[row][j] ^= gf_mul(factor, inverse[column][j]);
+            }
+        }
+    }
+    Some(inverse)
+}
+
+//==============================================================================
+// RECEIVER STATE
+//==============================================================================
+
+/// Shards of one message collected so far
+#[derive(Debug, Clone)]
+struct Group {
+    /// Code shape announced by the first shard
+    params: FecParams,
+
+    /// One slot per shard index
+    shards: Vec<Option<Vec<u8>>>,
+
+    /// Filled slots
+    received: usize,
+
+    /// Arrival of the first shard, for expiry
+    started: Instant,
+
+    /// Message already delivered; later shards are dropped
+    complete: bool,
+}
+
+/// Outcome of feeding one shard to the decoder
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ShardOutcome {
+    /// More shards are needed (or the shard was redundant)
+    Pending,
+
+    /// The message is complete; `recovered` if repair shards were used
+    Complete { message: Vec<u8>, recovered: bool },
+}
+
+/// Reassembles FEC groups from every sender in range
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FecDecoder {
+    /// Groups keyed by wire source and group id
+    groups: HashMap<(DroneId, u32), Group>,
+}
+
+impl FecDecoder {
+    /// Add a received shard
+    pub fn insert(&mut self, source: DroneId, header: ShardHeader, shard: &[u8], now: Instant) -> ShardOutcome {
+        let params = header.params();
+        if !params.is_valid() || header.index as usize >= params.total_shards() {
+            return ShardOutcome::Pending;
+        }
+
+        let key = (source, header.group);
+        if !self.groups.contains_key(&key) && self.groups.len() >= MAX_GROUPS {
+            let oldest = self.groups.iter().min_by_key(|(_, group)| group.started).map(|(&key, _)| key);
+            oldest.map(|key| self.groups.remove(&key));
+        }
+        let group = self.groups.entry(key).or_insert_with(|| Group {
+            params,
+            shards: vec![None; params.total_shards()],
+            received: 0,
+            started: now,
+            complete: false,
+        });
+
+        let index = header.index as usize;
+        let consistent = group.params == params
+            && group.shards.iter().flatten().all(|held| held.len() == shard.len());
+        if group.complete || !consistent || group.shards[index].is_some() {
+            return ShardOutcome::Pending;
+        }
+        group.shards[index] = Some(shard.to_vec());
+        group.received += 1;
+
+        let k = params.data_shards as usize;
+        if group.received < k {
+            return ShardOutcome::Pending;
+        }
+
+        let received: Vec<(usize, &[u8])> = group.shards
+            .iter()
+            .enumerate()
+            .filter_map(|(index, shard)| shard.as_deref().map(|bytes| (index, bytes)))
+            .take(k)
+            .collect();
+        let recovered = received.iter().any(|&(index, _)| index >= k);
+        let message = decode(params, &received);
+
+        // Keep a tombstone so stragglers of this group are not reassembled again
+        group.complete = true;
+        group.shards = Vec::new();
+        match message {
+            Some(message) => ShardOutcome::Complete { message, recovered },
+            None => ShardOutcome::Pending,
+        }
+    }
+
+    /// Drop groups older than `timeout`, returning how many were incomplete
+    pub fn expire(&mut self, now: Instant, timeout: Duration) -> usize {
+        let mut failed = 0;
+        self.groups.retain(|_, group| {
+            let keep = now.duration_since(group.started) < timeout;
+            failed += (!keep && !group.complete) as usize;
+            keep
+        });
+        failed
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every choice of K surviving shards reconstructs the message
+    #[test]
+    fn test_any_k_of_n_reconstructs() {
+        let message: Vec<u8> = (0..1000u32).map(|i| (i * 7 + 3) as u8).collect();
+        let params = FecParams::new(4, 3);
+        let shards = encode(&message, params).unwrap();
+        assert_eq!(shards.len(), 7);
+        assert_eq!(&shards[0][4..], &message[..shards[0].len() - 4]);
+
+        for mask in 0u32..(1 << 7) {
+            if mask.count_ones() != 4 {
+                continue;
+            }
+            let received: Vec<(usize, &[u8])> = (0..7)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| (i, shards[i].as_slice()))
+                .collect();
+            assert_eq!(decode(params, &received).as_deref(), Some(message.as_slice()), "mask {mask:07b}");
+        }
+    }
+
+    /// The decoder delivers once, reports repair use and expires stale groups
+    #[test]
+    fn test_decoder_reassembles_group() {
+        let params = FecParams::new(2, 2);
+        let shards = encode(b"waypoints", params).unwrap();
+        let header = |index| ShardHeader { group: 9, index, data_shards: 2, repair_shards: 2 };
+        let now = Instant::now();
+
+        let mut decoder = FecDecoder::default();
+        assert_eq!(decoder.insert(5, header(0), &shards[0], now), ShardOutcome::Pending);
+        assert_eq!(decoder.insert(5, header(0), &shards[0], now), ShardOutcome::Pending);
+        assert_eq!(
+            decoder.insert(5, header(3), &shards[3], now),
+            ShardOutcome::Complete { message: b"waypoints".to_vec(), recovered: true },
+        );
+        assert_eq!(decoder.insert(5, header(1), &shards[1], now), ShardOutcome::Pending);
+
+        decoder.insert(6, header(2), &shards[2], now);
+        assert_eq!(decoder.expire(now + Duration::from_secs(2), Duration::from_secs(1)), 1);
+        assert!(encode(b"x", FecParams::new(0, 1)).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! # Fragmentation and Reassembly
+//!
+//! Payloads larger than one frame are split into numbered fragments, each
+//! carried in its own frame with a fragment section:
+//!
+//! ```text
+//! [message_id:4][index:2][count:2]
+//! ```
+//!
+//! Receivers collect fragments per `(source, message_id)` and deliver the
+//! message once every fragment arrived. Partial messages expire after a
+//! timeout, and each sender may only pin a bounded amount of receiver memory
+//! (the slots for fragments still missing count too), in a bounded number of
+//! messages, so a peer that never finishes its messages cannot exhaust a drone.
+//! A cap on messages across all senders holds against spoofed source IDs.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of the fragment section in bytes
+pub(crate) const FRAGMENT_SECTION_SIZE: usize = 8;
+
+/// Receiver-side reassembly limits
+#[derive(Debug, Copy, Clone)]
+pub struct ReassemblyConfig {
+    /// How long a partial message may wait for its missing fragments
+    pub timeout: Duration,
+
+    /// Bytes of partial messages held per sender, fragment slots included
+    /// (oldest evicted beyond this)
+    pub max_bytes_per_sender: usize,
+
+    /// Messages reassembled at once per sender (oldest evicted beyond this)
+    pub max_partials_per_sender: usize,
+
+    /// Messages reassembled at once across all senders, which bounds what
+    /// spoofed source IDs can pin (oldest evicted beyond this)
+    pub max_partials: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> Self {
+        ReassemblyConfig {
+            timeout: Duration::from_secs(2),
+            max_bytes_per_sender: 1 << 20,
+            max_partials_per_sender: 16,
+            max_partials: 256,
+        }
+    }
+}
+
+//==============================================================================
+// FRAGMENT SECTION
+//==============================================================================
+
+/// Fragment section fields
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FragmentHeader {
+    /// Sender-chosen identifier shared by all fragments of a message
+    pub message_id: u32,
+
+    /// Position of this fragment
+    pub index: u16,
+
+    /// Fragments in the message
+    pub count: u16,
+}
+
+impl FragmentHeader {
+    /// Encode as the fragment section
+    pub fn to_bytes(self) -> [u8; FRAGMENT_SECTION_SIZE] {
+        let mut section = [0u8; FRAGMENT_SECTION_SIZE];
+        section[..4].copy_from_slice(&self.message_id.to_le_bytes());
+        section[4..6].copy_from_slice(&self.index.to_le_bytes());
+        section[6..8].copy_from_slice(&self.count.to_le_bytes());
+        section
+    }
+
+    /// Decode the fragment section
+    pub fn from_bytes(section: &[u8]) -> Self {
+        FragmentHeader {
+            message_id: u32::from_le_bytes(section[..4].try_into().unwrap()),
+            index: u16::from_le_bytes(section[4..6].try_into().unwrap()),
+            count: u16::from_le_bytes(section[6..8].try_into().unwrap()),
+        }
+    }
+}
+
+//==============================================================================
+// RECEIVER STATE
+//==============================================================================
+
+/// Receiver memory charged per fragment slot of a partial message, whether
+/// or not the fragment has arrived
+pub(crate) const SLOT_SIZE: usize = std::mem::size_of::<Option<Vec<u8>>>();
+
+/// Fragments of one message collected so far
+#[derive(Debug, Clone)]
+struct Partial {
+    /// One slot per fragment index
+    fragments: Vec<Option<Vec<u8>>>,
+
+    /// Filled slots
+    received: usize,
+
+    /// Bytes charged to the sender: slots plus fragments held
+    bytes: usize,
+
+    /// Arrival of the first fragment, for expiry
+    started: Instant,
+
+    /// Message already delivered; later copies are dropped
+    complete: bool,
+}
+
+/// Receiver memory pinned by one sender
+#[derive(Debug, Copy, Clone, Default)]
+struct Usage {
+    /// Bytes charged for its partial messages
+    bytes: usize,
+
+    /// Partial messages and tombstones held
+    partials: usize,
+}
+
+/// Reassembles fragmented messages from every sender in range
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Reassembler {
+    /// Partial messages keyed by wire source and message id
+    partials: HashMap<(DroneId, u32), Partial>,
+
+    /// Running totals per sender, kept in step with `partials`
+    usage: HashMap<DroneId, Usage>,
+}
+
+impl Reassembler {
+    /// Add a received fragment, returning the message once complete
+    ///
+    /// The second value counts partial messages evicted to stay within the
+    /// sender's memory budget or the partial message caps.
+    pub fn insert(
+        &mut self,
+        source: DroneId,
+        header: FragmentHeader,
+        fragment: &[u8],
+        now: Instant,
+        config: &ReassemblyConfig,
+    ) -> (Option<Vec<u8>>, usize) {
+        // Slots are charged up front and every fragment holds a byte at least,
+        // so counts that could never fit the budget are refused outright
+        let count = header.count as usize;
+        let index = header.index as usize;
+        if fragment.is_empty() || index >= count || count * (SLOT_SIZE + 1) > config.max_bytes_per_sender {
+            return (None, 0);
+        }
+
+        let key = (source, header.message_id);
+        let is_new = match self.partials.get(&key) {
+            Some(partial) if partial.complete || partial.fragments.len() != count || partial.fragments[index].is_some() => {
+                return (None, 0);
+            }
+            Some(_) => false,
+            None => true,
+        };
+        let incoming = fragment.len() + if is_new { count * SLOT_SIZE } else { 0 };
+        let evicted = self.make_room(key, incoming, is_new, config);
+
+        let usage = self.usage.get(&source).copied().unwrap_or_default();
+        if usage.bytes + incoming > config.max_bytes_per_sender {
+            // This message alone exceeds the budget - give up on it
+            return (None, evicted + self.remove(key) as usize);
+        }
+        if is_new && (usage.partials >= config.max_partials_per_sender || self.partials.len() >= config.max_partials) {
+            return (None, evicted);
+        }
+
+        let usage = self.usage.entry(source).or_default();
+        usage.bytes += incoming;
+        usage.partials += is_new as usize;
+        let partial = self.partials.entry(key).or_insert_with(|| Partial {
+            fragments: vec![None; count],
+            received: 0,
+            bytes: 0,
+            started: now,
+            complete: false,
+        });
+        partial.fragments[index] = Some(fragment.to_vec());
+        partial.received += 1;
+        partial.bytes += incoming;
+
+        if partial.received < partial.fragments.len() {
+            return (None, evicted);
+        }
+
+        // Keep a tombstone so duplicate fragments do not start a new message
+        let message = std::mem::take(&mut partial.fragments).into_iter().flatten().flatten().collect();
+        partial.complete = true;
+        usage.bytes -= std::mem::take(&mut partial.bytes);
+        (Some(message), evicted)
+    }
+
+    /// Drop messages older than `timeout`, returning how many were incomplete
+    pub fn expire(&mut self, now: Instant, timeout: Duration) -> usize {
+        let expired: Vec<(DroneId, u32)> = self.partials.iter()
+            .filter(|(_, partial)| now.duration_since(partial.started) >= timeout)
+            .map(|(&key, _)| key)
+            .collect();
+        expired.into_iter().filter(|&key| self.remove(key)).count()
+    }
+
+    /// Evict older entries until a fragment of `incoming` bytes for `key`
+    /// fits: first the sender's own when its budget or cap is reached, then
+    /// anyone's when the total cap is. Tombstones go before incomplete messages.
+    fn make_room(&mut self, key: (DroneId, u32), incoming: usize, is_new: bool, config: &ReassemblyConfig) -> usize {
+        let source = key.0;
+        let mut evicted = 0;
+        loop {
+            let usage = self.usage.get(&source).copied().unwrap_or_default();
+            let sender_full = usage.bytes + incoming > config.max_bytes_per_sender
+                || (is_new && usage.partials >= config.max_partials_per_sender);
+            let total_full = is_new && self.partials.len() >= config.max_partials;
+            if !sender_full && !total_full {
+                return evicted;
+            }
+            let oldest = self.partials
+                .iter()
+                .filter(|&(&other, _)| other != key && (!sender_full || other.0 == source))
+                .min_by_key(|(_, partial)| (!partial.complete, partial.started))
+                .map(|(&other, _)| other);
+            match oldest {
+                Some(other) => evicted += self.remove(other) as usize,
+                None => return evicted,
+            }
+        }
+    }
+
+    /// Drop an entry and release what it pinned, returning true if it was
+    /// an incomplete message
+    fn remove(&mut self, key: (DroneId, u32)) -> bool {
+        let Some(partial) = self.partials.remove(&key) else {
+            return false;
+        };
+        if let Some(usage) = self.usage.get_mut(&key.0) {
+            usage.bytes -= partial.bytes;
+            usage.partials -= 1;
+            if usage.partials == 0 {
+                self.usage.remove(&key.0);
+            }
+        }
+        !partial.complete
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(message_id: u32, index: u16, count: u16) -> FragmentHeader {
+        FragmentHeader { message_id, index, count }
+    }
+
+    /// Out-of-order fragments reassemble once; duplicates and stragglers are dropped
+    #[test]
+    fn test_reassembly_out_of_order() {
+        let config = ReassemblyConfig::default();
+        let mut reassembler = Reassembler::default();
+        let now = Instant::now();
+
+        assert_eq!(reassembler.insert(3, header(1, 2, 3), b"ef", now, &config), (None, 0));
+        assert_eq!(reassembler.insert(3, header(1, 0, 3), b"ab", now, &config), (None, 0));
+        assert_eq!(reassembler.insert(3, header(1, 0, 3), b"ab", now, &config), (None, 0));
+        assert_eq!(reassembler.insert(3, header(1, 1, 3), b"cd", now, &config), (Some(b"abcdef".to_vec()), 0));
+        assert_eq!(reassembler.insert(3, header(1, 1, 3), b"cd", now, &config), (None, 0));
+        assert_eq!(reassembler.insert(3, header(2, 3, 3), b"zz", now, &config), (None, 0));
+
+        assert_eq!(reassembler.insert(4, header(1, 0, 2), b"x", now, &config), (None, 0));
+        assert_eq!(reassembler.expire(now + config.timeout, config.timeout), 1);
+    }
+
+    /// A sender exceeding its memory budget loses its oldest partial message
+    #[test]
+    fn test_per_sender_memory_limit() {
+        let config = ReassemblyConfig { max_bytes_per_sender: 2 * SLOT_SIZE + 10, ..ReassemblyConfig::default() };
+        let mut reassembler = Reassembler::default();
+        let now = Instant::now();
+
+        reassembler.insert(3, header(1, 0, 2), &[0; 6], now, &config);
+        reassembler.insert(9, header(1, 0, 2), &[0; 6], now, &config);
+        let later = now + Duration::from_millis(1);
+        assert_eq!(reassembler.insert(3, header(2, 0, 2), &[0; 6], later, &config), (None, 1));
+        assert_eq!(reassembler.insert(3, header(1, 1, 2), &[0; 2], later, &config).0, None);
+        assert_eq!(reassembler.insert(9, header(1, 1, 2), &[1; 2], later, &config).0, Some([0, 0, 0, 0, 0, 0, 1, 1].to_vec()));
+    }
+
+    /// Tiny fragments announcing huge counts cannot pin unbounded memory
+    #[test]
+    fn test_slot_and_partial_caps() {
+        let config = ReassemblyConfig { max_partials_per_sender: 2, max_partials: 3, ..ReassemblyConfig::default() };
+        let mut reassembler = Reassembler::default();
+        let now = Instant::now();
+
+        // Empty fragments and counts whose slots alone exceed the budget
+        assert_eq!(reassembler.insert(3, header(1, 0, 2), b"", now, &config), (None, 0));
+        assert_eq!(reassembler.insert(3, header(1, 0, u16::MAX), b"x", now, &config), (None, 0));
+        assert!(reassembler.partials.is_empty());
+
+        // Slots count against the budget: 1 MiB holds few 40000-slot messages
+        let big = ReassemblyConfig { max_partials_per_sender: 100, ..config };
+        let evicted: usize = (0..4).map(|id| reassembler.insert(3, header(id, 0, 40_000), b"x", now, &big).1).sum();
+        assert_eq!((reassembler.partials.len(), evicted), (1, 3));
+        assert_eq!(reassembler.usage[&3].bytes, 40_000 * SLOT_SIZE + 1);
+
+        // Per-sender and total caps evict the oldest
+        let mut reassembler = Reassembler::default();
+        for id in 0..3u32 {
+            let at = now + Duration::from_millis(id as u64);
+            assert_eq!(reassembler.insert(3, header(id, 0, 2), b"x", at, &config), (None, (id == 2) as usize));
+        }
+        assert_eq!(reassembler.usage[&3].partials, 2);
+        let later = now + Duration::from_millis(10);
+        reassembler.insert(4, header(0, 0, 2), b"x", later, &config);
+        assert_eq!(reassembler.insert(5, header(0, 0, 2), b"x", later, &config), (None, 1));
+        assert_eq!(reassembler.partials.len(), 3);
+
+        // Expiry releases everything
+        assert_eq!(reassembler.expire(later + config.timeout, config.timeout), 3);
+        assert!(reassembler.usage.is_empty());
+    }
+}
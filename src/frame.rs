@@ -0,0 +1,754 @@
+//! # OVP Wire Format
+//!
+//! Header flag definitions and a safe, allocation-free view over received frames.
+//!
+//! The second header word of every OVP frame is split into two halves:
+//!
+//! ```text
+//! [magic:4][target_field:2][flags:2][payload_len:4][sections][targets:words*8][payload:payload_len][trailers]
+//! ```
+//!
+//! The target field gives the encoding and size of the target section (see
+//! `targets`); for a plain list it is simply the number of targets.
+//!
+//! A frame with `flags == 0` is byte-for-byte identical to the original v2 layout,
+//! so plain emissions keep their 12-byte header. Each flag bit switches on one
+//! optional fixed-size section (between header and targets) or trailer (after
+//! the payload). Sections and trailers appear in the order their flags are
+//! declared below, except that the signature and then the checksum always
+//! come last: each covers every byte in front of it.
+
+use std::ops::Range;
+
+use crate::{DroneId, OVP_MAGIC};
+use crate::checksum::{verify_checksum, CHECKSUM_SIZE};
+use crate::enrollment::MEMBERSHIP_TAG_SIZE;
+use crate::fec::{ShardHeader, FEC_SECTION_SIZE};
+use crate::fragment::{FragmentHeader, FRAGMENT_SECTION_SIZE};
+use crate::ordering::{MessageType, ORDERING_SECTION_SIZE};
+use crate::position::{Position, POSITION_SECTION_SIZE};
+use crate::priority::{Priority, PRIORITY_SECTION_SIZE};
+use crate::relay::{HopCount, HOP_SECTION_SIZE};
+use crate::scan;
+use crate::signing::{KeyId, SIGNATURE_TRAILER_SIZE};
+use crate::targets::{self, TargetEncoding};
+
+//==============================================================================
+// HEADER LAYOUT
+//==============================================================================
+
+/// Fixed header size: magic + target_count/flags + payload_len
+pub(crate) const HEADER_SIZE: usize = 12;
+
+/// Mask selecting the target field from the second header word
+pub(crate) const TARGET_FIELD_MASK: u32 = 0xFFFF;
+
+/// Shift selecting the flags from the second header word
+pub(crate) const FLAGS_SHIFT: u32 = 16;
+
+/// Frame carries an Ed25519 signature trailer: `[key_id:8][timestamp_us:8][signature:64]`
+pub(crate) const FLAG_SIGNED: u16 = 1 << 0;
+
+/// Frame carries an origin section: `[source:8]`
+pub(crate) const FLAG_ORIGIN: u16 = 1 << 1;
+
+/// Payload is an OVP control message `[kind:1][body]`, consumed by the protocol
+pub(crate) const FLAG_CONTROL: u16 = 1 << 2;
+
+/// Frame carries a swarm membership tag trailer: `[tag:16]`
+pub(crate) const FLAG_MEMBERSHIP: u16 = 1 << 3;
+
+/// Target list and origin hold per-epoch pseudonyms; section: `[epoch:4]`
+pub(crate) const FLAG_PSEUDONYMOUS: u16 = 1 << 4;
+
+/// Frame carries a CRC-32C trailer over all preceding bytes: `[crc:4]`
+pub(crate) const FLAG_CHECKSUM: u16 = 1 << 5;
+
+/// Frame carries a per-sender sequence number; section: `[sequence:4]`
+pub(crate) const FLAG_SEQUENCE: u16 = 1 << 6;
+
+/// Targets must answer with an ACK control message (requires origin and sequence)
+pub(crate) const FLAG_ACK_REQUEST: u16 = 1 << 7;
+
+/// Broadcast belongs to the sender's NACK-repaired stream (requires origin and sequence)
+pub(crate) const FLAG_RELIABLE_BROADCAST: u16 = 1 << 8;
+
+/// Payload is one shard of an erasure-coded message (requires origin);
+/// section: `[group:4][index:1][data_shards:1][repair_shards:1][reserved:1]`
+pub(crate) const FLAG_FEC: u16 = 1 << 9;
+
+/// Payload is one piece of a larger message (requires origin);
+/// section: `[message_id:4][index:2][count:2]`
+pub(crate) const FLAG_FRAGMENT: u16 = 1 << 10;
+
+/// Frame belongs to an ordered per-type stream (requires origin and sequence);
+/// section: `[message_type:2]`
+pub(crate) const FLAG_ORDERED: u16 = 1 << 11;
+
+/// Frame has a priority class other than `Normal`; section: `[priority:1]`
+pub(crate) const FLAG_PRIORITY: u16 = 1 << 12;
+
+/// Frame may be relayed beyond one hop; section: `[ttl:1][hops:1]`
+/// (rewritten by relays, so tags and signatures cover it zeroed)
+pub(crate) const FLAG_MULTIHOP: u16 = 1 << 13;
+
+/// Frame carries the position of the drone that transmitted it;
+/// section: `[x:4][y:4][z:4]` (rewritten by relays like the hop section)
+pub(crate) const FLAG_POSITION: u16 = 1 << 14;
+
+/// Target list holds multicast group identifiers instead of drone IDs
+/// (the last flag bit - further options need a section of their own)
+pub(crate) const FLAG_GROUP: u16 = 1 << 15;
+
+/// Sections relays rewrite; they are adjacent and last
+const RELAY_SECTIONS: u16 = FLAG_MULTIHOP | FLAG_POSITION;
+
+//==============================================================================
+// CONTROL MESSAGE KINDS
+//==============================================================================
+
+/// Enrollment request from a joining drone
+pub(crate) const CONTROL_JOIN_REQUEST: u8 = 1;
+
+/// Enrollment answer from the authority carrying the wrapped swarm key
+pub(crate) const CONTROL_JOIN_ACCEPT: u8 = 2;
+
+/// Signed revocation of a drone ID
+pub(crate) const CONTROL_REVOKE: u8 = 3;
+
+/// Acknowledgement of a frame that carried `FLAG_ACK_REQUEST`
+pub(crate) const CONTROL_ACK: u8 = 4;
+
+/// Request to rebroadcast frames missing from a reliable-broadcast stream
+pub(crate) const CONTROL_NACK: u8 = 5;
+
+/// Periodic neighbour discovery beacon
+pub(crate) const CONTROL_HELLO: u8 = 6;
+
+/// Broadcast search for a route to a drone
+pub(crate) const CONTROL_ROUTE_REQUEST: u8 = 7;
+
+/// Route answer passed back toward the requester
+pub(crate) const CONTROL_ROUTE_REPLY: u8 = 8;
+
+/// Notice that routes through the sender broke
+pub(crate) const CONTROL_ROUTE_ERROR: u8 = 9;
+
+/// Routed frame handed to the next hop
+pub(crate) const CONTROL_ROUTED: u8 = 10;
+
+/// Frame addressed to a place, handed to the next hop
+pub(crate) const CONTROL_GEO: u8 = 11;
+
+/// Frame for every drone inside a region
+pub(crate) const CONTROL_GEOCAST: u8 = 12;
+
+/// Delay-tolerant bundle carried until its lifetime runs out
+pub(crate) const CONTROL_DTN_BUNDLE: u8 = 13;
+
+/// Summary vector of the bundles a drone holds
+pub(crate) const CONTROL_DTN_SUMMARY: u8 = 14;
+
+/// Request for bundles missing from our store
+pub(crate) const CONTROL_DTN_REQUEST: u8 = 15;
+
+/// Reliable-broadcast session heartbeat carrying the sender's newest sequence number
+pub(crate) const CONTROL_SESSION: u8 = 16;
+
+/// Rotated swarm key wrapped for each drone still provisioned
+pub(crate) const CONTROL_REKEY: u8 = 17;
+
+/// Control kinds whose proofs bind real drone IDs and so bypass ghost mode
+#[inline(always)]
+pub(crate) fn binds_real_ids(kind: u8) -> bool {
+    matches!(kind, CONTROL_JOIN_REQUEST | CONTROL_JOIN_ACCEPT | CONTROL_REVOKE | CONTROL_REKEY)
+}
+
+/// Enrollment handshake kinds, exempt from the membership requirement: they
+/// carry their own proofs and a joining drone has no tag yet
+#[inline(always)]
+pub(crate) fn is_enrollment(kind: u8) -> bool {
+    matches!(kind, CONTROL_JOIN_REQUEST | CONTROL_JOIN_ACCEPT | CONTROL_REVOKE)
+}
+
+//==============================================================================
+// FRAME METADATA
+//==============================================================================
+
+/// Header fields written by [`write_frame`] beyond targets and payload
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct FrameMeta {
+    /// Header flag bits
+    pub flags: u16,
+
+    /// Sending drone, written when `FLAG_ORIGIN` is set
+    pub source: DroneId,
+
+    /// Pseudonym epoch, written when `FLAG_PSEUDONYMOUS` is set
+    pub epoch: u32,
+
+    /// Sender sequence number, written when `FLAG_SEQUENCE` is set
+    pub sequence: u32,
+
+    /// Erasure-coding position, written when `FLAG_FEC` is set
+    pub shard: ShardHeader,
+
+    /// Fragment position, written when `FLAG_FRAGMENT` is set
+    pub fragment: FragmentHeader,
+
+    /// Ordered stream type, written when `FLAG_ORDERED` is set
+    pub message_type: MessageType,
+
+    /// Traffic class, written when `FLAG_PRIORITY` is set
+    pub priority: Priority,
+
+    /// Relay budget, written when `FLAG_MULTIHOP` is set
+    pub hop: HopCount,
+
+    /// Transmitter position, written when `FLAG_POSITION` is set (None = unknown)
+    pub position: Option<Position>,
+}
+
+/// Decoded view over a received OVP frame
+///
+/// Borrows the receive buffer - no bytes are copied. Only produced for frames
+/// whose magic, lengths and trailers are structurally consistent.
+pub(crate) struct FrameView<'a> {
+    /// Header flag bits
+    pub flags: u16,
+
+    /// Sending drone if `FLAG_ORIGIN` is set (a pseudonym if `FLAG_PSEUDONYMOUS`)
+    pub source: Option<DroneId>,
+
+    /// Pseudonym epoch if `FLAG_PSEUDONYMOUS` is set
+    pub epoch: Option<u32>,
+
+    /// Sender sequence number if `FLAG_SEQUENCE` is set
+    pub sequence: Option<u32>,
+
+    /// Erasure-coding position if `FLAG_FEC` is set
+    pub shard: Option<ShardHeader>,
+
+    /// Fragment position if `FLAG_FRAGMENT` is set
+    pub fragment: Option<FragmentHeader>,
+
+    /// Ordered stream type if `FLAG_ORDERED` is set
+    pub message_type: Option<MessageType>,
+
+    /// Traffic class (`Normal` unless `FLAG_PRIORITY` is set)
+    pub priority: Priority,
+
+    /// Relay budget if `FLAG_MULTIHOP` is set
+    pub hop: Option<HopCount>,
+
+    /// Transmitter position if `FLAG_POSITION` is set and the position is known
+    pub position: Option<Position>,
+
+    /// Raw target section (`words * 8` bytes), encoded as `target_encoding` says
+    pub targets: &'a [u8],
+
+    /// List, bitmap or Bloom filter
+    pub target_encoding: TargetEncoding,
+
+    /// Application payload
+    pub payload: &'a [u8],
+
+    /// Offset of the payload from the start of the frame
+    pub payload_offset: usize,
+
+    /// Membership tag trailer if `FLAG_MEMBERSHIP` is set
+    pub membership_tag: Option<&'a [u8]>,
+
+    /// Bytes covered by the membership tag: header through payload
+    pub membership_region: &'a [u8],
+
+    /// Key id, signed timestamp and signature if `FLAG_SIGNED` is set
+    pub signature: Option<(KeyId, u64, &'a [u8])>,
+
+    /// Bytes covered by the signature: everything before the signature itself
+    pub signed_region: &'a [u8],
+
+    /// False if `FLAG_CHECKSUM` is set and the CRC-32C does not match
+    pub checksum_ok: bool,
+}
+
+impl FrameView<'_> {
+    /// Check whether `id` appears in the target list (broadcast matches everyone)
+    ///
+    /// Pseudonymous frames are scanned in constant time so the position of a
+    /// match cannot be inferred from timing.
+    #[inline(always)]
+    pub fn is_addressed_to(&self, id: DroneId) -> bool {
+        if self.targets.is_empty() {
+            return true;
+        }
+        if self.target_encoding != TargetEncoding::List {
+            return targets::matches(self.target_encoding, self.targets, id);
+        }
+        if self.has(FLAG_PSEUDONYMOUS) {
+            return contains_target_ct(self.targets, id);
+        }
+        scan::contains_id(self.targets, id)
+    }
+
+    /// Check a header flag
+    #[inline(always)]
+    pub fn has(&self, flag: u16) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// True for enrollment handshake frames (see `is_enrollment`)
+    #[inline(always)]
+    pub fn is_enrollment(&self) -> bool {
+        self.has(FLAG_CONTROL) && self.payload.first().is_some_and(|&kind| is_enrollment(kind))
+    }
+}
+
+//==============================================================================
+// LAYOUT HELPERS
+//==============================================================================
+
+/// Split the second header word into `(target_field, flags)`
+#[inline(always)]
+pub(crate) fn split_count_word(word: u32) -> (usize, u16) {
+    ((word & TARGET_FIELD_MASK) as usize, (word >> FLAGS_SHIFT) as u16)
+}
+
+/// Combine a target field and flags into the second header word
+#[inline(always)]
+pub(crate) fn count_word(target_field: usize, flags: u16) -> u32 {
+    (target_field as u32 & TARGET_FIELD_MASK) | ((flags as u32) << FLAGS_SHIFT)
+}
+
+/// Total size of the sections between header and targets implied by `flags`
+#[inline(always)]
+pub(crate) fn section_size(flags: u16) -> usize {
+    let mut size = 0;
+    if flags & FLAG_ORIGIN != 0 {
+        size += 8;
+    }
+    if flags & FLAG_PSEUDONYMOUS != 0 {
+        size += 4;
+    }
+    if flags & FLAG_SEQUENCE != 0 {
+        size += 4;
+    }
+    if flags & FLAG_FEC != 0 {
+        size += FEC_SECTION_SIZE;
+    }
+    if flags & FLAG_FRAGMENT != 0 {
+        size += FRAGMENT_SECTION_SIZE;
+    }
+    if flags & FLAG_ORDERED != 0 {
+        size += ORDERING_SECTION_SIZE;
+    }
+    if flags & FLAG_PRIORITY != 0 {
+        size += PRIORITY_SECTION_SIZE;
+    }
+    if flags & FLAG_MULTIHOP != 0 {
+        size += HOP_SECTION_SIZE;
+    }
+    if flags & FLAG_POSITION != 0 {
+        size += POSITION_SECTION_SIZE;
+    }
+    size
+}
+
+/// Offset of the section selected by `flag` (the sections of lower flag bits precede it)
+#[inline(always)]
+fn section_offset(flags: u16, flag: u16) -> usize {
+    HEADER_SIZE + section_size(flags & (flag - 1))
+}
+
+/// Bytes of the sections relays rewrite, which tags and signatures cover zeroed
+#[inline(always)]
+pub(crate) fn relay_sections(flags: u16) -> Range<usize> {
+    let start = section_offset(flags, FLAG_MULTIHOP);
+    start..start + section_size(flags & RELAY_SECTIONS)
+}
+
+/// Overwrite the hop section of a `FLAG_MULTIHOP` frame in place
+#[inline(always)]
+pub(crate) fn write_hop_count(frame: &mut [u8], flags: u16, hop: HopCount) {
+    let offset = section_offset(flags, FLAG_MULTIHOP);
+    frame[offset..offset + HOP_SECTION_SIZE].copy_from_slice(&hop.to_bytes());
+}
+
+/// Overwrite the position section of a `FLAG_POSITION` frame in place
+#[inline(always)]
+pub(crate) fn write_position(frame: &mut [u8], flags: u16, position: Option<Position>) {
+    let offset = section_offset(flags, FLAG_POSITION);
+    frame[offset..offset + POSITION_SECTION_SIZE].copy_from_slice(&Position::to_wire(position));
+}
+
+/// Scan a raw target list for `id` without early exit
+///
+/// Every entry is compared and the results are OR-ed together, so the scan
+/// time depends only on the list length, never on where (or whether) the
+/// identifier appears.
+#[inline(always)]
+pub(crate) fn contains_target_ct(targets: &[u8], id: DroneId) -> bool {
+    let mut found = 0u64;
+    for target in targets.chunks_exact(8) {
+        let diff = u64::from_le_bytes(target.try_into().unwrap()) ^ id;
+        // 1 if diff == 0, else 0 - branch free
+        found |= 1 ^ ((diff | diff.wrapping_neg()) >> 63);
+    }
+    found != 0
+}
+
+/// Total trailer size implied by a set of header flags
+#[inline(always)]
+pub(crate) fn trailer_size(flags: u16) -> usize {
+    let mut size = 0;
+    if flags & FLAG_MEMBERSHIP != 0 {
+        size += MEMBERSHIP_TAG_SIZE;
+    }
+    if flags & FLAG_SIGNED != 0 {
+        size += SIGNATURE_TRAILER_SIZE;
+    }
+    if flags & FLAG_CHECKSUM != 0 {
+        size += CHECKSUM_SIZE;
+    }
+    size
+}
+
+//==============================================================================
+// PARSING
+//==============================================================================
+
+/// Parse a frame into a [`FrameView`], validating every length field
+///
+/// Returns None for non-OVP traffic, truncated frames and frames whose
+/// sections hold invalid values.
+pub(crate) fn parse_frame(frame: &[u8]) -> Option<FrameView<'_>> {
+    if frame.len() < HEADER_SIZE {
+        return None;
+    }
+
+    let magic = u32::from_le_bytes(frame[0..4].try_into().unwrap());
+    if magic != OVP_MAGIC {
+        return None;
+    }
+
+    let (target_field, flags) = split_count_word(u32::from_le_bytes(frame[4..8].try_into().unwrap()));
+    let (target_encoding, target_words) = targets::split_field(target_field)?;
+    let payload_len = u32::from_le_bytes(frame[8..12].try_into().unwrap()) as usize;
+
+    let targets_start = HEADER_SIZE + section_size(flags);
+    let targets_end = targets_start + target_words * 8;
+    let payload_end = targets_end.checked_add(payload_len)?;
+    if frame.len() < payload_end + trailer_size(flags) {
+        return None;
+    }
+
+    // Sections, in flag declaration order
+    let mut offset = HEADER_SIZE;
+    let source = (flags & FLAG_ORIGIN != 0).then(|| {
+        offset += 8;
+        DroneId::from_le_bytes(frame[offset - 8..offset].try_into().unwrap())
+    });
+    let epoch = (flags & FLAG_PSEUDONYMOUS != 0).then(|| {
+        offset += 4;
+        u32::from_le_bytes(frame[offset - 4..offset].try_into().unwrap())
+    });
+    let sequence = (flags & FLAG_SEQUENCE != 0).then(|| {
+        offset += 4;
+        u32::from_le_bytes(frame[offset - 4..offset].try_into().unwrap())
+    });
+    let shard = (flags & FLAG_FEC != 0).then(|| {
+        offset += FEC_SECTION_SIZE;
+        ShardHeader::from_bytes(&frame[offset - FEC_SECTION_SIZE..offset])
+    });
+    let fragment = (flags & FLAG_FRAGMENT != 0).then(|| {
+        offset += FRAGMENT_SECTION_SIZE;
+        FragmentHeader::from_bytes(&frame[offset - FRAGMENT_SECTION_SIZE..offset])
+    });
+    let message_type = (flags & FLAG_ORDERED != 0).then(|| {
+        offset += ORDERING_SECTION_SIZE;
+        MessageType::from_le_bytes(frame[offset - ORDERING_SECTION_SIZE..offset].try_into().unwrap())
+    });
+    let priority = if flags & FLAG_PRIORITY != 0 {
+        offset += PRIORITY_SECTION_SIZE;
+        Priority::from_wire(frame[offset - PRIORITY_SECTION_SIZE])?
+    } else {
+        Priority::Normal
+    };
+    let hop = (flags & FLAG_MULTIHOP != 0).then(|| {
+        offset += HOP_SECTION_SIZE;
+        HopCount::from_bytes(&frame[offset - HOP_SECTION_SIZE..offset])
+    });
+    let position = if flags & FLAG_POSITION != 0 {
+        Position::from_wire(&frame[offset..offset + POSITION_SECTION_SIZE])
+    } else {
+        None
+    };
+
+    // Trailers, in flag declaration order with signature and checksum last
+    let mut offset = payload_end;
+    let membership_tag = if flags & FLAG_MEMBERSHIP != 0 {
+        offset += MEMBERSHIP_TAG_SIZE;
+        Some(&frame[payload_end..offset])
+    } else {
+        None
+    };
+
+    let (signature, signed_region) = if flags & FLAG_SIGNED != 0 {
+        let key_id = KeyId::from_le_bytes(frame[offset..offset + 8].try_into().unwrap());
+        let timestamp = u64::from_le_bytes(frame[offset + 8..offset + 16].try_into().unwrap());
+        let signature = (key_id, timestamp, &frame[offset + 16..offset + SIGNATURE_TRAILER_SIZE]);
+        let signed_region = &frame[..offset + 16];
+        offset += SIGNATURE_TRAILER_SIZE;
+        (Some(signature), signed_region)
+    } else {
+        (None, &frame[..offset])
+    };
+
+    let checksum_ok = flags & FLAG_CHECKSUM == 0
+        || verify_checksum(&frame[..offset], &frame[offset..offset + CHECKSUM_SIZE]);
+
+    Some(FrameView {
+        flags,
+        source,
+        epoch,
+        sequence,
+        shard,
+        fragment,
+        message_type,
+        priority,
+        hop,
+        position,
+        targets: &frame[targets_start..targets_end],
+        target_encoding,
+        payload: &frame[targets_end..payload_end],
+        payload_offset: targets_end,
+        membership_tag,
+        membership_region: &frame[..payload_end],
+        signature,
+        signed_region,
+        checksum_ok,
+    })
+}
+
+//==============================================================================
+// BUILDING
+//==============================================================================
+
+/// Write header, sections, targets and payload into `buf`, returning the bytes written
+///
+/// Trailers announced by `meta.flags` are NOT written here - the caller appends
+/// them after the body is in place (tags and signatures must cover the finished
+/// body). `buf` must have room for the trailers as well; this is checked up front.
+pub(crate) fn write_frame(
+    buf: &mut [u8],
+    meta: &FrameMeta,
+    targets: &[DroneId],
+    payload: &[u8],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let flags = meta.flags;
+    let (encoding, words) = targets::choose(targets, flags & FLAG_PSEUDONYMOUS != 0);
+    let body_size = HEADER_SIZE + section_size(flags) + words * 8 + payload.len();
+    if words > targets::WORDS_MASK || body_size + trailer_size(flags) > buf.len() {
+        return Err("Frame exceeds maximum size - reduce targets or payload".into());
+    }
+
+    buf[0..4].copy_from_slice(&OVP_MAGIC.to_le_bytes());
+    buf[4..8].copy_from_slice(&count_word(targets::field(encoding, words), flags).to_le_bytes());
+    buf[8..12].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+
+    let mut offset = HEADER_SIZE;
+    if flags & FLAG_ORIGIN != 0 {
+        buf[offset..offset + 8].copy_from_slice(&meta.source.to_le_bytes());
+        offset += 8;
+    }
+    if flags & FLAG_PSEUDONYMOUS != 0 {
+        buf[offset..offset + 4].copy_from_slice(&meta.epoch.to_le_bytes());
+        offset += 4;
+    }
+    if flags & FLAG_SEQUENCE != 0 {
+        buf[offset..offset + 4].copy_from_slice(&meta.sequence.to_le_bytes());
+        offset += 4;
+    }
+    if flags & FLAG_FEC != 0 {
+        buf[offset..offset + FEC_SECTION_SIZE].copy_from_slice(&meta.shard.to_bytes());
+        offset += FEC_SECTION_SIZE;
+    }
+    if flags & FLAG_FRAGMENT != 0 {
+        buf[offset..offset + FRAGMENT_SECTION_SIZE].copy_from_slice(&meta.fragment.to_bytes());
+        offset += FRAGMENT_SECTION_SIZE;
+    }
+    if flags & FLAG_ORDERED != 0 {
+        buf[offset..offset + ORDERING_SECTION_SIZE].copy_from_slice(&meta.message_type.to_le_bytes());
+        offset += ORDERING_SECTION_SIZE;
+    }
+    if flags & FLAG_PRIORITY != 0 {
+        buf[offset] = meta.priority as u8;
+        offset += PRIORITY_SECTION_SIZE;
+    }
+    if flags & FLAG_MULTIHOP != 0 {
+        buf[offset..offset + HOP_SECTION_SIZE].copy_from_slice(&meta.hop.to_bytes());
+        offset += HOP_SECTION_SIZE;
+    }
+    if flags & FLAG_POSITION != 0 {
+        buf[offset..offset + POSITION_SECTION_SIZE].copy_from_slice(&Position::to_wire(meta.position));
+        offset += POSITION_SECTION_SIZE;
+    }
+
+    targets::write(&mut buf[offset..offset + words * 8], targets, encoding);
+    offset += words * 8;
+    buf[offset..offset + payload.len()].copy_from_slice(payload);
+
+    Ok(body_size)
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain frames written by `write_frame` must parse with the legacy fast path
+    #[test]
+    fn test_plain_frame_roundtrip() {
+        let mut buf = [0u8; 128];
+        let len = write_frame(&mut buf, &FrameMeta::default(), &[7, 9], b"ping").unwrap();
+
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!(view.flags, 0);
+        assert_eq!(view.source, None);
+        assert_eq!(view.targets.len(), 16);
+        assert_eq!(view.payload, b"ping");
+        assert!(view.is_addressed_to(9));
+        assert!(!view.is_addressed_to(8));
+
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 7), Some(b"ping".as_slice()));
+    }
+
+    /// Sections shift the target list; the fast path must follow them
+    #[test]
+    fn test_origin_section_roundtrip() {
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_ORIGIN, source: 77, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[5], b"pos").unwrap();
+
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!(view.source, Some(77));
+        assert!(view.is_addressed_to(5));
+        assert_eq!(view.payload, b"pos");
+
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 5), Some(b"pos".as_slice()));
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 77), None);
+
+        let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_ACK_REQUEST, source: 77, sequence: 31, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[5], b"pos").unwrap();
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!((view.source, view.sequence, view.priority), (Some(77), Some(31), Priority::Normal));
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 5), Some(b"pos".as_slice()));
+    }
+
+    /// The constant-time matcher agrees with a plain scan
+    #[test]
+    fn test_contains_target_ct() {
+        let targets: Vec<u8> = [3u64, 0, u64::MAX].iter().flat_map(|t| t.to_le_bytes()).collect();
+        for id in [3, 0, u64::MAX] {
+            assert!(contains_target_ct(&targets, id));
+        }
+        for id in [1, 4, u64::MAX - 1] {
+            assert!(!contains_target_ct(&targets, id));
+        }
+        assert!(!contains_target_ct(&[], 0));
+    }
+
+    /// Large target sets are compacted and still match on both parsing paths
+    #[test]
+    fn test_compact_targets_roundtrip() {
+        let mut buf = [0u8; 1500];
+        let squad: Vec<DroneId> = (5000..5400).collect();
+        let len = write_frame(&mut buf, &FrameMeta::default(), &squad, b"form-up").unwrap();
+        assert_eq!(len, HEADER_SIZE + 8 + 56 + 7);
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!(view.target_encoding, TargetEncoding::Bitmap);
+        assert!(view.is_addressed_to(5399) && !view.is_addressed_to(5400));
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 5000), Some(b"form-up".as_slice()));
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 4999), None);
+
+        let fleet: Vec<DroneId> = (0..600u64).map(|i| i * 1_000_003).collect();
+        let meta = FrameMeta { flags: FLAG_ORIGIN, source: 1, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &fleet, b"rtb").unwrap();
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!(view.target_encoding, TargetEncoding::Bloom);
+        assert_eq!(view.payload, b"rtb");
+        assert!(fleet.iter().all(|&id| view.is_addressed_to(id)));
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 1_000_003 * 599), Some(b"rtb".as_slice()));
+
+        // Reserved encoding
+        buf[5] |= 0xC0;
+        assert!(parse_frame(&buf[..len]).is_none());
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 0), None);
+    }
+
+    /// Group frames match group members only, on both parsing paths
+    #[test]
+    fn test_group_frame() {
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_GROUP, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[0xA1FA], b"hold").unwrap();
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert!(view.has(FLAG_GROUP));
+        assert_eq!(view.payload, b"hold");
+
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 0xA1FA), None);
+        assert_eq!(crate::parse_ovp_frame_fast_with_groups(&buf[..len], 7, &[3, 0xA1FA]), Some(b"hold".as_slice()));
+        assert_eq!(crate::parse_ovp_frame_fast_with_groups(&buf[..len], 0xA1FA, &[3]), None);
+    }
+
+    /// Write a broadcast frame with `flags`, checking the full parser accepts it
+    fn protocol_frame(buf: &mut [u8], flags: u16) -> usize {
+        let meta = FrameMeta { flags, ..FrameMeta::default() };
+        let len = write_frame(buf, &meta, &[], b"body").unwrap();
+        assert_eq!(parse_frame(&buf[..len]).unwrap().payload, b"body");
+        len
+    }
+
+    /// Control bodies are never handed out as application payload
+    #[test]
+    fn test_fast_path_skips_control() {
+        let mut buf = [0u8; 128];
+        let len = protocol_frame(&mut buf, FLAG_CONTROL);
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 7), None);
+        assert_eq!(crate::parse_ovp_frame_fast_with_groups(&buf[..len], 7, &[1]), None);
+    }
+
+    /// FEC parity shards are never handed out as application payload
+    #[test]
+    fn test_fast_path_skips_fec_shards() {
+        let mut buf = [0u8; 128];
+        let len = protocol_frame(&mut buf, FLAG_FEC);
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 7), None);
+    }
+
+    /// Fragments are never handed out as application payload
+    #[test]
+    fn test_fast_path_skips_fragments() {
+        let mut buf = [0u8; 128];
+        let len = protocol_frame(&mut buf, FLAG_FRAGMENT);
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], 7), None);
+    }
+
+    /// Missing trailers and bad section values are rejected
+    #[test]
+    fn test_reject_truncated_trailers() {
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_SIGNED, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[], b"x").unwrap();
+        assert!(parse_frame(&buf[..len]).is_none());
+
+        let meta = FrameMeta { flags: FLAG_PRIORITY, priority: Priority::Emergency, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[], b"x").unwrap();
+        assert_eq!(parse_frame(&buf[..len]).unwrap().priority, Priority::Emergency);
+        buf[HEADER_SIZE] = 9;
+        assert!(parse_frame(&buf[..len]).is_none());
+    }
+}
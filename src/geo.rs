@@ -0,0 +1,376 @@
+//! # Geographic Forwarding
+//!
+//! Position-based forwarding in the style of GPSR: frames sent with
+//! `OVP::emit_geo` are addressed to a place rather than a drone, and each
+//! drone passes them to the neighbour that brings them closest, using the
+//! positions neighbours announce in their HELLOs (see `OvpConfig::neighbors`
+//! and `OVP::set_position`).
+//!
+//! A frame is delivered by the first drone within `radius` of the destination
+//! position. A drone with no neighbour closer than itself - the edge of a
+//! void - switches the frame to perimeter mode and walks the faces of the
+//! planarised neighbour graph by the right-hand rule, changing face where an
+//! edge crosses the line from the point where the walk began to the
+//! destination, until it reaches a drone closer than that point and greedy
+//! forwarding resumes. A walk that comes back to its first edge found no way
+//! around: a frame for a point (radius 0) is delivered there, at the drone
+//! nearest the point that can be reached; one for a region is dropped.
+//!
+//! ```text
+//! GEO body: [destination:12][radius:4][sender:12][mode:1]
+//!           ([entry:12][face_x:4][face_y:4][edge_from:8][edge_to:8] in perimeter mode)
+//!           [inner frame]
+//! ```
+//!
+//! Greedy distances are three-dimensional; the planar graph and face walk use
+//! the horizontal (x, y) projection. As with routing, messages travel as a
+//! multi-hop inner frame (TTL 0) wrapped in a control frame for the next hop,
+//! and edges carry real drone IDs, so geographic forwarding is unavailable in
+//! ghost mode.
+
+use std::f32::consts::TAU;
+
+use crate::position::{Position, POSITION_SECTION_SIZE};
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of the greedy-mode header
+const GREEDY_HEADER_SIZE: usize = 2 * POSITION_SECTION_SIZE + 5;
+
+/// Size of the perimeter state following the greedy-mode header
+const PERIMETER_SIZE: usize = POSITION_SECTION_SIZE + 24;
+
+/// Largest geographic header
+pub(crate) const MAX_HEADER_SIZE: usize = GREEDY_HEADER_SIZE + PERIMETER_SIZE;
+
+/// Geographic forwarding limits
+#[derive(Debug, Copy, Clone)]
+pub struct GeoConfig {
+    /// Most transmissions a frame may take, perimeter walks included
+    pub max_hops: u8,
+}
+
+impl Default for GeoConfig {
+    fn default() -> Self {
+        GeoConfig { max_hops: 32 }
+    }
+}
+
+/// Place a geographic frame is addressed to
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeoDestination {
+    /// Centre of the destination
+    pub position: Position,
+
+    /// Distance from `position` within which a drone delivers the frame, in
+    /// metres (0 = the drone nearest the point)
+    pub radius: f32,
+}
+
+impl GeoDestination {
+    /// The drone nearest `position`
+    pub const fn point(position: Position) -> Self {
+        GeoDestination { position, radius: 0.0 }
+    }
+
+    /// Any drone within `radius` of `center`
+    pub const fn sphere(center: Position, radius: f32) -> Self {
+        GeoDestination { position: center, radius }
+    }
+}
+
+//==============================================================================
+// HEADER ENCODING
+//==============================================================================
+
+/// State of a face walk around a void
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Perimeter {
+    /// Where greedy forwarding failed
+    pub entry: Position,
+
+    /// Where the walk entered the current face (x, y)
+    pub face: (f32, f32),
+
+    /// First edge walked on the current face
+    pub first_edge: (DroneId, DroneId),
+}
+
+/// Geographic header in front of the inner frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct GeoHeader {
+    /// Where the frame is going
+    pub destination: GeoDestination,
+
+    /// Position of the drone that sent this copy
+    pub sender: Option<Position>,
+
+    /// Face walk in progress, if greedy forwarding failed
+    pub perimeter: Option<Perimeter>,
+}
+
+impl GeoHeader {
+    /// Encode as the start of a control body
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(MAX_HEADER_SIZE);
+        body.extend_from_slice(&Position::to_wire(Some(self.destination.position)));
+        body.extend_from_slice(&self.destination.radius.to_le_bytes());
+        body.extend_from_slice(&Position::to_wire(self.sender));
+        body.push(self.perimeter.is_some() as u8);
+        if let Some(perimeter) = self.perimeter {
+            body.extend_from_slice(&Position::to_wire(Some(perimeter.entry)));
+            body.extend_from_slice(&perimeter.face.0.to_le_bytes());
+            body.extend_from_slice(&perimeter.face.1.to_le_bytes());
+            body.extend_from_slice(&perimeter.first_edge.0.to_le_bytes());
+            body.extend_from_slice(&perimeter.first_edge.1.to_le_bytes());
+        }
+        body
+    }
+
+    /// Decode the start of a control body, returning the header and its size
+    pub fn parse(body: &[u8]) -> Option<(Self, usize)> {
+        let header = body.get(..GREEDY_HEADER_SIZE)?;
+        let float = |bytes: &[u8], at: usize| f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let position = Position::from_wire(&header[..12])?;
+        let radius = float(header, 12);
+        if !(radius >= 0.0 && radius.is_finite()) {
+            return None;
+        }
+        let sender = Position::from_wire(&header[16..28]);
+        let (perimeter, size) = match header[28] {
+            0 => (None, GREEDY_HEADER_SIZE),
+            1 => {
+                let state = body.get(GREEDY_HEADER_SIZE..GREEDY_HEADER_SIZE + PERIMETER_SIZE)?;
+                let id = |at: usize| DroneId::from_le_bytes(state[at..at + 8].try_into().unwrap());
+                let perimeter = Perimeter {
+                    entry: Position::from_wire(&state[..12])?,
+                    face: (float(state, 12), float(state, 16)),
+                    first_edge: (id(20), id(28)),
+                };
+                (Some(perimeter), GREEDY_HEADER_SIZE + PERIMETER_SIZE)
+            }
+            _ => return None,
+        };
+        Some((GeoHeader { destination: GeoDestination { position, radius }, sender, perimeter }, size))
+    }
+}
+
+//==============================================================================
+// FORWARDING DECISION
+//==============================================================================
+
+/// What a drone does with a geographic frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum GeoStep {
+    /// Accept it here
+    Deliver,
+
+    /// Pass it to this neighbour with the updated header
+    Forward(DroneId, GeoHeader),
+
+    /// Give up: no neighbour gets it any closer
+    Drop,
+}
+
+/// Decide where a frame goes next from this drone
+///
+/// `previous` is the drone this copy came from and its position (None at
+/// the origin); `neighbors` are the drones in range with known positions.
+pub(crate) fn next_step(
+    me: DroneId,
+    here: Position,
+    header: &GeoHeader,
+    previous: Option<(DroneId, Position)>,
+    neighbors: &[(DroneId, Position)],
+) -> GeoStep {
+    let destination = header.destination.position;
+    let distance = here.distance(&destination);
+    if distance <= header.destination.radius {
+        return GeoStep::Deliver;
+    }
+
+    // Greedy forwarding, also resuming once closer than where a walk began
+    if header.perimeter.is_none_or(|p| distance < p.entry.distance(&destination)) {
+        let closest = neighbors.iter()
+            .filter(|(_, at)| at.distance(&destination) < distance)
+            .min_by(|(_, a), (_, b)| a.distance(&destination).total_cmp(&b.distance(&destination)));
+        if let Some(&(next, _)) = closest {
+            return GeoStep::Forward(next, GeoHeader { perimeter: None, ..*header });
+        }
+
+        // Local minimum: start a walk on the face the destination line enters
+        let planar = planarize(here, neighbors);
+        let Some((next, _)) = right_hand(here, bearing(here, destination), &planar) else {
+            return GeoStep::Drop;
+        };
+        let perimeter = Perimeter { entry: here, face: (here.x, here.y), first_edge: (me, next) };
+        return GeoStep::Forward(next, GeoHeader { perimeter: Some(perimeter), ..*header });
+    }
+
+    let (Some(mut perimeter), Some((_, from))) = (header.perimeter, previous) else {
+        return GeoStep::Drop;
+    };
+    let planar = planarize(here, neighbors);
+    let Some(mut next) = right_hand(here, bearing(here, from), &planar) else {
+        return GeoStep::Drop;
+    };
+
+    // Change face where the edge crosses the entry-destination line closer in
+    let mut changed = false;
+    for _ in 0..planar.len() {
+        let Some(cross) = intersect(here, next.1, perimeter.entry, destination) else {
+            break;
+        };
+        if distance_xy(cross, destination) >= distance_xy(perimeter.face, destination) {
+            break;
+        }
+        perimeter.face = cross;
+        next = match right_hand(here, bearing(here, next.1), &planar) {
+            Some(edge) => edge,
+            None => break,
+        };
+        perimeter.first_edge = (me, next.0);
+        changed = true;
+    }
+
+    // Back on the first edge: the face was walked all the way round
+    if !changed && perimeter.first_edge == (me, next.0) {
+        return if header.destination.radius == 0.0 { GeoStep::Deliver } else { GeoStep::Drop };
+    }
+    GeoStep::Forward(next.0, GeoHeader { perimeter: Some(perimeter), ..*header })
+}
+
+/// Neighbours kept by the Gabriel graph: no other neighbour lies within the
+/// circle whose diameter is the edge
+fn planarize(here: Position, neighbors: &[(DroneId, Position)]) -> Vec<(DroneId, Position)> {
+    neighbors.iter()
+        .filter(|(id, at)| {
+            let middle = ((here.x + at.x) / 2.0, (here.y + at.y) / 2.0);
+            let radius = distance_xy((here.x, here.y), *at) / 2.0;
+            !neighbors.iter().any(|(other, w)| other != id && distance_xy(middle, *w) < radius)
+        })
+        .copied()
+        .collect()
+}
+
+/// First neighbour counterclockwise from `reference` (a bearing), the one
+/// at `reference` itself last
+fn right_hand(here: Position, reference: f32, planar: &[(DroneId, Position)]) -> Option<(DroneId, Position)> {
+    planar.iter()
+        .map(|&(id, at)| {
+            let turn = (bearing(here, at) - reference).rem_euclid(TAU);
+            (if turn <= f32::EPSILON { TAU } else { turn }, (id, at))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, neighbor)| neighbor)
+}
+
+/// Horizontal bearing from `from` to `to`, in radians counterclockwise from east
+fn bearing(from: Position, to: Position) -> f32 {
+    (to.y - from.y).atan2(to.x - from.x)
+}
+
+/// Horizontal distance from point `(x, y)` to `to`
+fn distance_xy((x, y): (f32, f32), to: Position) -> f32 {
+    ((to.x - x).powi(2) + (to.y - y).powi(2)).sqrt()
+}
+
+/// Horizontal crossing point of segments `a`-`b` and `c`-`d`, if they cross
+fn intersect(a: Position, b: Position, c: Position, d: Position) -> Option<(f32, f32)> {
+    let (rx, ry) = (b.x - a.x, b.y - a.y);
+    let (sx, sy) = (d.x - c.x, d.y - c.y);
+    let denominator = rx * sy - ry * sx;
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let t = ((c.x - a.x) * sy - (c.y - a.y) * sx) / denominator;
+    let u = ((c.x - a.x) * ry - (c.y - a.y) * rx) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some((a.x + t * rx, a.y + t * ry))
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn at(x: f32, y: f32) -> Position {
+        Position::new(x, y, 0.0)
+    }
+
+    fn header(radius: f32) -> GeoHeader {
+        GeoHeader { destination: GeoDestination::sphere(at(10.0, 0.0), radius), sender: None, perimeter: None }
+    }
+
+    /// Headers in both modes survive encoding; malformed ones are rejected
+    #[test]
+    fn test_geo_header_roundtrip() {
+        let greedy = GeoHeader { sender: Some(at(1.0, 2.0)), ..header(5.0) };
+        let bytes = greedy.to_bytes();
+        assert_eq!(GeoHeader::parse(&bytes), Some((greedy, GREEDY_HEADER_SIZE)));
+
+        let perimeter = Perimeter { entry: at(3.0, 4.0), face: (3.0, 4.0), first_edge: (7, 9) };
+        let walking = GeoHeader { perimeter: Some(perimeter), ..greedy };
+        let bytes = walking.to_bytes();
+        assert_eq!(GeoHeader::parse(&bytes), Some((walking, GREEDY_HEADER_SIZE + PERIMETER_SIZE)));
+        assert_eq!(GeoHeader::parse(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(GeoHeader::parse(&header(-1.0).to_bytes()), None);
+    }
+
+    /// Frames go to the neighbour closest to the destination
+    #[test]
+    fn test_greedy_forwarding() {
+        let neighbors = [(2, at(3.0, 3.0)), (3, at(4.0, -1.0)), (4, at(-2.0, 0.0))];
+        assert_eq!(next_step(1, at(0.0, 0.0), &header(1.0), None, &neighbors), GeoStep::Forward(3, header(1.0)));
+        assert_eq!(next_step(1, at(9.5, 0.0), &header(1.0), None, &neighbors), GeoStep::Deliver);
+    }
+
+    /// A void is walked around by the right-hand rule, then greedy resumes
+    #[test]
+    fn test_perimeter_recovery() {
+        // 1 at (0,0) only reaches 2 at (0,3), which reaches 3 at (4,4), which reaches 4 near the goal
+        let step = next_step(1, at(0.0, 0.0), &header(1.0), None, &[(2, at(0.0, 3.0))]);
+        let GeoStep::Forward(2, walking) = step else {
+            panic!("expected a perimeter walk, got {step:?}");
+        };
+        let perimeter = walking.perimeter.unwrap();
+        assert_eq!((perimeter.entry, perimeter.first_edge), (at(0.0, 0.0), (1, 2)));
+
+        let neighbors = [(1, at(0.0, 0.0)), (3, at(4.0, 4.0))];
+        let step = next_step(2, at(0.0, 3.0), &walking, Some((1, at(0.0, 0.0))), &neighbors);
+        let GeoStep::Forward(3, walking) = step else {
+            panic!("expected the walk to continue to 3, got {step:?}");
+        };
+        let neighbors = [(2, at(0.0, 3.0)), (4, at(8.0, 1.0))];
+        let step = next_step(3, at(4.0, 4.0), &walking, Some((2, at(0.0, 3.0))), &neighbors);
+        assert_eq!(step, GeoStep::Forward(4, GeoHeader { perimeter: None, ..walking }));
+    }
+
+    /// A walk back on its first edge drops region frames and delivers point frames
+    #[test]
+    fn test_perimeter_gives_up() {
+        let GeoStep::Forward(2, walking) = next_step(1, at(0.0, 0.0), &header(1.0), None, &[(2, at(0.0, 3.0))]) else {
+            panic!("expected a perimeter walk");
+        };
+        let back = next_step(2, at(0.0, 3.0), &walking, Some((1, at(0.0, 0.0))), &[(1, at(0.0, 0.0))]);
+        assert_eq!(back, GeoStep::Forward(1, walking));
+        let returned = Some((2, at(0.0, 3.0)));
+        assert_eq!(next_step(1, at(0.0, 0.0), &walking, returned, &[(2, at(0.0, 3.0))]), GeoStep::Drop);
+
+        let point = GeoHeader { destination: GeoDestination::point(at(10.0, 0.0)), ..walking };
+        assert_eq!(next_step(1, at(0.0, 0.0), &point, returned, &[(2, at(0.0, 3.0))]), GeoStep::Deliver);
+    }
+
+    /// The Gabriel graph drops edges with a witness inside their circle
+    #[test]
+    fn test_planarize() {
+        let neighbors = [(2, at(4.0, 0.0)), (3, at(2.0, 0.5)), (4, at(0.0, 5.0))];
+        let kept: Vec<DroneId> = planarize(at(0.0, 0.0), &neighbors).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(kept, vec![3, 4]);
+    }
+}
@@ -0,0 +1,331 @@
+//! # Geocast
+//!
+//! Delivery to every drone inside a region. Frames sent with
+//! `OVP::emit_geocast` carry the region, and each receiver delivers them only
+//! if its own position (see `OVP::set_position`) lies inside it; drones with
+//! no position never do.
+//!
+//! ```text
+//! GEOCAST body: [ttl:1][sender:12][shape:1][region][inner frame]
+//!
+//! sphere  (0): [center:12][radius:4]
+//! box     (1): [min:12][max:12]
+//! polygon (2): [floor:4][ceiling:4][count:1] ([x:4][y:4]) * count
+//! ```
+//!
+//! With `OvpConfig::geocast` set, drones also relay geocasts toward and
+//! within the region: a drone rebroadcasts a geocast it hears for the first
+//! time if it is inside the region, or nearer to it than the drone it heard
+//! it from, while the TTL lasts. Relays carry the frame outward from the
+//! sender only where that makes progress, so the flood stays in a corridor
+//! toward the region rather than covering the swarm. As with routing, the
+//! message travels as a complete inner frame, so tags and signatures hold end
+//! to end.
+
+use crate::position::{Position, POSITION_SECTION_SIZE};
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Region shape codes
+const SHAPE_SPHERE: u8 = 0;
+const SHAPE_BOX: u8 = 1;
+const SHAPE_POLYGON: u8 = 2;
+
+/// Fewest and most polygon vertices
+const MIN_VERTICES: usize = 3;
+const MAX_VERTICES: usize = 64;
+
+/// Size of the header fields before the region
+const FIXED_SIZE: usize = 2 + POSITION_SECTION_SIZE;
+
+/// Geocast relaying
+#[derive(Debug, Copy, Clone)]
+pub struct GeocastConfig {
+    /// Relays a geocast may take on its way to and through the region
+    pub max_hops: u8,
+}
+
+impl Default for GeocastConfig {
+    fn default() -> Self {
+        GeocastConfig { max_hops: 8 }
+    }
+}
+
+/// Area whose drones a geocast is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoRegion {
+    /// Everything within `radius` metres of `center`
+    Sphere {
+        /// Centre of the sphere
+        center: Position,
+
+        /// Radius in metres
+        radius: f32,
+    },
+
+    /// Axis-aligned box between two corners
+    Box {
+        /// Corner with the smallest coordinates
+        min: Position,
+
+        /// Corner with the largest coordinates
+        max: Position,
+    },
+
+    /// Vertical prism over a horizontal polygon
+    Polygon {
+        /// Corners in order, (x, y), 3 to 64 of them
+        vertices: Vec<(f32, f32)>,
+
+        /// Lowest altitude (z) included
+        floor: f32,
+
+        /// Highest altitude (z) included
+        ceiling: f32,
+    },
+}
+
+impl GeoRegion {
+    /// True if `position` lies inside the region (boundary included)
+    pub fn contains(&self, position: &Position) -> bool {
+        self.distance(position) == 0.0
+    }
+
+    /// Distance from `position` to the nearest point of the region, 0 inside
+    pub fn distance(&self, position: &Position) -> f32 {
+        match self {
+            GeoRegion::Sphere { center, radius } => (center.distance(position) - radius).max(0.0),
+            GeoRegion::Box { min, max } => {
+                // max/min rather than clamp: never panics on swapped corners
+                let nearest = Position::new(
+                    position.x.max(min.x).min(max.x),
+                    position.y.max(min.y).min(max.y),
+                    position.z.max(min.z).min(max.z),
+                );
+                nearest.distance(position)
+            }
+            GeoRegion::Polygon { vertices, floor, ceiling } => {
+                let point = (position.x, position.y);
+                let horizontal = if polygon_contains(vertices, point) {
+                    0.0
+                } else {
+                    edges(vertices).map(|(a, b)| segment_distance(point, a, b)).fold(f32::INFINITY, f32::min)
+                };
+                let vertical = (floor - position.z).max(position.z - ceiling).max(0.0);
+                (horizontal * horizontal + vertical * vertical).sqrt()
+            }
+        }
+    }
+
+    /// Encode the shape code and region fields
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            GeoRegion::Sphere { center, radius } => {
+                out.push(SHAPE_SPHERE);
+                out.extend_from_slice(&Position::to_wire(Some(*center)));
+                out.extend_from_slice(&radius.to_le_bytes());
+            }
+            GeoRegion::Box { min, max } => {
+                out.push(SHAPE_BOX);
+                out.extend_from_slice(&Position::to_wire(Some(*min)));
+                out.extend_from_slice(&Position::to_wire(Some(*max)));
+            }
+            GeoRegion::Polygon { vertices, floor, ceiling } => {
+                out.push(SHAPE_POLYGON);
+                out.extend_from_slice(&floor.to_le_bytes());
+                out.extend_from_slice(&ceiling.to_le_bytes());
+                out.push(vertices.len() as u8);
+                for (x, y) in vertices {
+                    out.extend_from_slice(&x.to_le_bytes());
+                    out.extend_from_slice(&y.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    /// Decode a shape code and region fields, returning the region and its size
+    fn read(bytes: &[u8]) -> Option<(Self, usize)> {
+        let float = |at: usize| bytes.get(at..at + 4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).filter(|f| f.is_finite());
+        let position = |at: usize| bytes.get(at..at + POSITION_SECTION_SIZE).and_then(Position::from_wire);
+        match *bytes.first()? {
+            SHAPE_SPHERE => {
+                let radius = float(13).filter(|&r| r >= 0.0)?;
+                Some((GeoRegion::Sphere { center: position(1)?, radius }, 17))
+            }
+            SHAPE_BOX => {
+                let (min, max) = (position(1)?, position(13)?);
+                box_ordered(&min, &max).then_some((GeoRegion::Box { min, max }, 25))
+            }
+            SHAPE_POLYGON => {
+                let (floor, ceiling) = (float(1)?, float(5)?);
+                let count = *bytes.get(9)? as usize;
+                if !(MIN_VERTICES..=MAX_VERTICES).contains(&count) {
+                    return None;
+                }
+                let vertices = (0..count)
+                    .map(|i| Some((float(10 + i * 8)?, float(14 + i * 8)?)))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((GeoRegion::Polygon { vertices, floor, ceiling }, 10 + count * 8))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check the region can be sent
+    pub(crate) fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            GeoRegion::Polygon { vertices, .. } if !(MIN_VERTICES..=MAX_VERTICES).contains(&vertices.len()) => {
+                Err("Geocast polygons need 3 to 64 vertices".into())
+            }
+            GeoRegion::Box { min, max } if !box_ordered(min, max) => {
+                Err("Geocast box corners must satisfy min <= max on every axis".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// True if `min` is no larger than `max` on every axis
+fn box_ordered(min: &Position, max: &Position) -> bool {
+    min.x <= max.x && min.y <= max.y && min.z <= max.z
+}
+
+/// Edges of a closed polygon
+fn edges(vertices: &[(f32, f32)]) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+    vertices.iter().zip(vertices.iter().cycle().skip(1)).map(|(&a, &b)| (a, b))
+}
+
+/// Even-odd rule: a ray from `point` crosses the boundary an odd number of times
+/// (points on an edge count as inside)
+fn polygon_contains(vertices: &[(f32, f32)], point: (f32, f32)) -> bool {
+    let (x, y) = point;
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        if segment_distance(point, a, b) == 0.0 {
+            return true;
+        }
+        if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Distance from `point` to the segment `a`-`b`
+fn segment_distance(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 { 0.0 } else { (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0) };
+    let (nx, ny) = (a.0 + t * dx - point.0, a.1 + t * dy - point.1);
+    (nx * nx + ny * ny).sqrt()
+}
+
+//==============================================================================
+// HEADER ENCODING
+//==============================================================================
+
+/// Geocast header in front of the inner frame
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GeocastHeader {
+    /// Relays still allowed
+    pub ttl: u8,
+
+    /// Position of the drone that sent this copy, if known
+    pub sender: Option<Position>,
+
+    /// Drones the frame is for
+    pub region: GeoRegion,
+}
+
+impl GeocastHeader {
+    /// Encode as the start of a control body
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(FIXED_SIZE + 25);
+        body.push(self.ttl);
+        body.extend_from_slice(&Position::to_wire(self.sender));
+        self.region.write(&mut body);
+        body
+    }
+
+    /// Decode the start of a control body, returning the header and its size
+    pub fn parse(body: &[u8]) -> Option<(Self, usize)> {
+        let ttl = *body.first()?;
+        let sender = Position::from_wire(body.get(1..FIXED_SIZE - 1)?);
+        let (region, size) = GeoRegion::read(&body[FIXED_SIZE - 1..])?;
+        Some((GeocastHeader { ttl, sender, region }, FIXED_SIZE - 1 + size))
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> GeoRegion {
+        GeoRegion::Polygon { vertices: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], floor: 0.0, ceiling: 50.0 }
+    }
+
+    /// Every shape knows what it contains and how far away a point is
+    #[test]
+    fn test_region_contains_and_distance() {
+        let sphere = GeoRegion::Sphere { center: Position::new(0.0, 0.0, 0.0), radius: 5.0 };
+        assert!(sphere.contains(&Position::new(3.0, 4.0, 0.0)));
+        assert_eq!(sphere.distance(&Position::new(0.0, 8.0, 0.0)), 3.0);
+
+        let cuboid = GeoRegion::Box { min: Position::new(0.0, 0.0, 0.0), max: Position::new(10.0, 10.0, 10.0) };
+        assert!(cuboid.contains(&Position::new(5.0, 10.0, 2.0)));
+        assert_eq!(cuboid.distance(&Position::new(13.0, 14.0, 5.0)), 5.0);
+
+        let polygon = square();
+        assert!(polygon.contains(&Position::new(5.0, 5.0, 20.0)));
+        assert!(polygon.contains(&Position::new(10.0, 5.0, 20.0)));
+        assert!(!polygon.contains(&Position::new(5.0, 5.0, 60.0)));
+        assert_eq!(polygon.distance(&Position::new(13.0, 14.0, 20.0)), 5.0);
+
+        // Concave: the notch of an L is outside
+        let l_shape = GeoRegion::Polygon {
+            vertices: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 4.0), (4.0, 4.0), (4.0, 10.0), (0.0, 10.0)],
+            floor: 0.0,
+            ceiling: 50.0,
+        };
+        assert!(l_shape.contains(&Position::new(2.0, 8.0, 1.0)));
+        assert!(!l_shape.contains(&Position::new(8.0, 8.0, 1.0)));
+    }
+
+    /// Headers with every shape survive encoding; malformed ones are rejected
+    #[test]
+    fn test_geocast_header_roundtrip() {
+        let regions = [
+            GeoRegion::Sphere { center: Position::new(1.0, 2.0, 3.0), radius: 40.0 },
+            GeoRegion::Box { min: Position::new(0.0, 0.0, 0.0), max: Position::new(5.0, 5.0, 5.0) },
+            square(),
+        ];
+        for region in regions {
+            let header = GeocastHeader { ttl: 3, sender: Some(Position::new(9.0, 9.0, 9.0)), region };
+            let mut bytes = header.to_bytes();
+            let size = bytes.len();
+            bytes.extend_from_slice(b"inner");
+            assert_eq!(GeocastHeader::parse(&bytes), Some((header, size)));
+            assert_eq!(GeocastHeader::parse(&bytes[..size - 1]), None);
+        }
+        let degenerate = GeoRegion::Polygon { vertices: vec![(0.0, 0.0), (1.0, 1.0)], floor: 0.0, ceiling: 1.0 };
+        assert!(degenerate.validate().is_err());
+        let header = GeocastHeader { ttl: 0, sender: None, region: degenerate };
+        assert_eq!(GeocastHeader::parse(&header.to_bytes()), None);
+    }
+
+    /// Boxes with swapped corners are refused on both ends instead of panicking
+    #[test]
+    fn test_swapped_box_rejected() {
+        let swapped = GeoRegion::Box { min: Position::new(0.0, 10.0, 0.0), max: Position::new(10.0, 0.0, 10.0) };
+        assert!(swapped.validate().is_err());
+        assert!(!swapped.contains(&Position::new(5.0, 5.0, 5.0)));
+        let header = GeocastHeader { ttl: 2, sender: None, region: swapped };
+        assert_eq!(GeocastHeader::parse(&header.to_bytes()), None);
+    }
+}
@@ -0,0 +1,36 @@
+//! # Multicast Groups
+//!
+//! Addressing a team by listing its members costs 8 bytes per drone, caps the
+//! team size a frame can reach and tells every listener who is on the team.
+//! Frames with `FLAG_GROUP` list group identifiers instead: one entry reaches
+//! every drone that joined the group (`OVP::join_group`), however many there
+//! are, and membership never appears on the wire.
+//!
+//! Identifiers are 64-bit; `group_id` derives one from a name, so drones agree
+//! on "scouts" or "team-alpha" without coordination, and 32-bit identifiers
+//! fit as they are. In ghost mode group identifiers are replaced by per-epoch
+//! pseudonyms, like drone IDs.
+
+use hmac_sha256::Hash;
+
+/// Identifier of a multicast group
+pub type GroupId = u64;
+
+/// Domain separator for identifiers derived from names
+const GROUP_ID_CONTEXT: &[u8] = b"ovp-group-v1";
+
+/// Group identifier derived from a name
+///
+/// # Example
+///
+/// ```rust
+/// # use ovp::group_id;
+/// assert_eq!(group_id("scouts"), group_id("scouts"));
+/// assert_ne!(group_id("scouts"), group_id("team-alpha"));
+/// ```
+pub fn group_id(name: &str) -> GroupId {
+    let mut hash = Hash::new();
+    hash.update(GROUP_ID_CONTEXT);
+    hash.update(name.as_bytes());
+    GroupId::from_le_bytes(hash.finalize()[..8].try_into().unwrap())
+}
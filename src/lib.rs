@@ -38,6 +38,84 @@ use std::mem;
 use std::ffi::CString;
 use std::thread;
 use std::sync::mpsc::{channel, Receiver};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+mod checksum;
+mod congestion;
+mod dedup;
+mod dtn;
+mod enrollment;
+mod fec;
+mod fragment;
+mod frame;
+mod geo;
+mod geocast;
+mod group;
+mod nack;
+mod neighbor;
+mod ordering;
+mod position;
+mod priority;
+mod privacy;
+mod radiotap;
+mod random;
+mod ratelimit;
+mod relay;
+mod reliable;
+mod routing;
+mod scan;
+mod signing;
+mod targets;
+
+pub use checksum::crc32c;
+pub use congestion::CongestionConfig;
+pub use dedup::DedupConfig;
+pub use dtn::DtnConfig;
+pub use enrollment::{DeviceCredential, EnrollmentAuthority, CREDENTIAL_SIZE, SWARM_KEY_SIZE};
+pub use fec::FecParams;
+pub use fragment::ReassemblyConfig;
+pub use geo::{GeoConfig, GeoDestination};
+pub use geocast::{GeoRegion, GeocastConfig};
+pub use group::{group_id, GroupId};
+pub use nack::NackConfig;
+pub use neighbor::{Neighbor, NeighborConfig, NeighborEvent};
+pub use ordering::{MessageType, OrderingPolicy};
+pub use position::Position;
+pub use priority::Priority;
+pub use privacy::PseudonymScheme;
+pub use ratelimit::{RateLimit, RateLimitConfig, ThrottleMode, WouldExceedBudget};
+pub use relay::{RelayConfig, RelayPolicy};
+pub use reliable::{DeliveryStatus, RetransmitPolicy};
+pub use routing::{Route, RoutingConfig};
+pub use signing::{key_id_of, Authority, KeyId, SigningKey, TrustStore, PUBLIC_KEY_SIZE};
+
+use congestion::ChannelMonitor;
+use dedup::DuplicateCache;
+use dtn::{BundleId, BundleStore, BUNDLE_HEADER_SIZE};
+use enrollment::{Membership, NONCE_SIZE};
+use frame::{
+    FrameMeta, FLAG_ACK_REQUEST, FLAG_CHECKSUM, FLAG_CONTROL, FLAG_MEMBERSHIP, FLAG_ORIGIN, FLAG_PSEUDONYMOUS,
+    FLAG_FEC, FLAG_FRAGMENT, FLAG_GROUP, FLAG_MULTIHOP, FLAG_ORDERED, FLAG_POSITION, FLAG_PRIORITY,
+    FLAG_RELIABLE_BROADCAST, FLAG_SEQUENCE, FLAG_SIGNED,
+};
+use fec::{FecDecoder, ShardHeader, ShardOutcome};
+use fragment::{FragmentHeader, Reassembler};
+use geo::{GeoHeader, GeoStep};
+use geocast::GeocastHeader;
+use nack::{Nack, RepairBuffer, StreamTracker};
+use neighbor::{Hello, NeighborTable};
+use ordering::{Ordered, OrderingBuffer};
+use priority::PriorityQueue;
+use random::Jitter;
+use ratelimit::RateLimiter;
+use relay::{HopCount, RelayQueue};
+use privacy::PseudonymCache;
+use reliable::{Ack, AckWaiter};
+use targets::TargetEncoding;
+use routing::{DiscoveryStep, RouteReply, RouteRequest, RoutingTable, ROUTED_HEADER_SIZE};
+use signing::ReplayGuard;
 
 //==============================================================================
 // RAW SOCKET CONSTANTS
@@ -61,13 +139,28 @@ const PACKET_ADD_MEMBERSHIP: i32 = 1;
 /// Promiscuous mode flag - receive ALL frames on interface, not just addressed to us
 const PACKET_MR_PROMISC: i32 = 1;
 
+/// Poll event flag - data is available to read
+const POLLIN: i16 = 0x001;
+
+/// Socket level for generic socket options
+const SOL_SOCKET: i32 = 1;
+
+/// Socket option setting the kernel priority of sent frames
+const SO_PRIORITY: i32 = 12;
+
+/// Send flag - fail with EAGAIN instead of blocking on a full send queue
+const MSG_DONTWAIT: i32 = 0x40;
+
+/// Error number for a full device queue
+const ENOBUFS: i32 = 105;
+
 //==============================================================================
 // OVP PROTOCOL CONSTANTS
 //==============================================================================
 
 /// OVP Protocol magic number - identifies valid OVP frames
 /// 0xDEADBEEF chosen for easy hex identification in network traces
-const OVP_MAGIC: u32 = 0xDEADBEEF;
+pub(crate) const OVP_MAGIC: u32 = 0xDEADBEEF;
 
 /// Maximum frame size based on standard Ethernet MTU
 /// Prevents fragmentation and ensures single-packet transmission
@@ -77,6 +170,18 @@ const MAX_FRAME_SIZE: usize = 1500;
 /// 64KB provides substantial headroom for high-throughput scenarios
 const RECV_BUFFER_SIZE: usize = 65536;
 
+/// Messages queued while a reliable emission waits for its ACK or released
+/// together by an ordered stream
+/// Oldest low-priority messages are dropped first once the inbox is full
+const INBOX_CAPACITY: usize = 256;
+
+/// Frames held while the socket's send queue is full
+/// Lowest-priority frames are dropped first once the queue is full
+const TX_QUEUE_CAPACITY: usize = 256;
+
+/// Most pending frames pulled in at once to sort them by priority
+const RX_BATCH: usize = 32;
+
 //==============================================================================
 // CORE TYPE DEFINITIONS
 //==============================================================================
@@ -88,12 +193,17 @@ pub type DroneId = u64;
 /// OVP Frame Header Structure
 /// 
 /// Packed representation ensures exact wire format control and minimal overhead.
-/// Total header size: 12 bytes + (target_count * 8) + payload_len
+/// Total header size: 12 bytes + (target_count * 8) + payload_len + trailers
 /// 
 /// Wire Format:
 /// ```text
-/// [magic:4][target_count:4][payload_len:4][targets:target_count*8][payload:payload_len]
+/// [magic:4][target_count:4][payload_len:4][targets:target_count*8][payload:payload_len][trailers]
 /// ```
+/// 
+/// The low 16 bits of `target_count` hold the count (or, for large target
+/// sets, a bitmap or Bloom filter encoding and its size), the high 16 bits hold
+/// feature flags selecting optional trailers (e.g. signatures). Frames with no
+/// flags set are identical to the original v2 layout.
 #[repr(C, packed)]
 #[derive(Debug, Copy, Clone)]
 pub struct OmegaFrame {
@@ -101,6 +211,8 @@ pub struct OmegaFrame {
     magic: u32,
     
     /// Number of specific target drones (0 = broadcast to all in range)
+    /// or compact target encoding in the low 16 bits, feature flags in the
+    /// high 16 bits
     target_count: u32,
     
     /// Length of payload data in bytes
@@ -133,7 +245,7 @@ struct PacketMreq {
 /// Specifies interface and protocol for raw packet transmission/reception
 #[repr(C, packed)]
 #[derive(Copy, Clone)]
-struct SockaddrLl {
+pub struct SockaddrLl {
     /// Address family (AF_PACKET)
     sll_family: u16,
     
@@ -156,6 +268,20 @@ struct SockaddrLl {
     sll_addr: [u8; 8],
 }
 
+/// Descriptor readiness request for `poll`
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct PollFd {
+    /// File descriptor to watch
+    fd: i32,
+    
+    /// Requested events (POLLIN)
+    events: i16,
+    
+    /// Events reported by the kernel
+    revents: i16,
+}
+
 //==============================================================================
 // UNSAFE SYSTEM CALL BINDINGS
 //==============================================================================
@@ -185,6 +311,9 @@ unsafe extern "C" {
     
     /// Close file descriptor
     unsafe fn close(fd: i32) -> i32;
+    
+    /// Wait for readiness on a set of file descriptors
+    unsafe fn poll(fds: *mut PollFd, nfds: u64, timeout: i32) -> i32;
 }
 
 //==============================================================================
@@ -214,6 +343,10 @@ pub struct OmegaSocket {
     /// Pre-computed destination address for broadcast operations
     /// Eliminates repeated address computation in hot path
     pub dest_addr: SockaddrLl,
+    
+    /// Append a CRC-32C trailer to every frame built by `build_and_emit`
+    /// Cheap corruption detection for deployments without cryptographic tags
+    pub checksum: bool,
 }
 
 impl OmegaSocket {
@@ -308,6 +441,7 @@ impl OmegaSocket {
                 send_buffer: Box::new([0u8; MAX_FRAME_SIZE]),
                 recv_buffer: Box::new([0u8; RECV_BUFFER_SIZE]),
                 dest_addr,
+                checksum: false,
             })
         }
     }
@@ -397,6 +531,7 @@ impl OmegaSocket {
     /// ULTIMATE ZERO-ALLOCATION HOT PATH
     /// Constructs the complete OVP frame directly in the pre-allocated send buffer,
     /// then emits via spherical broadcast. No intermediate allocations or copies.
+    /// When `checksum` is enabled a CRC-32C trailer is appended before emission.
     /// 
     /// # Arguments
     /// 
@@ -418,10 +553,17 @@ impl OmegaSocket {
     pub fn build_and_emit(&mut self, targets: &[DroneId], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         // Calculate total frame size and validate against buffer capacity
         let header_size = 12;                          // magic + target_count + payload_len
-        let targets_size = targets.len() * 8;          // 8 bytes per DroneId
-        let total_size = header_size + targets_size + payload.len();
+        let (encoding, words) = targets::choose(targets, false);
+        let targets_size = words * 8;                  // 8 bytes per DroneId, less when compacted
+        let body_size = header_size + targets_size + payload.len();
+        let (flags, trailer_size) = if self.checksum {
+            (frame::FLAG_CHECKSUM, checksum::CHECKSUM_SIZE)
+        } else {
+            (0, 0)
+        };
+        let total_size = body_size + trailer_size;
         
-        if total_size > MAX_FRAME_SIZE {
+        if total_size > MAX_FRAME_SIZE || words > targets::WORDS_MASK {
             return Err("Frame exceeds maximum size - reduce targets or payload".into());
         }
 
@@ -431,18 +573,26 @@ impl OmegaSocket {
             // Write OVP header directly to buffer using unaligned writes for speed
             // Little-endian format for consistent cross-platform compatibility
             ptr::write_unaligned(buf as *mut u32, OVP_MAGIC.to_le());
-            ptr::write_unaligned(buf.add(4) as *mut u32, (targets.len() as u32).to_le());
+            ptr::write_unaligned(buf.add(4) as *mut u32, frame::count_word(targets::field(encoding, words), flags).to_le());
             ptr::write_unaligned(buf.add(8) as *mut u32, (payload.len() as u32).to_le());
-            
-            // Write target drone IDs array
-            let mut offset = 12;
-            for &target in targets {
-                ptr::write_unaligned(buf.add(offset) as *mut u64, target.to_le());
-                offset += 8;
-            }
-            
+        }
+        
+        // Write target drone IDs array (or its bitmap / Bloom filter)
+        let offset = header_size + targets_size;
+        targets::write(&mut self.send_buffer[header_size..offset], targets, encoding);
+        
+        unsafe {
             // Copy payload data directly after targets
-            ptr::copy_nonoverlapping(payload.as_ptr(), buf.add(offset), payload.len());
+            ptr::copy_nonoverlapping(payload.as_ptr(), self.send_buffer.as_mut_ptr().add(offset), payload.len());
+        }
+        
+        // Optional CRC-32C trailer over everything written so far
+        if self.checksum {
+            checksum::append_checksum(&mut self.send_buffer[..], body_size);
+        }
+
+        unsafe {
+            let buf = self.send_buffer.as_ptr();
             
             // Immediate spherical emission - frame goes out instantly
             let sent = sendto(
@@ -461,6 +611,127 @@ impl OmegaSocket {
 
         Ok(())
     }
+
+    /// Emit the first `len` bytes of the send buffer
+    /// 
+    /// Companion to in-place frame construction: callers that must post-process
+    /// a frame before it leaves (e.g. append a signature trailer) build it
+    /// directly in `send_buffer` and hand it over here. No copies are made.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `len` - Number of valid bytes at the start of `send_buffer`
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating success or transmission error
+    #[inline(always)]
+    pub fn emit_send_buffer(&mut self, len: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if len > MAX_FRAME_SIZE {
+            return Err("Frame exceeds maximum size - reduce targets or payload".into());
+        }
+
+        unsafe {
+            let sent = sendto(
+                self.raw_fd,
+                self.send_buffer.as_ptr(),
+                len,
+                0,
+                ptr::addr_of!(self.dest_addr),
+                mem::size_of::<SockaddrLl>() as u32,
+            );
+
+            if sent < 0 {
+                return Err("Failed to emit constructed frame".into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emit a complete frame unless the socket's send queue is full
+    /// 
+    /// Non-blocking counterpart of `emit_frame` for callers that keep their
+    /// own transmit queue.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `frame_data` - Complete OVP frame ready for transmission
+    /// 
+    /// # Returns
+    /// 
+    /// true once sent, false if the kernel could not take the frame right
+    /// now, or a transmission error
+    #[inline(always)]
+    pub fn try_emit(&self, frame_data: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+        let sent = unsafe {
+            sendto(
+                self.raw_fd,
+                frame_data.as_ptr(),
+                frame_data.len(),
+                MSG_DONTWAIT,
+                ptr::addr_of!(self.dest_addr),
+                mem::size_of::<SockaddrLl>() as u32,
+            )
+        };
+        
+        if sent < 0 {
+            let error = std::io::Error::last_os_error();
+            // A full device queue is as temporary as a full socket buffer
+            if error.kind() == std::io::ErrorKind::WouldBlock || error.raw_os_error() == Some(ENOBUFS) {
+                return Ok(false);
+            }
+            return Err("Failed to emit constructed frame".into());
+        }
+        
+        Ok(true)
+    }
+
+    /// Set the kernel priority (`SO_PRIORITY`) of frames sent from now on
+    /// 
+    /// Queueing disciplines and 802.11 WMM access categories schedule frames
+    /// by this value. Values above 6 require `CAP_NET_ADMIN`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `priority` - Linux skb priority (0-7 map to the `TC_PRIO_*` classes)
+    #[inline]
+    pub fn set_priority(&self, priority: i32) -> Result<(), Box<dyn std::error::Error>> {
+        let ret = unsafe {
+            setsockopt(
+                self.raw_fd,
+                SOL_SOCKET,
+                SO_PRIORITY,
+                ptr::addr_of!(priority) as *const u8,
+                mem::size_of::<i32>() as u32,
+            )
+        };
+        
+        if ret < 0 {
+            return Err("Failed to set socket priority - check permissions".into());
+        }
+        
+        Ok(())
+    }
+
+    /// Wait until a frame is ready to be received or the timeout elapses
+    /// 
+    /// `receive_frame` blocks indefinitely; protocol handshakes that must give
+    /// up after a deadline call this first.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `timeout` - Maximum time to wait (millisecond resolution)
+    /// 
+    /// # Returns
+    /// 
+    /// true if a frame is pending, false on timeout or error
+    #[inline]
+    pub fn wait_readable(&self, timeout: Duration) -> bool {
+        let mut pfd = PollFd { fd: self.raw_fd, events: POLLIN, revents: 0 };
+        let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+        unsafe { poll(ptr::addr_of_mut!(pfd), 1, millis) > 0 && pfd.revents & POLLIN != 0 }
+    }
 }
 
 /// Automatic socket cleanup when OmegaSocket is dropped
@@ -478,6 +749,204 @@ impl Drop for OmegaSocket {
 // OVP HIGH-LEVEL CLIENT API
 //==============================================================================
 
+/// Optional protocol features selected at `OVP` construction
+/// 
+/// `OvpConfig::default()` reproduces the plain v2 behaviour: no signing key,
+/// an empty trust store, no enrollment, every frame unauthenticated.
+#[derive(Clone, Default)]
+pub struct OvpConfig {
+    /// Commander public keys whose signed frames are accepted
+    pub trust_store: TrustStore,
+    
+    /// Key used by `OVP::emit_signed` (None = this node cannot sign)
+    pub signing_key: Option<SigningKey>,
+    
+    /// Provisioned secret used by `OVP::enroll` to join the swarm
+    pub credential: Option<DeviceCredential>,
+    
+    /// Makes this node the swarm's enrollment authority
+    pub enrollment_authority: Option<EnrollmentAuthority>,
+    
+    /// Once enrolled, drop frames without a valid membership tag (only the
+    /// enrollment handshake is exempt)
+    pub require_membership: bool,
+    
+    /// Ghost mode: replace IDs on the wire with rotating pseudonyms
+    pub pseudonyms: Option<PseudonymScheme>,
+    
+    /// Append a CRC-32C trailer to every emitted frame
+    pub checksum: bool,
+    
+    /// ACK timeouts and retry limit for `OVP::emit_reliable`
+    pub retransmit: RetransmitPolicy,
+    
+    /// Repair buffer and NACK timers for `OVP::emit_reliable_broadcast`
+    pub nack: NackConfig,
+    
+    /// Timeout and memory limits for reassembling fragmented messages
+    pub reassembly: ReassemblyConfig,
+    
+    /// Drop frames already delivered recently (None = deliver every copy)
+    pub dedup: Option<DedupConfig>,
+    
+    /// Delivery order per message type of `OVP::emit_ordered` frames
+    /// (types not listed are delivered as they arrive)
+    pub ordering: HashMap<MessageType, OrderingPolicy>,
+    
+    /// Transmit budgets per node and per priority class (default: unlimited)
+    pub rate_limit: RateLimitConfig,
+    
+    /// Channel load estimation driving the `OVP::emit_beacon` rate
+    pub congestion: CongestionConfig,
+    
+    /// Rebroadcast multi-hop frames for other drones (None = never relay)
+    pub relay: Option<RelayConfig>,
+    
+    /// Send HELLO beacons and track drones in range (None = no discovery)
+    pub neighbors: Option<NeighborConfig>,
+    
+    /// Discover and maintain multi-hop unicast routes (None = no routing)
+    pub routing: Option<RoutingConfig>,
+    
+    /// Forward frames addressed to positions (None = no geographic forwarding)
+    pub geo: Option<GeoConfig>,
+    
+    /// Relay geocasts toward and within their region (None = never relay)
+    pub geocast: Option<GeocastConfig>,
+    
+    /// Store and pass on `OVP::emit_stored` bundles (None = deliver only)
+    pub dtn: Option<DtnConfig>,
+}
+
+/// Running counters maintained by an `OVP` instance
+#[derive(Debug, Clone, Default)]
+pub struct OvpStats {
+    /// Signed frames addressed to us that failed verification
+    pub rejected_signatures: u64,
+    
+    /// Validly signed frames dropped as replays or outside the trust
+    /// store's freshness window
+    pub stale_signatures: u64,
+    
+    /// Frames dropped for a missing or invalid membership tag
+    pub rejected_membership: u64,
+    
+    /// Frames dropped because their source ID was revoked
+    pub revoked_frames: u64,
+    
+    /// Rotated swarm keys received from the enrollment authority
+    pub rekeys: u64,
+    
+    /// Frames dropped because their CRC-32C trailer did not match
+    pub checksum_failures: u64,
+    
+    /// Reliable frames sent again after an ACK timeout
+    pub retransmissions: u64,
+    
+    /// NACKs sent for gaps in other drones' reliable broadcasts
+    pub nacks_sent: u64,
+    
+    /// Reliable broadcasts sent again in answer to a NACK
+    pub repairs_sent: u64,
+    
+    /// Session heartbeats announcing our newest reliable broadcast
+    pub heartbeats_sent: u64,
+    
+    /// Missing broadcasts given up on after the NACK attempt limit
+    pub unrecoverable_frames: u64,
+    
+    /// FEC messages that needed repair shards to reconstruct
+    pub fec_recovered: u64,
+    
+    /// FEC groups that expired with too few shards
+    pub fec_failed: u64,
+    
+    /// Fragmented messages abandoned on timeout or memory limit
+    pub reassembly_failures: u64,
+    
+    /// Repeated frames dropped by the duplicate cache
+    pub duplicates_dropped: u64,
+    
+    /// Ordered frames dropped for being older than what was already delivered
+    pub stale_frames: u64,
+    
+    /// Gaps in reorder-policy streams given up on after the timeout
+    pub ordering_gaps_skipped: u64,
+    
+    /// Frames queued because the socket's send queue was full
+    pub tx_deferred: u64,
+    
+    /// Frames dropped from a full transmit queue or failing on retry
+    pub tx_dropped: u64,
+    
+    /// Emissions that waited for their transmit budget
+    pub throttled_delayed: u64,
+    
+    /// Emissions discarded for exceeding their transmit budget
+    pub throttled_dropped: u64,
+    
+    /// Emissions refused with `WouldExceedBudget`
+    pub throttled_rejected: u64,
+    
+    /// Beacons sent by `OVP::emit_beacon`
+    pub beacons_sent: u64,
+    
+    /// Multi-hop frames rebroadcast for other drones
+    pub frames_relayed: u64,
+    
+    /// Rebroadcasts skipped as redundant by the relay policy
+    pub relays_suppressed: u64,
+    
+    /// HELLO beacons sent for neighbour discovery
+    pub hellos_sent: u64,
+    
+    /// Route requests originated by this drone
+    pub route_requests_sent: u64,
+    
+    /// Routed frames forwarded toward other drones
+    pub frames_routed: u64,
+    
+    /// Routed frames dropped for want of a route
+    pub routing_failures: u64,
+    
+    /// Geographic frames forwarded toward their destination for other drones
+    pub geo_forwarded: u64,
+    
+    /// Geographic frames dropped with no way closer to their destination
+    pub geo_dropped: u64,
+    
+    /// Geocasts rebroadcast toward or within their region
+    pub geocasts_relayed: u64,
+    
+    /// Bundles from other drones taken into storage
+    pub bundles_stored: u64,
+    
+    /// Stored bundles sent on for other drones
+    pub bundles_forwarded: u64,
+    
+    /// Stored bundles dropped to make room for higher-priority ones
+    pub bundles_evicted: u64,
+}
+
+/// Message delivered to the application together with its provenance
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReceivedMessage<'a> {
+    /// Message payload - borrowed from the receive buffer
+    pub payload: &'a [u8],
+    
+    /// Whether the frame carried a verified commander signature
+    pub authority: Authority,
+}
+
+/// Where the payload of an accepted frame lives
+enum Delivery {
+    /// Inside the receive buffer
+    InFrame(Range<usize>),
+    
+    /// Rebuilt from several frames
+    Reassembled(Vec<u8>),
+}
+
 /// High-level OVP client interface for drone swarm communication
 /// 
 /// Provides a simple, efficient API for drone-to-drone messaging while
@@ -491,10 +960,147 @@ pub struct OVP {
     my_drone_id: DroneId,
     
     /// Background thread handle for message reception (optional)
+    #[allow(dead_code)]
     receiver_handle: Option<thread::JoinHandle<()>>,
     
     /// Channel for receiving messages from background thread
+    #[allow(dead_code)]
     message_rx: Receiver<Vec<u8>>,
+    
+    /// Optional features selected at construction
+    config: OvpConfig,
+    
+    /// Running protocol counters
+    stats: OvpStats,
+    
+    /// Session keys once enrolled (None = not a swarm member)
+    membership: Option<Membership>,
+    
+    /// Nonce of our outstanding JOIN_REQUEST while `enroll` is waiting
+    pending_join: Option<[u8; NONCE_SIZE]>,
+    
+    /// Drone IDs whose frames are dropped
+    revoked: HashSet<DroneId>,
+    
+    /// Signed timestamps already accepted, against replayed commands
+    replay_guard: ReplayGuard,
+    
+    /// Timestamp of our last signature, so ours strictly increase
+    last_signature: u64,
+    
+    /// Our own pseudonyms for recent epochs (ghost mode)
+    pseudonym_cache: PseudonymCache,
+    
+    /// Sequence number stamped on the next sequenced frame
+    next_sequence: u32,
+    
+    /// Reliable emission waiting for its ACK
+    awaiting: Option<AckWaiter>,
+    
+    /// Messages waiting for `receive_message`, highest priority first
+    inbox: PriorityQueue<(Vec<u8>, Authority)>,
+    
+    /// Backing storage for the inbox message last handed out
+    delivered: Vec<u8>,
+    
+    /// Sequence number of our next reliable broadcast
+    next_broadcast_sequence: u32,
+    
+    /// Our recent reliable broadcasts, kept for NACK repair
+    repair_buffer: RepairBuffer,
+    
+    /// Earliest time of our next session heartbeat (pushed back by every broadcast)
+    next_heartbeat: Instant,
+    
+    /// Session heartbeats still owed for our newest broadcast
+    heartbeats_left: u32,
+    
+    /// Gap tracking for other drones' reliable broadcasts
+    streams: StreamTracker,
+    
+    /// Identifier of our next multi-frame message
+    next_message_id: u32,
+    
+    /// Partially received FEC groups
+    fec_decoder: FecDecoder,
+    
+    /// Partially received fragmented messages
+    reassembler: Reassembler,
+    
+    /// Recently delivered frames, if duplicate suppression is enabled
+    dedup: Option<DuplicateCache>,
+    
+    /// Next sequence number of each of our ordered message types
+    ordered_sequences: HashMap<MessageType, u32>,
+    
+    /// Reorder buffers and newest-seen marks of other drones' ordered streams
+    ordering: OrderingBuffer,
+    
+    /// Frames the socket could not take yet, highest priority first
+    tx_queue: PriorityQueue<Vec<u8>>,
+    
+    /// Class whose `SO_PRIORITY` the socket currently sends with
+    socket_priority: Priority,
+    
+    /// Token buckets enforcing `OvpConfig::rate_limit`
+    rate_limiter: RateLimiter,
+    
+    /// Load estimate from everything heard on the channel
+    channel: ChannelMonitor,
+    
+    /// Earliest time of our next beacon
+    next_beacon: Instant,
+    
+    /// Beacon interval randomisation
+    beacon_jitter: Jitter,
+    
+    /// Multi-hop frames seen recently, keyed with their hop section zeroed
+    multihop_seen: DuplicateCache,
+    
+    /// Rebroadcasts waiting for their jitter delay
+    relay_queue: RelayQueue,
+    
+    /// Relay delay randomisation and gossip coin flips
+    relay_jitter: Jitter,
+    
+    /// Our position as last reported by the application
+    position: Option<Position>,
+    
+    /// Drones heard directly, with pending add/remove events
+    neighbor_table: NeighborTable,
+    
+    /// Earliest time of our next HELLO
+    next_hello: Instant,
+    
+    /// Hardware address of our interface, announced in HELLOs
+    mac: [u8; 6],
+    
+    /// Number of our next HELLO
+    hello_sequence: u16,
+    
+    /// Signal strength of the frame in the receive buffer, if the interface reported it
+    frame_rssi: Option<i8>,
+    
+    /// Routes, discoveries in progress and recently seen route requests
+    routing_table: RoutingTable,
+    
+    /// Our destination sequence number for route discovery
+    route_sequence: u32,
+    
+    /// Identifier of our next route request
+    next_request_id: u32,
+    
+    /// Geocasts handled (relay sections zeroed), so each is relayed and delivered once
+    geocast_seen: DuplicateCache,
+    
+    /// Delay-tolerant bundles carried for the swarm
+    bundle_store: BundleStore,
+    
+    /// A neighbour appeared since our last summary vector
+    summary_due: bool,
+    
+    /// Multicast groups we joined
+    groups: HashSet<GroupId>,
 }
 
 impl OVP {
@@ -511,23 +1117,111 @@ impl OVP {
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut ovp = OVP::new("wlan0", 42)?;
+    /// # Ok(())
+    /// # }
     /// ```
     #[inline]
     pub fn new(interface: &str, my_drone_id: DroneId) -> Result<Self, Box<dyn std::error::Error>> {
-        let socket = OmegaSocket::new(interface)?;
+        Self::with_config(interface, my_drone_id, OvpConfig::default())
+    }
+
+    /// Create a new OVP client instance with optional protocol features
+    /// 
+    /// # Arguments
+    /// 
+    /// * `interface` - Network interface for drone communication (e.g., "wlan0")
+    /// * `my_drone_id` - Unique identifier for this drone in the swarm
+    /// * `config` - Trust store, signing key and other optional features
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{OVP, OvpConfig, TrustStore};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OvpConfig {
+    ///     trust_store: TrustStore::load("/etc/ovp/commanders.keys")?,
+    ///     ..OvpConfig::default()
+    /// };
+    /// let mut ovp = OVP::with_config("wlan0", 42, config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_config(interface: &str, my_drone_id: DroneId, config: OvpConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut socket = OmegaSocket::new(interface)?;
+        socket.checksum = config.checksum;
+        let dedup = config.dedup.map(DuplicateCache::new);
+        let bundle_store = BundleStore::new(config.dtn.unwrap_or_default());
+        let rate_limiter = RateLimiter::new(&config.rate_limit);
         let (_tx, rx) = channel();
         
         // Note: For multi-threaded usage, socket would need to be split into
         //       separate send/receive components. This simplified version assumes
         //       single-threaded usage for maximum performance in drone applications.
         
+        // The enrollment authority is a member of the swarm it admits
+        let membership = config.enrollment_authority.as_ref()
+            .map(|authority| Membership::new(*authority.swarm_key(), my_drone_id, authority.generation()));
+        
+        // Random starting sequence so a restarted drone is not mistaken for
+        // its previous incarnation
+        let mut sequences = [0u8; 12];
+        random::fill_random(&mut sequences)?;
+        
         Ok(OVP {
             socket,
             my_drone_id,
             receiver_handle: None,
             message_rx: rx,
+            config,
+            stats: OvpStats::default(),
+            membership,
+            pending_join: None,
+            revoked: HashSet::new(),
+            replay_guard: ReplayGuard::default(),
+            last_signature: 0,
+            pseudonym_cache: PseudonymCache::default(),
+            next_sequence: u32::from_le_bytes(sequences[..4].try_into().unwrap()),
+            awaiting: None,
+            inbox: PriorityQueue::default(),
+            delivered: Vec::new(),
+            next_broadcast_sequence: u32::from_le_bytes(sequences[4..8].try_into().unwrap()),
+            repair_buffer: RepairBuffer::default(),
+            next_heartbeat: Instant::now(),
+            heartbeats_left: 0,
+            streams: StreamTracker::new(),
+            next_message_id: u32::from_le_bytes(sequences[8..].try_into().unwrap()),
+            fec_decoder: FecDecoder::default(),
+            reassembler: Reassembler::default(),
+            dedup,
+            ordered_sequences: HashMap::new(),
+            ordering: OrderingBuffer::default(),
+            tx_queue: PriorityQueue::default(),
+            socket_priority: Priority::Normal,
+            rate_limiter,
+            channel: ChannelMonitor::new(Instant::now()),
+            next_beacon: Instant::now(),
+            beacon_jitter: Jitter::new(),
+            multihop_seen: DuplicateCache::new(DedupConfig::default()),
+            relay_queue: RelayQueue::default(),
+            relay_jitter: Jitter::new(),
+            position: None,
+            neighbor_table: NeighborTable::default(),
+            next_hello: Instant::now(),
+            mac: neighbor::interface_mac(interface).unwrap_or_default(),
+            hello_sequence: 0,
+            frame_rssi: None,
+            routing_table: RoutingTable::default(),
+            route_sequence: 0,
+            next_request_id: 0,
+            geocast_seen: DuplicateCache::new(DedupConfig::default()),
+            bundle_store,
+            summary_due: false,
+            groups: HashSet::new(),
         })
     }
 
@@ -549,6 +1243,9 @@ impl OVP {
     /// - **Unicast**: `emit(Some(&[drone_id]), payload)` - targets specific drone
     /// - **Multicast**: `emit(Some(&[id1, id2, id3]), payload)` - targets multiple drones
     /// 
+    /// Payloads of any size are accepted: anything that does not fit one
+    /// frame is split into fragments that receivers reassemble transparently.
+    /// 
     /// # Returns
     /// 
     /// Result indicating successful emission or transmission error
@@ -561,16 +1258,72 @@ impl OVP {
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
     /// // Broadcast emergency stop to all drones
     /// ovp.emit(None, b"EMERGENCY_STOP")?;
     /// 
     /// // Send position update to specific drones
     /// ovp.emit(Some(&[1, 2, 3]), b"POS:123.45,67.89,10.0")?;
+    /// # Ok(())
+    /// # }
     /// ```
     #[inline(always)]
     pub fn emit(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.emit_with_priority(neighbours, payload, Priority::Normal)
+    }
+
+    /// Emit a frame in a given priority class
+    /// 
+    /// Like `emit`, but frames other than `Normal` carry their class on the
+    /// wire and go out with the matching kernel priority (`SO_PRIORITY`).
+    /// When the socket cannot take a frame right away it waits in a
+    /// per-class queue that `service` drains strictly highest class first,
+    /// so an emergency stop overtakes queued telemetry. Receivers with
+    /// several frames pending deliver higher classes first. The frame is
+    /// charged to the node and class budgets of `OvpConfig::rate_limit`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `payload` - Message data to transmit
+    /// * `priority` - Traffic class of the message
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating the frame was sent, queued or dropped by its budget,
+    /// or an error (`WouldExceedBudget` in `ThrottleMode::Reject`)
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{Priority, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// ovp.emit_with_priority(None, b"EMERGENCY_STOP", Priority::Emergency)?;
+    /// ovp.emit_with_priority(None, &[0u8; 4096], Priority::Bulk)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_with_priority(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8], priority: Priority) -> Result<(), Box<dyn std::error::Error>> {
         let targets = neighbours.unwrap_or(&[]);
+        let flags = Self::priority_flags(priority);
+        
+        // Oversized payloads are split into fragments
+        if payload.len() > self.max_payload(flags, self.target_words(targets)) {
+            return self.emit_fragmented(FrameMeta { flags, priority, ..FrameMeta::default() }, targets, payload);
+        }
+        
+        // Enrolled drones must stamp origin and membership tag on every frame,
+        // ghost mode must rewrite every identifier, prioritised or queued
+        // traffic goes through the transmit queue, budgets must be charged
+        if self.membership.is_some() || self.config.pseudonyms.is_some() || flags != 0
+            || !self.tx_queue.is_empty() || self.socket_priority != Priority::Normal
+            || !self.rate_limiter.is_unlimited() {
+            return self.emit_frame(FrameMeta { flags, priority, ..FrameMeta::default() }, targets, payload);
+        }
         
         // ONE SPHERICAL EMIT - REACHES ALL SPECIFIED TARGETS IN RANGE
         // The magic of volumetric broadcasting - one transmission, multiple recipients
@@ -581,8 +1334,7 @@ impl OVP {
 
     /// Attempt to receive a message intended for this drone
     /// 
-    /// Non-blocking reception with optimized parsing for maximum throughput.
-    /// Uses early-exit parsing to minimize CPU usage on irrelevant frames.
+    /// Non-blocking; `receive_message` without the provenance.
     /// 
     /// # Returns
     /// 
@@ -591,29 +1343,2099 @@ impl OVP {
     /// 
     /// # Performance
     /// 
-    /// - Zero allocation parsing using unsafe pointer operations
-    /// - Early exit on invalid or irrelevant frames
-    /// - Direct slice return from receive buffer (zero-copy)
+    /// - Runs due protocol timers first (`service`): NACKs, session
+    ///   heartbeats, relays and HELLOs may be transmitted from this call
+    /// - Frames not addressed to us exit early, after the target scan
+    /// - Signatures, membership tags and checksums are verified before delivery
+    /// - A lone frame's payload is returned straight from the receive buffer;
+    ///   when several frames are pending, or ordering or reassembly holds
+    ///   them back, payloads are copied into the owned inbox first
     #[inline(always)]
     pub fn try_receive(&mut self) -> Option<&[u8]> {
-        match self.socket.receive_frame() {
-            Ok(frame) => parse_ovp_frame_fast(frame, self.my_drone_id),
-            Err(_) => None,
-        }
+        self.receive_message().map(|message| message.payload)
     }
-}
-impl Clone for OVP {
-    fn clone(&self) -> Self {
-        // Create a new OVP instance with the same socket and drone ID
-        // Note: This creates a new receiver channel since we can't clone the existing one
-        let (tx, rx) = std::sync::mpsc::channel();
-        
-        // Create a new OVP instance with the cloned socket and new receiver
-        OVP {
-            socket: self.socket.clone(),
-            my_drone_id: self.my_drone_id,
-            receiver_handle: None, // New instance won't have a receiver handle
+
+    /// Emit a frame carrying an Ed25519 signature from this node's commander key
+    /// 
+    /// Intended for authority-critical commands (emergency stop, return-to-home,
+    /// geofence updates). Receivers holding the matching public key in their
+    /// trust store deliver the message as `Authority::Signed`; everyone else
+    /// drops it.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `payload` - Command data to sign and transmit
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission, or an error if no signing key
+    /// was configured or the signed frame would exceed the maximum size
+    pub fn emit_signed(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.emit_with_flags(FLAG_SIGNED, neighbours.unwrap_or(&[]), payload)
+    }
+
+    /// Attempt to receive a message intended for this drone, with provenance
+    /// 
+    /// Like `try_receive`, but also reports whether the frame carried a valid
+    /// commander signature. Signed frames whose key is not in the trust store,
+    /// or whose signature does not match, are rejected and counted in
+    /// `OvpStats::rejected_signatures`. Frames from revoked drone IDs and,
+    /// once enrolled, frames with bad membership tags are dropped as well, as
+    /// are repeats caught by the duplicate cache (`OvpConfig::dedup`).
+    /// Protocol control frames (enrollment, revocation, ACKs) are consumed
+    /// here and never surface to the application; frames requesting an ACK
+    /// are acknowledged automatically, and due protocol timers run on each
+    /// call (see `service`). Queued messages - those that arrived while
+    /// `emit_reliable` was waiting, or were released by an ordered stream -
+    /// are delivered first (see `has_pending`). When several frames are
+    /// pending they are pulled in together and delivered highest priority
+    /// first; `Emergency` frames are delivered the moment they are read.
+    /// 
+    /// # Returns
+    /// 
+    /// Option containing the message if a valid OVP frame for this drone was
+    /// received, None otherwise
+    pub fn receive_message(&mut self) -> Option<ReceivedMessage<'_>> {
+        self.service();
+        
+        if self.inbox.is_empty() {
+            let len = self.read_frame()?;
+            if let Some((delivery, authority, priority)) = self.accept_frame(len) {
+                // Nothing else pending, or nothing that could outrank it
+                if priority == Priority::Emergency || !self.socket.wait_readable(Duration::ZERO) {
+                    let payload = match delivery {
+                        Delivery::InFrame(range) => &self.socket.recv_buffer[range],
+                        Delivery::Reassembled(message) => {
+                            self.delivered = message;
+                            &self.delivered
+                        }
+                    };
+                    return Some(ReceivedMessage { payload, authority });
+                }
+                self.enqueue_delivery(delivery, authority, priority);
+                for _ in 1..RX_BATCH {
+                    if !self.socket.wait_readable(Duration::ZERO) {
+                        break;
+                    }
+                    self.receive_into_inbox();
+                }
+            }
+        }
+        
+        // The frame may have released queued messages instead
+        let (_, (payload, authority)) = self.inbox.pop()?;
+        self.delivered = payload;
+        Some(ReceivedMessage { payload: &self.delivered, authority })
+    }
+
+    /// True if messages are queued for delivery
+    /// 
+    /// `receive_message` returns queued messages without waiting for a frame.
+    /// Check this after `service`, which may release held ordered messages
+    /// without any frame arriving.
+    pub fn has_pending(&self) -> bool {
+        !self.inbox.is_empty()
+    }
+
+    /// Emit a frame to one drone and wait until it is acknowledged
+    /// 
+    /// The frame carries our origin and a fresh sequence number and asks the
+    /// target for an ACK. Without one it is retransmitted unchanged following
+    /// `OvpConfig::retransmit` (exponential backoff, bounded retries). Blocks
+    /// until the outcome is known; messages received meanwhile are queued
+    /// for `receive_message`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `target` - The drone that must acknowledge
+    /// * `payload` - Message data to transmit
+    /// 
+    /// # Returns
+    /// 
+    /// `Delivered` once acknowledged, `Rejected` if the target refused the
+    /// frame (signature or membership checks), `TimedOut` after the last
+    /// retry, or an error if the frame could not be emitted
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{DeliveryStatus, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// if ovp.emit_reliable(7, b"WAYPOINT:12")? != DeliveryStatus::Delivered {
+    ///     // Fall back to another relay
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_reliable(&mut self, target: DroneId, payload: &[u8]) -> Result<DeliveryStatus, Box<dyn std::error::Error>> {
+        let report = self.emit_reliable_multicast(&[target], payload)?;
+        Ok(report[0].1)
+    }
+
+    /// Emit one frame to a group and track which members acknowledge it
+    /// 
+    /// Keeps the single-emission efficiency of `emit(Some(&[id1, id2, id3]))`:
+    /// every retransmission is one frame whose target list is rewritten to
+    /// just the members that have not answered yet, so the group shrinks as
+    /// ACKs arrive. Retries follow `OvpConfig::retransmit`; messages received
+    /// meanwhile are queued for `receive_message`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `targets` - Group members that must acknowledge (at least one)
+    /// * `payload` - Message data to transmit
+    /// 
+    /// # Returns
+    /// 
+    /// One `(target, status)` entry per listed target, in order, or an error
+    /// if the target list is empty or the frame could not be emitted
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{DeliveryStatus, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// for (drone, status) in ovp.emit_reliable_multicast(&[1, 2, 3], b"FORMATION:V")? {
+    ///     if status != DeliveryStatus::Delivered {
+    ///         println!("drone {drone} missed the formation change: {status:?}");
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_reliable_multicast(&mut self, targets: &[DroneId], payload: &[u8]) -> Result<Vec<(DroneId, DeliveryStatus)>, Box<dyn std::error::Error>> {
+        if targets.is_empty() {
+            return Err("Reliable emission needs at least one target".into());
+        }
+        
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_ACK_REQUEST, sequence, ..FrameMeta::default() };
+        
+        self.awaiting = Some(AckWaiter::new(sequence, targets));
+        let result = self.retransmit_until_acked(meta, payload);
+        let waiter = self.awaiting.take().expect("waiter is only cleared here");
+        result.map(|()| waiter.finish())
+    }
+
+    /// Broadcast a frame that every receiver repairs via NACKs
+    /// 
+    /// Scales to hundreds of drones where per-receiver ACKs would not:
+    /// receivers detect gaps in this sender's broadcast sequence and request
+    /// the missing frames, suppressing duplicate requests from neighbours.
+    /// The last `OvpConfig::nack.repair_buffer` broadcasts are kept for
+    /// retransmission. Does not block.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `payload` - Message data to disseminate
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission or transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 1)?;
+    /// ovp.emit_reliable_broadcast(b"MISSION:survey-grid-7")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_reliable_broadcast(&mut self, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let sequence = self.next_broadcast_sequence;
+        self.next_broadcast_sequence = self.next_broadcast_sequence.wrapping_add(1);
+        
+        self.emit_frame(Self::broadcast_meta(sequence), &[], payload)?;
+        let now = Instant::now();
+        self.repair_buffer.push(sequence, payload, self.config.nack.repair_buffer, now);
+        self.next_heartbeat = now + self.config.nack.heartbeat_interval;
+        self.heartbeats_left = self.config.nack.max_heartbeats;
+        Ok(())
+    }
+
+    /// Emit a frame belonging to one of this drone's ordered streams
+    /// 
+    /// Each message type is its own stream with its own sequence numbers.
+    /// Receivers that list the type in `OvpConfig::ordering` deliver its
+    /// frames in order: `Reorder` holds frames behind a gap until it fills or
+    /// times out, `LatestWins` drops anything older than the newest frame
+    /// delivered. Other receivers deliver the frames as they arrive. A
+    /// receiver joins a stream at the first frame it hears.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `message_type` - Application-defined stream the message belongs to
+    /// * `payload` - Message data to transmit (must fit a single frame)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission or transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// const POSITION: u16 = 1;
+    /// ovp.emit_ordered(None, POSITION, b"POS:12.5,40.1,30.0")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_ordered(&mut self, neighbours: Option<&[DroneId]>, message_type: MessageType, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let sequence = match self.ordered_sequences.get(&message_type) {
+            Some(&sequence) => sequence,
+            None => {
+                // Random start, like our other sequence spaces
+                let mut start = [0u8; 4];
+                random::fill_random(&mut start)?;
+                u32::from_le_bytes(start)
+            }
+        };
+        self.ordered_sequences.insert(message_type, sequence.wrapping_add(1));
+        
+        let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_ORDERED, sequence, message_type, ..FrameMeta::default() };
+        self.emit_frame(meta, neighbours.unwrap_or(&[]), payload)
+    }
+
+    /// Emit a frame that relaying drones carry beyond our radio range
+    /// 
+    /// The frame carries our origin, a sequence number and a hop section
+    /// allowing `ttl` relays. Drones with `OvpConfig::relay` set rebroadcast
+    /// it once each, after a short random delay, with the TTL decremented -
+    /// whether or not they are among the targets - until the TTL is spent.
+    /// Every drone delivers the first copy it hears and drops the rest.
+    /// Oversized payloads are fragmented, each fragment relayed on its own.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `payload` - Message data to transmit
+    /// * `ttl` - Relays allowed (0 = one hop, like `emit`)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission or transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// // Reach the far end of a stretched-out formation over up to 4 relays
+    /// ovp.emit_multihop(Some(&[97]), b"RTB", 4)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_multihop(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8], ttl: u8) -> Result<(), Box<dyn std::error::Error>> {
+        let targets = neighbours.unwrap_or(&[]);
+        let hop = HopCount { ttl, hops: 0 };
+        // Our position lets relays judge how much a rebroadcast would add
+        let flags = FLAG_MULTIHOP | if self.position.is_some() { FLAG_POSITION } else { 0 };
+        let meta = FrameMeta { flags, hop, position: self.position, ..FrameMeta::default() };
+        if payload.len() > self.max_payload(flags | FLAG_ORIGIN | FLAG_SEQUENCE, self.target_words(targets)) {
+            return self.emit_fragmented(meta, targets, payload);
+        }
+        
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.emit_frame(FrameMeta { flags: flags | FLAG_ORIGIN | FLAG_SEQUENCE, sequence, ..meta }, targets, payload)
+    }
+
+    /// Send a payload to every member of one or more multicast groups
+    /// 
+    /// The frame lists the groups rather than their members, so it is the
+    /// same size whatever the team size, and listeners cannot tell who is in
+    /// a group. Drones receive it if they joined one of the groups (see
+    /// `join_group`). Oversized payloads are fragmented.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `groups` - Groups to address (at least one)
+    /// * `payload` - Message data to transmit
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission or transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{group_id, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// ovp.emit_to_groups(&[group_id("scouts")], b"REGROUP")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_to_groups(&mut self, groups: &[GroupId], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if groups.is_empty() {
+            return Err("No groups given - use emit(None, ..) to broadcast".into());
+        }
+        if payload.len() > self.max_payload(FLAG_GROUP, self.target_words(groups)) {
+            return self.emit_fragmented(FrameMeta { flags: FLAG_GROUP, ..FrameMeta::default() }, groups, payload);
+        }
+        self.emit_with_flags(FLAG_GROUP, groups, payload)
+    }
+
+    /// Receive frames addressed to `group` from now on
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{group_id, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// ovp.join_group(group_id("scouts"));
+    /// ovp.join_group(0x0000_A1FA); // numbering scheme of your own
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn join_group(&mut self, group: GroupId) {
+        self.groups.insert(group);
+    }
+
+    /// Stop receiving frames addressed to `group`, returning false if we were
+    /// not a member
+    pub fn leave_group(&mut self, group: GroupId) -> bool {
+        self.groups.remove(&group)
+    }
+
+    /// Groups joined with `join_group`, in ascending order
+    pub fn groups(&self) -> Vec<GroupId> {
+        let mut groups: Vec<GroupId> = self.groups.iter().copied().collect();
+        groups.sort_unstable();
+        groups
+    }
+
+    /// Drones currently in range, by identifier
+    /// 
+    /// Requires `OvpConfig::neighbors`. A drone is listed from the first
+    /// frame heard directly from it until it was silent for
+    /// `NeighborConfig::expiry`; MAC, position and link quality come from its
+    /// HELLO beacons. Identifiers are as seen on the wire (pseudonyms in
+    /// ghost mode).
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{NeighborConfig, OvpConfig, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OvpConfig { neighbors: Some(NeighborConfig::default()), ..OvpConfig::default() };
+    /// let mut ovp = OVP::with_config("wlan0", 42, config)?;
+    /// ovp.service();
+    /// for neighbor in ovp.neighbors() {
+    ///     println!("{} quality {:.2}", neighbor.id, neighbor.link_quality);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn neighbors(&self) -> Vec<Neighbor> {
+        self.neighbor_table.snapshot(Instant::now())
+    }
+
+    /// Next change in the set of drones in range, oldest first
+    /// 
+    /// Additions are queued as frames arrive, removals by `service` once a
+    /// neighbour expired. Undelivered events beyond 256 are dropped.
+    pub fn next_neighbor_event(&mut self) -> Option<NeighborEvent> {
+        self.neighbor_table.next_event()
+    }
+
+    /// Send a payload to a drone that may be several hops away
+    /// 
+    /// Requires `OvpConfig::routing` on this drone and the ones between.
+    /// With a known route the frame goes to the next hop right away;
+    /// otherwise it is queued while a route is discovered and sent from
+    /// `receive_message` or `service` once the reply arrives. Routes break
+    /// and are rediscovered as drones move (see `routes`).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `destination` - Drone to deliver to
+    /// * `payload` - Message contents (must fit a single frame)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating the frame was sent or queued, or an error if
+    /// routing is disabled, ghost mode is on or the payload is too large
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{OvpConfig, RoutingConfig, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OvpConfig { routing: Some(RoutingConfig::default()), ..OvpConfig::default() };
+    /// let mut ovp = OVP::with_config("wlan0", 42, config)?;
+    /// ovp.emit_routed(97, b"WAYPOINT:12,40,30")?;
+    /// loop {
+    ///     ovp.service();
+    ///     while let Some(message) = ovp.try_receive() {
+    ///         // handle message
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn emit_routed(&mut self, destination: DroneId, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(routing) = self.config.routing else {
+            return Err("Routing disabled - set OvpConfig::routing".into());
+        };
+        if self.config.pseudonyms.is_some() {
+            return Err("Routing is unavailable in ghost mode".into());
+        }
+        
+        let inner = self.build_inner_frame(&[destination], payload, Priority::Normal, ROUTED_HEADER_SIZE)?;
+        let now = Instant::now();
+        match self.routing_table.next_hop(destination, now, now + routing.route_timeout) {
+            Some(next_hop) => {
+                if !self.spend_budget(inner.len(), Priority::Normal)? {
+                    return Ok(());
+                }
+                self.send_routed(next_hop, destination, &inner)
+            }
+            None => {
+                let deadline = now + routing.discovery_timeout;
+                let (started, dropped) = self.routing_table.queue(destination, inner, deadline, routing.discovery_retries);
+                self.stats.routing_failures += dropped as u64;
+                if started {
+                    self.send_route_request(destination, routing.max_hops);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Routing table entries, by destination
+    /// 
+    /// For debugging: lists valid routes and broken ones kept until they
+    /// expire, with next hop, length and destination sequence number.
+    pub fn routes(&self) -> Vec<Route> {
+        self.routing_table.snapshot()
+    }
+
+    /// Send a payload toward a place rather than a drone
+    /// 
+    /// Requires `OvpConfig::geo` and `OvpConfig::neighbors` on this drone and
+    /// the ones between, each with its position set (`set_position`). Every
+    /// drone passes the frame to the neighbour closest to the destination,
+    /// walking around voids with no closer neighbour, until a drone within
+    /// `destination.radius` delivers it.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `destination` - Point or sphere the frame is for
+    /// * `payload` - Message contents (must fit a single frame)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating the frame was sent, or an error if geographic
+    /// forwarding is disabled, our position is unknown, we are at the
+    /// destination already or no neighbour can take the frame
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{GeoConfig, GeoDestination, NeighborConfig, OvpConfig, Position, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OvpConfig {
+    ///     geo: Some(GeoConfig::default()),
+    ///     neighbors: Some(NeighborConfig::default()),
+    ///     ..OvpConfig::default()
+    /// };
+    /// let mut ovp = OVP::with_config("wlan0", 42, config)?;
+    /// ovp.set_position(Position::new(0.0, 0.0, 30.0));
+    /// // Whoever is over the landing zone
+    /// let zone = GeoDestination::sphere(Position::new(800.0, 120.0, 30.0), 25.0);
+    /// ovp.emit_geo(zone, b"SURVEY:start")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_geo(&mut self, destination: GeoDestination, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if self.config.geo.is_none() {
+            return Err("Geographic forwarding disabled - set OvpConfig::geo".into());
+        }
+        if self.config.pseudonyms.is_some() {
+            return Err("Geographic forwarding is unavailable in ghost mode".into());
+        }
+        if self.position.is_none() {
+            return Err("Own position unknown - call set_position first".into());
+        }
+        
+        let header = GeoHeader { destination, sender: None, perimeter: None };
+        match self.geo_step(&header, None) {
+            GeoStep::Forward(next_hop, header) => {
+                let inner = self.build_inner_frame(&[], payload, Priority::Normal, geo::MAX_HEADER_SIZE)?;
+                if !self.spend_budget(inner.len(), Priority::Normal)? {
+                    return Ok(());
+                }
+                self.send_geo(next_hop, header, &inner)
+            }
+            GeoStep::Deliver => Err("Already within the destination".into()),
+            GeoStep::Drop => {
+                self.stats.geo_dropped += 1;
+                Err("No neighbour with a known position to forward to".into())
+            }
+        }
+    }
+
+    /// Send a payload that drones carry until it can be delivered
+    /// 
+    /// Requires `OvpConfig::dtn`. Every drone with it enabled stores the
+    /// message for `lifetime` and hands it to the drones it meets later
+    /// (discovered through `OvpConfig::neighbors`), so drones out of range
+    /// when it was sent still receive it once they come back. When storage
+    /// runs short, lower-priority messages are dropped first.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `payload` - Message contents (must fit a single frame)
+    /// * `priority` - Traffic class of the message, which storage keeps longest
+    /// * `lifetime` - How long the swarm keeps it (at most `DtnConfig::max_lifetime`)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission, or an error if store-and-forward
+    /// is disabled or the payload is too large
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{DtnConfig, NeighborConfig, OvpConfig, Priority, OVP};
+    /// # use std::time::Duration;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OvpConfig {
+    ///     dtn: Some(DtnConfig::default()),
+    ///     neighbors: Some(NeighborConfig::default()),
+    ///     ..OvpConfig::default()
+    /// };
+    /// let mut ovp = OVP::with_config("wlan0", 42, config)?;
+    /// // Scouts still out of range pick it up when they return
+    /// ovp.emit_stored(None, b"MISSION:v7", Priority::High, Duration::from_secs(600))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_stored(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8], priority: Priority, lifetime: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(dtn) = self.config.dtn else {
+            return Err("Store-and-forward is disabled - set OvpConfig::dtn".into());
+        };
+        let targets = neighbours.unwrap_or(&[]);
+        let inner = self.build_inner_frame(targets, payload, priority, BUNDLE_HEADER_SIZE)?;
+        let id = Self::bundle_id(&inner).ok_or("Malformed bundle")?;
+        
+        // We carry our own bundles too, which also drops them when they come back
+        let now = Instant::now();
+        if let Some(evicted) = self.bundle_store.insert(id, inner.clone(), priority, lifetime, now) {
+            self.stats.bundles_evicted += evicted as u64;
+        }
+        let remaining = self.bundle_store.take_offer(id, now).map_or(lifetime.min(dtn.max_lifetime), |(_, remaining, _)| remaining);
+        if !self.spend_budget(inner.len(), priority)? {
+            return Ok(());
+        }
+        self.send_bundle(&inner, remaining)
+    }
+
+    /// Send a payload to every drone inside a region
+    /// 
+    /// Receivers deliver it only if the position their application reported
+    /// (`set_position`) lies inside `region`. With `OvpConfig::geocast` set,
+    /// drones inside the region or nearer to it than the previous sender
+    /// relay it, up to `GeocastConfig::max_hops` times, so the region need not
+    /// be within our radio range.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `region` - Sphere, box or polygon the frame is for
+    /// * `payload` - Message contents (must fit a single frame)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission or transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{GeoRegion, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// // Everyone over the field, up to 120 m
+    /// let field = GeoRegion::Polygon {
+    ///     vertices: vec![(0.0, 0.0), (200.0, 0.0), (200.0, 80.0), (0.0, 80.0)],
+    ///     floor: 0.0,
+    ///     ceiling: 120.0,
+    /// };
+    /// ovp.emit_geocast(&field, b"HOLD")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_geocast(&mut self, region: &GeoRegion, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        region.validate()?;
+        let ttl = self.config.geocast.map_or(0, |geocast| geocast.max_hops);
+        let header = GeocastHeader { ttl, sender: self.position, region: region.clone() };
+        let mut body = header.to_bytes();
+        let inner = self.build_inner_frame(&[], payload, Priority::Normal, body.len())?;
+        
+        // Never relay or deliver our own geocast when it comes back
+        let (key, _) = self.geocast_key(&inner).ok_or("Malformed geocast frame")?;
+        self.geocast_seen.check(key, Instant::now());
+        if !self.spend_budget(inner.len(), Priority::Normal)? {
+            return Ok(());
+        }
+        body.extend_from_slice(&inner);
+        self.emit_control(0, &[], frame::CONTROL_GEOCAST, &body)
+    }
+
+    /// Report this drone's current position
+    /// 
+    /// Positions are metres in a local frame the whole swarm shares. Once
+    /// set, multi-hop frames we send or relay carry it, so relays using
+    /// `RelayPolicy::Distance` can tell how close the last transmitter was,
+    /// and our HELLOs announce it to neighbours. Update it as the drone moves.
+    pub fn set_position(&mut self, position: Position) {
+        self.position = Some(position);
+    }
+
+    /// Broadcast a periodic status beacon if one is due
+    /// 
+    /// Call as often as convenient; a beacon only goes out once the current
+    /// beacon interval has passed since the last one. The interval adapts to
+    /// channel load (see `channel_load`) between `OvpConfig::congestion`'s
+    /// `min_interval` and `max_interval`, with up to 10% random jitter, so
+    /// beacons thin out automatically on a crowded channel.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `payload` - Beacon contents (position, battery, state...)
+    /// 
+    /// # Returns
+    /// 
+    /// true if the beacon was emitted, false if it is not due yet, or a
+    /// transmission error
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::OVP;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// loop {
+    ///     ovp.emit_beacon(b"STATUS:ok,batt=81")?;
+    ///     while let Some(message) = ovp.try_receive() {
+    ///         // handle message
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn emit_beacon(&mut self, payload: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+        let now = Instant::now();
+        if now < self.next_beacon {
+            return Ok(false);
+        }
+        
+        self.emit(None, payload)?;
+        let interval = self.beacon_interval();
+        // Jitter keeps drones that started together from colliding forever
+        self.next_beacon = now + interval + self.beacon_jitter.below(interval / 10);
+        self.stats.beacons_sent += 1;
+        Ok(true)
+    }
+
+    /// Estimated channel load, from 0 (idle) to 1 (saturated)
+    /// 
+    /// Derived from every frame received in promiscuous mode (from all
+    /// sources) relative to `OvpConfig::congestion.channel_capacity`, plus
+    /// loss indicators (corrupted frames, NACKs, retransmissions). Only
+    /// frames the application actually reads are counted.
+    pub fn channel_load(&self) -> f64 {
+        self.channel.load(Instant::now(), &self.config.congestion)
+    }
+
+    /// Current interval between beacons sent by `emit_beacon`
+    pub fn beacon_interval(&self) -> Duration {
+        self.config.congestion.interval(self.channel_load())
+    }
+
+    /// Emit a message as an erasure-coded group of frames
+    /// 
+    /// The message is split into `params.data_shards` frames plus
+    /// `params.repair_shards` parity frames; receivers rebuild it from any
+    /// `data_shards` of them, so burst losses up to `repair_shards` frames
+    /// need no retransmission. Choose `params` per message class. Receivers
+    /// deliver the whole message once (incomplete groups expire after 2 s).
+    /// 
+    /// # Arguments
+    /// 
+    /// * `neighbours` - Optional specific target drones (None = broadcast to all)
+    /// * `payload` - Message data to protect
+    /// * `params` - Erasure code shape (K data + M repair frames)
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating successful emission, or an error if `params` is
+    /// invalid or a shard would exceed the maximum frame size
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// # use ovp::{FecParams, OVP};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut ovp = OVP::new("wlan0", 42)?;
+    /// // Survives any two of six frames being lost
+    /// ovp.emit_fec(None, &[0u8; 3000], FecParams::new(4, 2))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_fec(&mut self, neighbours: Option<&[DroneId]>, payload: &[u8], params: FecParams) -> Result<(), Box<dyn std::error::Error>> {
+        let targets = neighbours.unwrap_or(&[]);
+        let shards = fec::encode(payload, params)?;
+        let group = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        
+        // Shards are equally sized: an oversized group fails on its first frame
+        for (index, shard) in shards.iter().enumerate() {
+            let header = ShardHeader {
+                group,
+                index: index as u8,
+                data_shards: params.data_shards,
+                repair_shards: params.repair_shards,
+            };
+            let meta = FrameMeta { flags: FLAG_ORIGIN | FLAG_FEC, shard: header, ..FrameMeta::default() };
+            self.emit_frame(meta, targets, shard)?;
+        }
+        Ok(())
+    }
+
+    /// Join the swarm by proving possession of the provisioned credential
+    /// 
+    /// Broadcasts a JOIN_REQUEST and waits for the enrollment authority's
+    /// JOIN_ACCEPT carrying the swarm key. From then on every emitted frame
+    /// carries this drone's ID and a membership tag. Frames received while
+    /// waiting are consumed.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `timeout` - How long to wait for the authority to answer
+    /// 
+    /// # Returns
+    /// 
+    /// Ok once enrolled, or an error if no credential is configured or no
+    /// valid answer arrived in time (the request may simply be retried)
+    pub fn enroll(&mut self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let credential = self.config.credential.as_ref()
+            .ok_or("No device credential configured - set OvpConfig::credential")?;
+        
+        let mut nonce = [0u8; NONCE_SIZE];
+        random::fill_random(&mut nonce)?;
+        let request = enrollment::join_request(credential, self.my_drone_id, &nonce);
+        
+        self.membership = None;
+        self.pending_join = Some(nonce);
+        self.emit_control(0, &[], frame::CONTROL_JOIN_REQUEST, &request)?;
+        
+        let deadline = Instant::now() + timeout;
+        while self.membership.is_none() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                self.pending_join = None;
+                return Err("Enrollment timed out - no enrollment authority answered".into());
+            }
+            if self.socket.wait_readable(remaining) {
+                self.receive_message();
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Revoke a drone ID swarm-wide
+    /// 
+    /// Emits a signed REVOKE control frame; every receiver that trusts this
+    /// node's commander key drops the revoked ID's frames from then on. On the
+    /// enrollment authority the ID is also deprovisioned so it cannot re-enroll,
+    /// and the swarm key is rotated: REKEY frames hand the next key to every
+    /// drone still provisioned, so the revoked device can no longer tag frames
+    /// under any ID once the old key's grace period ends.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `drone_id` - The compromised drone ID
+    /// 
+    /// # Returns
+    /// 
+    /// Result indicating the revocation was emitted, or an error if this node
+    /// has no signing key
+    pub fn revoke(&mut self, drone_id: DroneId) -> Result<(), Box<dyn std::error::Error>> {
+        self.emit_control(FLAG_SIGNED, &[], frame::CONTROL_REVOKE, &drone_id.to_le_bytes())?;
+        self.apply_revocation(drone_id);
+        Ok(())
+    }
+
+    /// Run protocol timers that are due
+    /// 
+    /// Sends NACKs for gaps in reliable broadcasts and the session heartbeats
+    /// owed for our own latest broadcast, expires incomplete
+    /// FEC groups and fragmented messages, releases ordered messages
+    /// whose reorder timeout passed (see `has_pending`), sends relays
+    /// whose jitter delay passed, and sends HELLOs and expires silent
+    /// neighbours when discovery is enabled. Called by every
+    /// `receive_message`; applications that may go a while without receiving
+    /// should also call it periodically (every few milliseconds).
+    pub fn service(&mut self) {
+        self.send_due_nacks();
+        let now = Instant::now();
+        if self.heartbeats_left > 0 && let Some(newest) = self.repair_buffer.newest() && now >= self.next_heartbeat {
+            // Back off exponentially so a quiet sender soon falls silent
+            self.heartbeats_left -= 1;
+            let sent = (self.config.nack.max_heartbeats - self.heartbeats_left).min(16);
+            self.next_heartbeat = now + self.config.nack.heartbeat_interval.saturating_mul(1 << sent);
+            if self.emit_control(0, &[], frame::CONTROL_SESSION, &newest.to_le_bytes()).is_ok() {
+                self.stats.heartbeats_sent += 1;
+            }
+        }
+        self.stats.fec_failed += self.fec_decoder.expire(now, fec::GROUP_TIMEOUT) as u64;
+        self.stats.reassembly_failures += self.reassembler.expire(now, self.config.reassembly.timeout) as u64;
+        let (released, skipped) = self.ordering.expire(now);
+        self.stats.ordering_gaps_skipped += skipped as u64;
+        for (payload, authority, priority) in released {
+            self.enqueue(payload, authority, priority);
+        }
+        self.send_due_relays();
+        if let Some(neighbors) = self.config.neighbors {
+            // Routes through a neighbour that fell silent are broken
+            for neighbor in self.neighbor_table.expire(now, neighbors.expiry) {
+                let lost = self.routing_table.break_link(neighbor);
+                self.send_route_error(&lost);
+            }
+            if now >= self.next_hello {
+                self.send_hello(neighbors.hello_interval);
+                let interval = neighbors.hello_interval;
+                self.next_hello = now + interval + self.beacon_jitter.below(interval / 10);
+            }
+        }
+        if let Some(routing) = self.config.routing {
+            self.maintain_routes(routing, now);
+        }
+        if self.config.dtn.is_some() {
+            self.bundle_store.expire(now);
+            if mem::take(&mut self.summary_due) {
+                self.send_summary();
+            }
+        }
+        self.flush_tx();
+    }
+
+    /// True once this drone holds swarm session keys
+    pub fn is_enrolled(&self) -> bool {
+        self.membership.is_some()
+    }
+
+    /// Protocol counters accumulated since construction
+    pub fn stats(&self) -> &OvpStats {
+        &self.stats
+    }
+
+    /// Build a frame with optional sections and trailers and emit it
+    /// 
+    /// Enrolled drones always add their origin and membership tag; ghost mode
+    /// swaps origin and targets for pseudonyms (except on enrollment and
+    /// revocation control frames, which must bind real IDs); a `FLAG_SIGNED` request
+    /// appends the commander signature last.
+    fn emit_with_flags(&mut self, flags: u16, targets: &[DroneId], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.emit_frame(FrameMeta { flags, ..FrameMeta::default() }, targets, payload)
+    }
+
+    /// Build and emit a frame from explicit header metadata
+    /// 
+    /// `meta.source` and `meta.epoch` are filled in here; `meta.sequence`
+    /// is written as given so retransmissions reuse their sequence number.
+    fn emit_frame(&mut self, meta: FrameMeta, targets: &[DroneId], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let (flags, priority) = (meta.flags, meta.priority);
+        let len = self.build_frame(meta, targets, payload)?;
+        
+        // Control frames keep the protocol working and are never throttled
+        if flags & FLAG_CONTROL == 0 && !self.spend_budget(len, priority)? {
+            return Ok(());
+        }
+        self.transmit(len, priority)
+    }
+
+    /// Write a complete frame into the send buffer, returning its length
+    fn build_frame(&mut self, mut meta: FrameMeta, targets: &[DroneId], payload: &[u8]) -> Result<usize, Box<dyn std::error::Error>> {
+        let real_ids = meta.flags & FLAG_CONTROL != 0 && payload.first().is_some_and(|&kind| frame::binds_real_ids(kind));
+        meta.flags = self.wire_flags(meta.flags, real_ids);
+        let flags = meta.flags;
+        meta.source = self.my_drone_id;
+        let scheme = self.config.pseudonyms.as_ref().filter(|_| !real_ids);
+        if let Some(scheme) = scheme {
+            meta.epoch = scheme.current_epoch();
+            meta.source = self.pseudonym_cache.get(scheme, self.my_drone_id, meta.epoch);
+        }
+        
+        let signing_key = if flags & FLAG_SIGNED != 0 {
+            Some(self.config.signing_key.as_ref()
+                .ok_or("No signing key configured - set OvpConfig::signing_key")?)
+        } else {
+            None
+        };
+        
+        // Tags and signatures cover the relay sections zeroed, as relays see them
+        let (hop, position) = (mem::take(&mut meta.hop), meta.position.take());
+        let pseudonyms = scheme.map(|scheme| scheme.pseudonymize_targets(targets, meta.epoch));
+        let buf = &mut self.socket.send_buffer[..];
+        let mut len = frame::write_frame(buf, &meta, pseudonyms.as_deref().unwrap_or(targets), payload)?;
+        if flags & FLAG_MULTIHOP != 0 {
+            // Never relay or deliver our own frame when it comes back
+            let key = self.multihop_seen.content_key(&buf[..len]);
+            self.multihop_seen.check(key, Instant::now());
+        }
+        if let Some(membership) = &self.membership {
+            len = membership.tag_frame(buf, len, meta.source);
+        }
+        if let Some(key) = signing_key {
+            self.last_signature = signing::now_micros().max(self.last_signature + 1);
+            len = signing::sign_frame(buf, len, key, self.last_signature);
+        }
+        if flags & FLAG_MULTIHOP != 0 {
+            frame::write_hop_count(buf, flags, hop);
+        }
+        if flags & FLAG_POSITION != 0 {
+            frame::write_position(buf, flags, position);
+        }
+        if meta.flags & FLAG_CHECKSUM != 0 {
+            len = checksum::append_checksum(buf, len);
+        }
+        Ok(len)
+    }
+
+    /// Charge a frame of `len` bytes to the transmit budgets
+    /// 
+    /// Waits, or returns false (drop) or `WouldExceedBudget` (reject) when
+    /// over budget, as `OvpConfig::rate_limit.mode` selects.
+    fn spend_budget(&mut self, len: usize, priority: Priority) -> Result<bool, Box<dyn std::error::Error>> {
+        let Err(mut wait) = self.rate_limiter.try_spend(priority, len, Instant::now()) else {
+            return Ok(true);
+        };
+        match self.config.rate_limit.mode {
+            ThrottleMode::Block => {
+                self.stats.throttled_delayed += 1;
+                loop {
+                    thread::sleep(wait);
+                    match self.rate_limiter.try_spend(priority, len, Instant::now()) {
+                        Ok(()) => return Ok(true),
+                        Err(remaining) => wait = remaining,
+                    }
+                }
+            }
+            ThrottleMode::Drop => {
+                self.stats.throttled_dropped += 1;
+                Ok(false)
+            }
+            ThrottleMode::Reject => {
+                self.stats.throttled_rejected += 1;
+                Err(Box::new(WouldExceedBudget { retry_after: wait }))
+            }
+        }
+    }
+
+    /// Send the first `len` bytes of the send buffer, or queue them
+    /// 
+    /// Frames are only sent directly while nothing is queued, so a class
+    /// never overtakes itself and higher classes always leave first.
+    fn transmit(&mut self, len: usize, priority: Priority) -> Result<(), Box<dyn std::error::Error>> {
+        if self.tx_queue.is_empty() {
+            self.apply_socket_priority(priority);
+            if self.socket.try_emit(&self.socket.send_buffer[..len])? {
+                return Ok(());
+            }
+        }
+        
+        self.stats.tx_deferred += 1;
+        let frame = self.socket.send_buffer[..len].to_vec();
+        self.stats.tx_dropped += self.tx_queue.push_bounded(priority, frame, TX_QUEUE_CAPACITY) as u64;
+        self.flush_tx();
+        Ok(())
+    }
+
+    /// Send queued frames, highest class first, until the socket is full
+    fn flush_tx(&mut self) {
+        while let Some((priority, frame)) = self.tx_queue.pop() {
+            self.apply_socket_priority(priority);
+            match self.socket.try_emit(&frame) {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.tx_queue.push_front(priority, frame);
+                    return;
+                }
+                Err(_) => self.stats.tx_dropped += 1,
+            }
+        }
+    }
+
+    /// Switch the socket's `SO_PRIORITY` to `priority`'s class if needed
+    /// 
+    /// A refused setting (missing privileges) is retried on the next frame;
+    /// the class still travels in the frame header.
+    fn apply_socket_priority(&mut self, priority: Priority) {
+        if self.socket_priority != priority && self.socket.set_priority(priority.socket_priority()).is_ok() {
+            self.socket_priority = priority;
+        }
+    }
+
+    /// Header flags announcing `priority` (none for `Normal`)
+    fn priority_flags(priority: Priority) -> u16 {
+        if priority == Priority::Normal { 0 } else { FLAG_PRIORITY }
+    }
+
+    /// Add the flags every frame from this node carries to `flags`
+    /// 
+    /// Membership adds origin and tag, `checksum` the CRC trailer, ghost mode
+    /// the epoch section (unless the frame must bind real IDs).
+    fn wire_flags(&self, mut flags: u16, real_ids: bool) -> u16 {
+        if self.membership.is_some() {
+            flags |= FLAG_ORIGIN | FLAG_MEMBERSHIP;
+        }
+        if self.socket.checksum {
+            flags |= FLAG_CHECKSUM;
+        }
+        if self.config.pseudonyms.is_some() && !real_ids {
+            flags |= FLAG_PSEUDONYMOUS;
+        }
+        flags
+    }
+
+    /// Largest payload that fits one frame with `flags` and `target_words` words of targets
+    fn max_payload(&self, flags: u16, target_words: usize) -> usize {
+        let flags = self.wire_flags(flags, false);
+        let overhead = frame::HEADER_SIZE + frame::section_size(flags) + target_words * 8 + frame::trailer_size(flags);
+        MAX_FRAME_SIZE.saturating_sub(overhead)
+    }
+
+    /// Size in 8-byte words `targets` take on the wire (lists, bitmaps or Bloom filters)
+    fn target_words(&self, targets: &[DroneId]) -> usize {
+        targets::choose(targets, self.config.pseudonyms.is_some()).1
+    }
+
+    /// Split a payload across as many frames as needed
+    /// 
+    /// Every fragment carries `meta`'s flags, class and hop budget.
+    fn emit_fragmented(&mut self, meta: FrameMeta, targets: &[DroneId], payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let flags = meta.flags | FLAG_ORIGIN | FLAG_FRAGMENT;
+        let chunk = self.max_payload(flags, self.target_words(targets));
+        if chunk == 0 {
+            return Err("Target list leaves no room for payload - reduce targets".into());
+        }
+        let count = payload.len().div_ceil(chunk);
+        if count > u16::MAX as usize {
+            return Err("Payload exceeds maximum fragmented message size".into());
+        }
+        
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        for (index, piece) in payload.chunks(chunk).enumerate() {
+            let fragment = FragmentHeader { message_id, index: index as u16, count: count as u16 };
+            self.emit_frame(FrameMeta { flags, fragment, ..meta }, targets, piece)?;
+        }
+        Ok(())
+    }
+
+    /// Emit a protocol control message `[kind][body]` stamped with our origin
+    fn emit_control(&mut self, flags: u16, targets: &[DroneId], kind: u8, body: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut payload = Vec::with_capacity(1 + body.len());
+        payload.push(kind);
+        payload.extend_from_slice(body);
+        self.emit_with_flags(flags | FLAG_CONTROL | FLAG_ORIGIN, targets, &payload)
+    }
+
+    /// Run a received frame through the acceptance pipeline
+    /// 
+    /// Returns where the payload lives, its provenance and priority, or None if the
+    /// frame was irrelevant, rejected, consumed as control or is one piece of
+    /// a message that is not complete yet.
+    fn accept_frame(&mut self, len: usize) -> Option<(Delivery, Authority, Priority)> {
+        let mut view = frame::parse_frame(&self.socket.recv_buffer[..len])?;
+        
+        // Corrupted frames are dropped before anything inside them is trusted
+        if !view.checksum_ok {
+            self.stats.checksum_failures += 1;
+            self.channel.observe_loss(Instant::now(), &self.config.congestion);
+            return None;
+        }
+        let priority = view.priority;
+        let direct = view.hop.is_none_or(|hop| hop.hops == 0);
+        
+        // Multi-hop frames: each copy is handled once, and relayed once
+        // whether or not it is addressed to us
+        if let Some(hop) = view.hop {
+            let flags = view.flags;
+            let frame_len = view.payload_offset + view.payload.len() + frame::trailer_size(flags);
+            let distance = view.position.zip(self.position).map(|(from, here)| from.distance(&here));
+            // Tags and signatures cover the relay sections zeroed
+            self.socket.recv_buffer[frame::relay_sections(flags)].fill(0);
+            view = frame::parse_frame(&self.socket.recv_buffer[..frame_len])?;
+            
+            let now = Instant::now();
+            let key = self.multihop_seen.content_key(view.membership_region);
+            if self.multihop_seen.check(key, now) {
+                self.relay_queue.heard_again(key, distance);
+                self.stats.duplicates_dropped += 1;
+                return None;
+            }
+            if let (Some(relay), Some(next)) = (self.config.relay, hop.next())
+                && frame_len <= MAX_FRAME_SIZE
+                && self.may_relay(&view) {
+                if let RelayPolicy::Gossip { probability } = relay.policy
+                    && !self.relay_jitter.chance(probability) {
+                    self.stats.relays_suppressed += 1;
+                } else {
+                    let copy = relay::forward(&self.socket.recv_buffer[..frame_len], flags, next, self.position);
+                    let due = now + self.relay_jitter.below(relay.max_jitter);
+                    self.stats.tx_dropped += self.relay_queue.schedule(key, copy, priority, due, distance) as u64;
+                }
+            }
+        }
+        
+        // Anyone heard directly is in range, whoever the frame is for
+        if self.config.neighbors.is_some()
+            && let Some(source) = view.source
+            && direct
+            && (view.epoch.is_some() || !self.revoked.contains(&source))
+            && !self.wire_id_matches(source, self.my_drone_id, view.epoch)
+            && self.meets_membership(&view) {
+            // Drones just met are told which bundles we carry
+            if self.neighbor_table.observe_frame(source, Instant::now(), self.frame_rssi) {
+                self.summary_due = true;
+            }
+        }
+        
+        // Ghost mode frames are addressed to our pseudonym for the frame's epoch
+        let (my_address, scheme) = match (view.epoch, &self.config.pseudonyms) {
+            (None, _) => (self.my_drone_id, None),
+            (Some(epoch), Some(scheme)) if scheme.is_fresh(epoch) => {
+                (self.pseudonym_cache.get(scheme, self.my_drone_id, epoch), Some((scheme, epoch)))
+            }
+            (Some(_), _) if view.targets.is_empty() => (self.my_drone_id, None),
+            (Some(_), _) => return None,
+        };
+        // Group frames list groups (pseudonyms for the epoch in ghost mode) instead of drones
+        let addressed = if view.has(FLAG_GROUP) {
+            view.targets.is_empty() || self.groups.iter().any(|&group| {
+                view.is_addressed_to(scheme.map_or(group, |(scheme, epoch)| scheme.pseudonym(group, epoch)))
+            })
+        } else {
+            view.is_addressed_to(my_address)
+        };
+        if !addressed {
+            return None;
+        }
+        
+        // Frames claiming a revoked ID are dropped before any crypto is spent
+        let revoked = view.source.is_some_and(|source| match scheme {
+            Some((scheme, epoch)) => self.revoked.iter().any(|&id| scheme.pseudonym(id, epoch) == source),
+            None => self.revoked.contains(&source),
+        });
+        if revoked {
+            self.stats.revoked_frames += 1;
+            return None;
+        }
+        
+        // Explicitly addressed frames asking for an ACK get one, either way
+        let ack_request = match (view.source, view.sequence) {
+            (Some(source), Some(sequence)) if view.has(FLAG_ACK_REQUEST) && !view.targets.is_empty() => {
+                Some(Ack { acked_source: source, sequence, accepted: true })
+            }
+            _ => None,
+        };
+        
+        let authority = if view.has(FLAG_SIGNED) {
+            match self.config.trust_store.verify(&view) {
+                Some((key_id, timestamp)) => {
+                    // A replay is a copy of a genuine frame: drop it, no ACK either way
+                    let max_age = self.config.trust_store.max_age();
+                    if !self.replay_guard.admit(key_id, timestamp, signing::now_micros(), max_age) {
+                        self.stats.stale_signatures += 1;
+                        return None;
+                    }
+                    Authority::Signed { by: key_id }
+                }
+                None => {
+                    self.stats.rejected_signatures += 1;
+                    self.send_ack(ack_request, false);
+                    return None;
+                }
+            }
+        } else {
+            Authority::Unsigned
+        };
+        
+        // Only the enrollment handshake is exempt from the membership
+        // requirement - ACKs, NACKs, HELLOs and routing frames need a tag too
+        if let Some(membership) = &self.membership {
+            let tagged = view.has(FLAG_MEMBERSHIP);
+            let required = self.config.require_membership && !view.is_enrollment();
+            if (tagged && !membership.verify(&view)) || (!tagged && required) {
+                self.stats.rejected_membership += 1;
+                self.send_ack(ack_request, false);
+                return None;
+            }
+        }
+        
+        if view.has(FLAG_CONTROL) {
+            let source = view.source?;
+            let epoch = view.epoch;
+            let (&kind, body) = view.payload.split_first()?;
+            let start = view.payload_offset + 1;
+            match kind {
+                frame::CONTROL_ROUTED => return self.accept_routed(start..start + body.len()),
+                frame::CONTROL_GEO => return self.accept_geo(source, start..start + body.len()),
+                frame::CONTROL_GEOCAST => return self.accept_geocast(start..start + body.len()),
+                frame::CONTROL_DTN_BUNDLE => return self.accept_bundle(start..start + body.len()),
+                _ => {}
+            }
+            let body = body.to_vec();
+            self.handle_control(source, epoch, kind, &body, authority);
+            return None;
+        }
+        
+        // Reliable broadcasts: note gaps for NACKing, drop repeats
+        if view.has(FLAG_RELIABLE_BROADCAST)
+            && let (Some(source), Some(sequence)) = (view.source, view.sequence)
+            && source != my_address
+            && !self.streams.observe(source, sequence, Instant::now(), &self.config.nack) {
+            return None;
+        }
+        
+        // Repeats of a delivered frame are dropped - but still ACKed, since
+        // a retransmission means the sender missed our first ACK
+        if let Some(cache) = self.dedup.as_mut() {
+            let key = match (view.source, view.sequence) {
+                (Some(source), Some(sequence)) => {
+                    // Unicast, reliable broadcast and each ordered type count separately
+                    let stream = match view.message_type {
+                        Some(message_type) => 1 << 16 | message_type as u32,
+                        None => (view.flags & FLAG_RELIABLE_BROADCAST) as u32,
+                    };
+                    cache.sequenced_key(source, sequence, stream)
+                }
+                _ => cache.content_key(view.membership_region),
+            };
+            if cache.check(key, Instant::now()) {
+                self.stats.duplicates_dropped += 1;
+                self.send_ack(ack_request, true);
+                return None;
+            }
+        }
+        
+        // Ordered streams: frames behind a gap wait, stale ones are dropped
+        if let (Some(message_type), Some(source), Some(sequence)) = (view.message_type, view.source, view.sequence)
+            && let Some(&policy) = self.config.ordering.get(&message_type) {
+            let now = Instant::now();
+            match self.ordering.accept(source, message_type, sequence, policy, view.payload, authority, priority, now) {
+                Ordered::Stale => {
+                    self.stats.stale_frames += 1;
+                    return None;
+                }
+                Ordered::Held => return None,
+                Ordered::Deliver => {
+                    let (released, skipped) = self.ordering.ready(source, message_type, now);
+                    self.stats.ordering_gaps_skipped += skipped as u64;
+                    // Queue behind anything already waiting so order holds
+                    if !released.is_empty() || !self.inbox.is_empty() {
+                        let payload = view.payload.to_vec();
+                        self.enqueue(payload, authority, priority);
+                        for (payload, authority, priority) in released {
+                            self.enqueue(payload, authority, priority);
+                        }
+                        return None;
+                    }
+                }
+            }
+        }
+        
+        // FEC shards are held until K of the group's frames arrived
+        if let (Some(shard), Some(source)) = (view.shard, view.source) {
+            return match self.fec_decoder.insert(source, shard, view.payload, Instant::now()) {
+                ShardOutcome::Complete { message, recovered } => {
+                    self.stats.fec_recovered += recovered as u64;
+                    Some((Delivery::Reassembled(message), authority, priority))
+                }
+                ShardOutcome::Pending => None,
+            };
+        }
+        
+        // Fragments are held until the whole message arrived
+        if let (Some(fragment), Some(source)) = (view.fragment, view.source) {
+            let (message, evicted) = self.reassembler.insert(
+                source, fragment, view.payload, Instant::now(), &self.config.reassembly,
+            );
+            self.stats.reassembly_failures += evicted as u64;
+            return message.map(|message| (Delivery::Reassembled(message), authority, priority));
+        }
+        
+        let start = view.payload_offset;
+        let range = start..start + view.payload.len();
+        self.send_ack(ack_request, true);
+        Some((Delivery::InFrame(range), authority, priority))
+    }
+
+    /// Check whether a multi-hop frame may be relayed on the swarm's behalf
+    /// 
+    /// Frames from revoked drones are not, nor - once enrolled with
+    /// `require_membership` - frames other than the enrollment handshake
+    /// without a valid membership tag.
+    fn may_relay(&self, view: &frame::FrameView<'_>) -> bool {
+        if view.epoch.is_none() && view.source.is_some_and(|source| self.revoked.contains(&source)) {
+            return false;
+        }
+        view.is_enrollment() || self.meets_membership(view)
+    }
+
+    /// Check a frame against the membership requirement, if we enforce one
+    fn meets_membership(&self, view: &frame::FrameView<'_>) -> bool {
+        match &self.membership {
+            Some(membership) if self.config.require_membership => {
+                view.has(FLAG_MEMBERSHIP) && membership.verify(view)
+            }
+            _ => true,
+        }
+    }
+
+    /// Broadcast a HELLO announcing our interval, hardware address, position
+    /// and how well we hear each neighbour
+    fn send_hello(&mut self, interval: Duration) {
+        let hello = Hello {
+            interval,
+            mac: self.mac,
+            position: self.position,
+            sequence: self.hello_sequence,
+            reports: self.neighbor_table.reception_reports(),
+        };
+        self.hello_sequence = self.hello_sequence.wrapping_add(1);
+        if self.emit_control(0, &[], frame::CONTROL_HELLO, &hello.to_bytes()).is_ok() {
+            self.stats.hellos_sent += 1;
+        }
+    }
+
+    /// Build the multi-hop inner frame of a routed, geographic, geocast or
+    /// delay-tolerant message
+    /// 
+    /// The frame is never flooded (TTL 0) and counts its first transmission
+    /// as a hop, so the receiver never mistakes the origin for a neighbour.
+    /// It must fit a control frame together with a `header_size` header.
+    fn build_inner_frame(&mut self, targets: &[DroneId], payload: &[u8], priority: Priority, header_size: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        let meta = FrameMeta {
+            flags: FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_MULTIHOP | Self::priority_flags(priority),
+            priority,
+            sequence,
+            hop: HopCount { ttl: 0, hops: 1 },
+            ..FrameMeta::default()
+        };
+        let len = self.build_frame(meta, targets, payload)?;
+        if 1 + header_size + len > self.max_payload(FLAG_CONTROL | FLAG_ORIGIN, 1) {
+            return Err("Payload too large for a single forwarded frame".into());
+        }
+        Ok(self.socket.send_buffer[..len].to_vec())
+    }
+
+    /// Hand a routed inner frame for `destination` to `next_hop`
+    fn send_routed(&mut self, next_hop: DroneId, destination: DroneId, inner: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut body = Vec::with_capacity(ROUTED_HEADER_SIZE + inner.len());
+        body.extend_from_slice(&destination.to_le_bytes());
+        body.extend_from_slice(inner);
+        self.emit_control(0, &[next_hop], frame::CONTROL_ROUTED, &body)
+    }
+
+    /// Broadcast a fresh request for a route to `destination`
+    fn send_route_request(&mut self, destination: DroneId, max_hops: u8) {
+        self.route_sequence = self.route_sequence.wrapping_add(1);
+        let request = RouteRequest {
+            request_id: self.next_request_id,
+            origin: self.my_drone_id,
+            origin_seq: self.route_sequence,
+            destination,
+            hops: 0,
+            ttl: max_hops.saturating_sub(1),
+        };
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        if self.emit_control(0, &[], frame::CONTROL_ROUTE_REQUEST, &request.to_bytes()).is_ok() {
+            self.stats.route_requests_sent += 1;
+        }
+    }
+
+    /// Broadcast that routes to `lost` no longer go through us
+    fn send_route_error(&mut self, lost: &[(DroneId, u32)]) {
+        for chunk in lost.chunks(routing::MAX_ERROR_ENTRIES) {
+            let _ = self.emit_control(0, &[], frame::CONTROL_ROUTE_ERROR, &routing::route_error_to_bytes(chunk));
+        }
+    }
+
+    /// Learn the reverse route from a route request, then answer or pass it on
+    fn handle_route_request(&mut self, transmitter: DroneId, body: &[u8]) {
+        let (Some(routing), Some(request)) = (self.config.routing, RouteRequest::parse(body)) else {
+            return;
+        };
+        let now = Instant::now();
+        if self.config.pseudonyms.is_some()
+            || request.origin == self.my_drone_id
+            || !self.routing_table.first_request(request.origin, request.request_id, now) {
+            return;
+        }
+        let hops = request.hops.saturating_add(1);
+        let expires = now + routing.route_timeout;
+        self.routing_table.update(request.origin, transmitter, hops, request.origin_seq, expires);
+        
+        if request.destination == self.my_drone_id {
+            self.route_sequence = self.route_sequence.wrapping_add(1);
+            let reply = RouteReply {
+                origin: request.origin,
+                destination: self.my_drone_id,
+                destination_seq: self.route_sequence,
+                hops: 0,
+            };
+            let _ = self.emit_control(0, &[transmitter], frame::CONTROL_ROUTE_REPLY, &reply.to_bytes());
+        } else if request.ttl > 0 {
+            let request = RouteRequest { hops, ttl: request.ttl - 1, ..request };
+            let _ = self.emit_control(0, &[], frame::CONTROL_ROUTE_REQUEST, &request.to_bytes());
+        }
+    }
+
+    /// Learn the forward route from a route reply, then use it or pass it on
+    fn handle_route_reply(&mut self, transmitter: DroneId, body: &[u8]) {
+        let (Some(routing), Some(reply)) = (self.config.routing, RouteReply::parse(body)) else {
+            return;
+        };
+        if self.config.pseudonyms.is_some() {
+            return;
+        }
+        let now = Instant::now();
+        let hops = reply.hops.saturating_add(1);
+        let expires = now + routing.route_timeout;
+        self.routing_table.update(reply.destination, transmitter, hops, reply.destination_seq, expires);
+        
+        if reply.origin == self.my_drone_id {
+            for inner in self.routing_table.take_queued(reply.destination) {
+                let Some(next_hop) = self.routing_table.next_hop(reply.destination, now, expires) else {
+                    break;
+                };
+                if self.rate_limiter.try_spend(Priority::Normal, inner.len(), now).is_err() {
+                    self.stats.throttled_dropped += 1;
+                } else {
+                    let _ = self.send_routed(next_hop, reply.destination, &inner);
+                }
+            }
+        } else if let Some(next_hop) = self.routing_table.next_hop(reply.origin, now, expires) {
+            let reply = RouteReply { hops, ..reply };
+            let _ = self.emit_control(0, &[next_hop], frame::CONTROL_ROUTE_REPLY, &reply.to_bytes());
+        }
+    }
+
+    /// Deliver or forward the routed frame whose body is at `body` in the receive buffer
+    /// 
+    /// The inner frame is accepted like any received frame once it reached
+    /// its destination; forwarders count a hop and pass it to their next hop
+    /// or, lacking a route, report the route broken.
+    fn accept_routed(&mut self, body: Range<usize>) -> Option<(Delivery, Authority, Priority)> {
+        let routing = self.config.routing?;
+        if self.config.pseudonyms.is_some() || body.len() < ROUTED_HEADER_SIZE {
+            return None;
+        }
+        let destination = DroneId::from_le_bytes(self.socket.recv_buffer[body.start..body.start + 8].try_into().unwrap());
+        let inner = body.start + ROUTED_HEADER_SIZE..body.end;
+        
+        // Only application data travels routed, in a frame never flooded
+        let view = frame::parse_frame(&self.socket.recv_buffer[inner.clone()])?;
+        let hop = view.hop?;
+        if view.has(FLAG_CONTROL) || hop.ttl != 0 {
+            return None;
+        }
+        let (flags, priority) = (view.flags, view.priority);
+        if destination == self.my_drone_id {
+            let len = inner.len();
+            self.socket.recv_buffer.copy_within(inner, 0);
+            return self.accept_frame(len);
+        }
+        
+        // Copies of a frame already forwarded are dropped (keyed like relays)
+        let now = Instant::now();
+        let sections = frame::relay_sections(flags);
+        self.socket.recv_buffer[inner.start + sections.start..inner.start + sections.end].fill(0);
+        let view = frame::parse_frame(&self.socket.recv_buffer[inner.clone()])?;
+        let key = self.multihop_seen.content_key(view.membership_region);
+        if self.multihop_seen.check(key, now) {
+            self.stats.duplicates_dropped += 1;
+            return None;
+        }
+        
+        let next_hop = self.routing_table.next_hop(destination, now, now + routing.route_timeout);
+        match next_hop {
+            Some(next_hop) if hop.hops < routing.max_hops => {
+                let hop = HopCount { ttl: 0, hops: hop.hops + 1 };
+                let copy = relay::forward(&self.socket.recv_buffer[inner], flags, hop, None);
+                if self.rate_limiter.try_spend(priority, copy.len(), now).is_err() {
+                    self.stats.throttled_dropped += 1;
+                } else if self.send_routed(next_hop, destination, &copy).is_ok() {
+                    self.stats.frames_routed += 1;
+                }
+            }
+            Some(_) => self.stats.routing_failures += 1,
+            None => {
+                self.stats.routing_failures += 1;
+                let lost = [(destination, self.routing_table.sequence(destination))];
+                self.send_route_error(&lost);
+            }
+        }
+        None
+    }
+
+    /// Where a geographic frame goes next, judged from our position and our
+    /// neighbours' (`previous`: the drone it came from and its position)
+    fn geo_step(&self, header: &GeoHeader, previous: Option<(DroneId, Position)>) -> GeoStep {
+        let Some(here) = self.position else {
+            return GeoStep::Drop;
+        };
+        let neighbors: Vec<(DroneId, Position)> = self.neighbor_table.snapshot(Instant::now()).into_iter()
+            .filter_map(|neighbor| neighbor.position.map(|position| (neighbor.id, position)))
+            .collect();
+        geo::next_step(self.my_drone_id, here, header, previous, &neighbors)
+    }
+
+    /// Hand a geographic inner frame to `next_hop`, stamped with our position
+    fn send_geo(&mut self, next_hop: DroneId, header: GeoHeader, inner: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut body = GeoHeader { sender: self.position, ..header }.to_bytes();
+        body.extend_from_slice(inner);
+        self.emit_control(0, &[next_hop], frame::CONTROL_GEO, &body)
+    }
+
+    /// Deliver or forward the geographic frame whose body is at `body` in the
+    /// receive buffer, sent to us by `transmitter`
+    fn accept_geo(&mut self, transmitter: DroneId, body: Range<usize>) -> Option<(Delivery, Authority, Priority)> {
+        let geo = self.config.geo?;
+        if self.config.pseudonyms.is_some() {
+            return None;
+        }
+        let (header, size) = GeoHeader::parse(&self.socket.recv_buffer[body.clone()])?;
+        let inner = body.start + size..body.end;
+        let view = frame::parse_frame(&self.socket.recv_buffer[inner.clone()])?;
+        let hop = view.hop?;
+        if view.has(FLAG_CONTROL) || hop.ttl != 0 {
+            return None;
+        }
+        let (flags, priority) = (view.flags, view.priority);
+        
+        // Face walks may pass a drone more than once, so copies are told
+        // apart by their hop count
+        let now = Instant::now();
+        let key = self.multihop_seen.content_key(&self.socket.recv_buffer[inner.clone()]);
+        if self.multihop_seen.check(key, now) {
+            self.stats.duplicates_dropped += 1;
+            return None;
+        }
+        
+        let previous = header.sender.map(|position| (transmitter, position));
+        match self.geo_step(&header, previous) {
+            GeoStep::Deliver => {
+                let len = inner.len();
+                self.socket.recv_buffer.copy_within(inner, 0);
+                return self.accept_frame(len);
+            }
+            GeoStep::Forward(next_hop, header) if hop.hops < geo.max_hops => {
+                let hop = HopCount { ttl: 0, hops: hop.hops + 1 };
+                let copy = relay::forward(&self.socket.recv_buffer[inner], flags, hop, None);
+                if self.rate_limiter.try_spend(priority, copy.len(), now).is_err() {
+                    self.stats.throttled_dropped += 1;
+                } else if self.send_geo(next_hop, header, &copy).is_ok() {
+                    self.stats.geo_forwarded += 1;
+                }
+            }
+            GeoStep::Forward(..) | GeoStep::Drop => self.stats.geo_dropped += 1,
+        }
+        None
+    }
+
+    /// Duplicate key of a geocast inner frame, and a copy with its relay
+    /// sections zeroed ready to be relayed
+    fn geocast_key(&self, inner: &[u8]) -> Option<(u64, Vec<u8>)> {
+        let view = frame::parse_frame(inner)?;
+        let mut zeroed = inner.to_vec();
+        zeroed[frame::relay_sections(view.flags)].fill(0);
+        let view = frame::parse_frame(&zeroed)?;
+        Some((self.geocast_seen.content_key(view.membership_region), zeroed))
+    }
+
+    /// Relay and deliver the geocast whose body is at `body` in the receive buffer
+    /// 
+    /// The frame is relayed (if enabled) when we are inside the region or
+    /// nearer to it than its sender, and delivered when we are inside.
+    fn accept_geocast(&mut self, body: Range<usize>) -> Option<(Delivery, Authority, Priority)> {
+        let (header, size) = GeocastHeader::parse(&self.socket.recv_buffer[body.clone()])?;
+        let inner = body.start + size..body.end;
+        let view = frame::parse_frame(&self.socket.recv_buffer[inner.clone()])?;
+        let hop = view.hop?;
+        if view.has(FLAG_CONTROL) || hop.ttl != 0 {
+            return None;
+        }
+        let (flags, priority) = (view.flags, view.priority);
+        
+        // Each geocast is handled once, however many drones relay it
+        let now = Instant::now();
+        let (key, zeroed) = self.geocast_key(&self.socket.recv_buffer[inner.clone()])?;
+        if self.geocast_seen.check(key, now) {
+            self.stats.duplicates_dropped += 1;
+            return None;
+        }
+        
+        let inside = self.position.is_some_and(|here| header.region.contains(&here));
+        if let (Some(geocast), Some(here)) = (self.config.geocast, self.position)
+            && header.ttl > 0
+            && hop.hops <= geocast.max_hops
+            && (inside || header.sender.is_none_or(|from| header.region.distance(&here) < header.region.distance(&from))) {
+            let copy = relay::forward(&zeroed, flags, HopCount { ttl: 0, hops: hop.hops.saturating_add(1) }, None);
+            let relayed = GeocastHeader { ttl: header.ttl - 1, sender: Some(here), ..header };
+            let mut body = relayed.to_bytes();
+            body.extend_from_slice(&copy);
+            if self.rate_limiter.try_spend(priority, copy.len(), now).is_err() {
+                self.stats.throttled_dropped += 1;
+            } else if self.emit_control(0, &[], frame::CONTROL_GEOCAST, &body).is_ok() {
+                self.stats.geocasts_relayed += 1;
+            }
+        }
+        if !inside {
+            return None;
+        }
+        let len = inner.len();
+        self.socket.recv_buffer.copy_within(inner, 0);
+        self.accept_frame(len)
+    }
+
+    /// Origin and sequence number identifying a bundle's inner frame
+    fn bundle_id(inner: &[u8]) -> Option<BundleId> {
+        let view = frame::parse_frame(inner)?;
+        Some((view.source?, view.sequence?))
+    }
+
+    /// Broadcast a bundle's inner frame with `remaining` lifetime
+    fn send_bundle(&mut self, inner: &[u8], remaining: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let lifetime_ms = remaining.as_millis().min(u32::MAX as u128) as u32;
+        let mut body = Vec::with_capacity(BUNDLE_HEADER_SIZE + inner.len());
+        body.extend_from_slice(&lifetime_ms.to_le_bytes());
+        body.extend_from_slice(inner);
+        self.emit_control(0, &[], frame::CONTROL_DTN_BUNDLE, &body)
+    }
+
+    /// Broadcast the stored bundles among `ids`, each charged to its budget
+    fn offer_bundles(&mut self, ids: &[BundleId]) {
+        let now = Instant::now();
+        for &id in ids {
+            let Some((inner, remaining, priority)) = self.bundle_store.take_offer(id, now) else {
+                continue;
+            };
+            if self.rate_limiter.try_spend(priority, inner.len(), now).is_err() {
+                self.stats.throttled_dropped += 1;
+            } else if self.send_bundle(&inner, remaining).is_ok() {
+                self.stats.bundles_forwarded += 1;
+            }
+        }
+    }
+
+    /// Broadcast the summary vector of the bundles we carry
+    fn send_summary(&mut self) {
+        for chunk in self.bundle_store.summary().chunks(dtn::MAX_ENTRIES) {
+            let _ = self.emit_control(0, &[], frame::CONTROL_DTN_SUMMARY, &dtn::ids_to_bytes(chunk));
+        }
+    }
+
+    /// Store, pass on and deliver the bundle whose body is at `body` in the receive buffer
+    fn accept_bundle(&mut self, body: Range<usize>) -> Option<(Delivery, Authority, Priority)> {
+        let header = self.socket.recv_buffer.get(body.start..body.start + BUNDLE_HEADER_SIZE)?;
+        let lifetime = Duration::from_millis(u32::from_le_bytes(header.try_into().ok()?) as u64);
+        let inner = body.start + BUNDLE_HEADER_SIZE..body.end;
+        let view = frame::parse_frame(&self.socket.recv_buffer[inner.clone()])?;
+        let hop = view.hop?;
+        if view.has(FLAG_CONTROL) || hop.ttl != 0 {
+            return None;
+        }
+        let id = (view.source?, view.sequence?);
+        let (flags, priority) = (view.flags, view.priority);
+        
+        // Every drone carrying a bundle may offer it again
+        if self.bundle_store.contains(id) {
+            self.stats.duplicates_dropped += 1;
+            return None;
+        }
+        if self.config.dtn.is_some() && !lifetime.is_zero() {
+            let hop = HopCount { ttl: 0, hops: hop.hops.saturating_add(1) };
+            let copy = relay::forward(&self.socket.recv_buffer[inner.clone()], flags, hop, None);
+            if let Some(evicted) = self.bundle_store.insert(id, copy, priority, lifetime, Instant::now()) {
+                self.stats.bundles_stored += 1;
+                self.stats.bundles_evicted += evicted as u64;
+                // Our neighbours may be out of the sender's range
+                self.offer_bundles(&[id]);
+            }
+        }
+        let len = inner.len();
+        self.socket.recv_buffer.copy_within(inner, 0);
+        self.accept_frame(len)
+    }
+
+    /// Expire routes and retry or abandon discoveries past their deadline
+    fn maintain_routes(&mut self, routing: RoutingConfig, now: Instant) {
+        self.routing_table.expire(now);
+        for step in self.routing_table.due_discoveries(now, routing.discovery_timeout) {
+            match step {
+                DiscoveryStep::Retry(destination) => self.send_route_request(destination, routing.max_hops),
+                DiscoveryStep::Failed(_, lost) => self.stats.routing_failures += lost as u64,
+            }
+        }
+    }
+
+    /// Send relays whose jitter delay passed
+    /// 
+    /// Relays are charged to the transmit budgets but never wait for them:
+    /// a relay over budget is dropped.
+    fn send_due_relays(&mut self) {
+        let Some(relay) = self.config.relay else {
+            return;
+        };
+        let now = Instant::now();
+        let (due, suppressed) = self.relay_queue.due(now, relay.policy);
+        self.stats.relays_suppressed += suppressed as u64;
+        for (relayed, priority) in due {
+            if self.rate_limiter.try_spend(priority, relayed.len(), now).is_err() {
+                self.stats.throttled_dropped += 1;
+                continue;
+            }
+            self.socket.send_buffer[..relayed.len()].copy_from_slice(&relayed);
+            if self.transmit(relayed.len(), priority).is_ok() {
+                self.stats.frames_relayed += 1;
+            }
+        }
+    }
+
+    /// Header metadata of a reliable broadcast (originals and repairs alike)
+    fn broadcast_meta(sequence: u32) -> FrameMeta {
+        FrameMeta { flags: FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_RELIABLE_BROADCAST, sequence, ..FrameMeta::default() }
+    }
+
+    /// Emit NACKs whose suppression timers expired
+    fn send_due_nacks(&mut self) {
+        let (nacks, abandoned) = self.streams.due_nacks(Instant::now(), &self.config.nack);
+        self.stats.unrecoverable_frames += abandoned as u64;
+        for nack in nacks {
+            if self.emit_control(0, &[], frame::CONTROL_NACK, &nack.to_bytes()).is_ok() {
+                self.stats.nacks_sent += 1;
+            }
+        }
+    }
+
+    /// Rebroadcast requested frames still in the repair buffer
+    fn send_repairs(&mut self, sequences: &[u32]) {
+        let now = Instant::now();
+        for &sequence in sequences {
+            let Some(payload) = self.repair_buffer.repair(sequence, now, self.config.nack.max_nack_delay) else {
+                continue;
+            };
+            if self.emit_frame(Self::broadcast_meta(sequence), &[], &payload).is_ok() {
+                self.stats.repairs_sent += 1;
+            }
+        }
+    }
+
+    /// Answer a frame that requested an acknowledgement
+    fn send_ack(&mut self, request: Option<Ack>, accepted: bool) {
+        if let Some(ack) = request {
+            let body = Ack { accepted, ..ack }.to_bytes();
+            let _ = self.emit_control(0, &[], frame::CONTROL_ACK, &body);
+        }
+    }
+
+    /// Send a reliable frame until every target answered or the retries run out
+    /// 
+    /// Each attempt is addressed only to the targets still pending in
+    /// `self.awaiting`; outcomes are left there for the caller.
+    fn retransmit_until_acked(&mut self, meta: FrameMeta, payload: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let policy = self.config.retransmit;
+        for attempt in 0..=policy.max_retries {
+            let Some(pending) = self.awaiting.as_ref().map(AckWaiter::pending) else {
+                return Ok(());
+            };
+            if attempt > 0 {
+                self.stats.retransmissions += 1;
+                self.channel.observe_loss(Instant::now(), &self.config.congestion);
+            }
+            self.emit_frame(meta, &pending, payload)?;
+            
+            let deadline = Instant::now() + policy.timeout(attempt);
+            loop {
+                if self.awaiting.as_ref().is_none_or(AckWaiter::is_complete) {
+                    return Ok(());
+                }
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                if self.socket.wait_readable(remaining) {
+                    self.receive_into_inbox();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive one frame into the receive buffer, counting it as channel load
+    /// 
+    /// Radiotap and 802.11 headers in front of the OVP frame (monitor-mode
+    /// interfaces) are stripped, keeping the signal strength they report.
+    fn read_frame(&mut self) -> Option<usize> {
+        let mut len = self.socket.receive_frame().ok()?.len();
+        self.channel.observe_frame(Instant::now(), &self.config.congestion);
+        self.frame_rssi = None;
+        if let Some((start, rssi)) = radiotap::locate_frame(&self.socket.recv_buffer[..len]) {
+            self.socket.recv_buffer.copy_within(start..len, 0);
+            len -= start;
+            self.frame_rssi = rssi;
+        }
+        Some(len)
+    }
+
+    /// Receive one pending frame, queueing any application message it carries
+    fn receive_into_inbox(&mut self) {
+        let Some(len) = self.read_frame() else {
+            return;
+        };
+        if let Some((delivery, authority, priority)) = self.accept_frame(len) {
+            self.enqueue_delivery(delivery, authority, priority);
+        }
+    }
+
+    /// Queue an accepted frame's message, copying it out of the receive buffer
+    fn enqueue_delivery(&mut self, delivery: Delivery, authority: Authority, priority: Priority) {
+        let payload = match delivery {
+            Delivery::InFrame(range) => self.socket.recv_buffer[range].to_vec(),
+            Delivery::Reassembled(message) => message,
+        };
+        self.enqueue(payload, authority, priority);
+    }
+
+    /// Queue a message for `receive_message`, dropping low priorities first when full
+    fn enqueue(&mut self, payload: Vec<u8>, authority: Authority, priority: Priority) {
+        self.inbox.push_bounded(priority, (payload, authority), INBOX_CAPACITY);
+    }
+
+    /// Check whether an identifier seen on the wire denotes drone `id`
+    /// 
+    /// In ghost mode the identifier is a pseudonym from around `epoch`;
+    /// neighbouring epochs are accepted because an ACK may be sent just after
+    /// the epoch of the frame it acknowledges.
+    fn wire_id_matches(&self, wire_id: DroneId, id: DroneId, epoch: Option<u32>) -> bool {
+        match (epoch, &self.config.pseudonyms) {
+            (Some(epoch), Some(scheme)) => (epoch.saturating_sub(1)..=epoch.saturating_add(1))
+                .any(|e| scheme.pseudonym(id, e) == wire_id),
+            _ => wire_id == id,
+        }
+    }
+
+    /// Act on a protocol control message
+    fn handle_control(&mut self, source: DroneId, epoch: Option<u32>, kind: u8, body: &[u8], authority: Authority) {
+        match kind {
+            frame::CONTROL_JOIN_REQUEST => {
+                let mut nonce = [0u8; NONCE_SIZE];
+                let accept = self.config.enrollment_authority.as_ref()
+                    .filter(|_| random::fill_random(&mut nonce).is_ok())
+                    .and_then(|authority| authority.accept(source, body, &nonce));
+                if let Some(accept) = accept {
+                    let _ = self.emit_control(0, &[source], frame::CONTROL_JOIN_ACCEPT, &accept);
+                }
+            }
+            frame::CONTROL_JOIN_ACCEPT => {
+                let (Some(nonce), Some(credential)) = (&self.pending_join, &self.config.credential) else {
+                    return;
+                };
+                if let Some((swarm_key, generation)) = enrollment::open_accept(credential, self.my_drone_id, nonce, body) {
+                    self.membership = Some(Membership::new(swarm_key, self.my_drone_id, generation));
+                    self.pending_join = None;
+                }
+            }
+            frame::CONTROL_REVOKE => {
+                // Only commanders in our trust store may revoke
+                if let (Authority::Signed { .. }, Ok(id)) = (authority, <[u8; 8]>::try_from(body)) {
+                    self.apply_revocation(DroneId::from_le_bytes(id));
+                }
+            }
+            frame::CONTROL_REKEY => {
+                // The entry for us is wrapped under our own credential
+                let (Some(membership), Some(credential)) = (&mut self.membership, &self.config.credential) else {
+                    return;
+                };
+                if let Some((swarm_key, generation)) = enrollment::open_rekey(credential, self.my_drone_id, membership.generation(), body) {
+                    membership.rekey(swarm_key, generation, Instant::now());
+                    self.stats.rekeys += 1;
+                }
+            }
+            frame::CONTROL_ACK => {
+                let (Some(ack), Some(mut waiter)) = (Ack::parse(body), self.awaiting.take()) else {
+                    return;
+                };
+                // The ACK must name us as its sender and come from a pending target
+                if ack.sequence == waiter.sequence && self.wire_id_matches(ack.acked_source, self.my_drone_id, epoch) {
+                    for (target, outcome) in waiter.targets.iter_mut() {
+                        if outcome.is_none() && self.wire_id_matches(source, *target, epoch) {
+                            *outcome = Some(ack.status());
+                        }
+                    }
+                }
+                self.awaiting = Some(waiter);
+            }
+            frame::CONTROL_ROUTE_REQUEST => self.handle_route_request(source, body),
+            frame::CONTROL_ROUTE_REPLY => self.handle_route_reply(source, body),
+            frame::CONTROL_ROUTE_ERROR => {
+                if self.config.routing.is_some()
+                    && let Some(unreachable) = routing::parse_route_error(body) {
+                    let lost = self.routing_table.apply_error(source, &unreachable);
+                    self.send_route_error(&lost);
+                }
+            }
+            frame::CONTROL_DTN_SUMMARY => {
+                // Ask the drone for the bundles we lack
+                if self.config.dtn.is_some()
+                    && let Some(ids) = dtn::parse_ids(body) {
+                    let missing: Vec<BundleId> = ids.into_iter().filter(|&id| !self.bundle_store.contains(id)).collect();
+                    if !missing.is_empty() {
+                        let _ = self.emit_control(0, &[source], frame::CONTROL_DTN_REQUEST, &dtn::ids_to_bytes(&missing));
+                    }
+                }
+            }
+            frame::CONTROL_DTN_REQUEST => {
+                if let Some(ids) = dtn::parse_ids(body) {
+                    self.offer_bundles(&ids);
+                }
+            }
+            frame::CONTROL_HELLO => {
+                if let Some(hello) = Hello::parse(body) {
+                    let outbound = hello.report_for(|id| self.wire_id_matches(id, self.my_drone_id, epoch));
+                    self.neighbor_table.observe_hello(source, &hello, outbound, Instant::now());
+                }
+            }
+            frame::CONTROL_NACK => {
+                let Some(nack) = Nack::parse(body) else {
+                    return;
+                };
+                if self.wire_id_matches(source, self.my_drone_id, epoch) {
+                    // Our own NACK echoed back
+                    return;
+                }
+                self.channel.observe_loss(Instant::now(), &self.config.congestion);
+                if self.wire_id_matches(nack.source, self.my_drone_id, epoch) {
+                    self.send_repairs(&nack.sequences);
+                } else {
+                    // Someone else asked first - hold back our own request
+                    self.streams.suppress(nack.source, &nack.sequences, Instant::now(), &self.config.nack);
+                }
+            }
+            frame::CONTROL_SESSION => {
+                if let Ok(newest) = body.try_into().map(u32::from_le_bytes) {
+                    self.streams.heartbeat(source, newest, Instant::now(), &self.config.nack);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a revoked drone ID locally
+    /// 
+    /// The enrollment authority also deprovisions the ID and rotates the swarm
+    /// key, whichever commander issued the revocation.
+    fn apply_revocation(&mut self, drone_id: DroneId) {
+        if !self.revoked.insert(drone_id) {
+            return;
+        }
+        if drone_id == self.my_drone_id {
+            self.membership = None;
+        }
+        
+        let Some(authority) = self.config.enrollment_authority.as_mut() else {
+            return;
+        };
+        authority.deprovision(drone_id);
+        let mut swarm_key = [0u8; SWARM_KEY_SIZE];
+        if random::fill_random(&mut swarm_key).is_err() {
+            return;
+        }
+        let generation = authority.generation() + 1;
+        // Sent under the old key, which members still hold
+        for body in authority.rekey(swarm_key) {
+            let _ = self.emit_control(0, &[], frame::CONTROL_REKEY, &body);
+        }
+        if let Some(membership) = self.membership.as_mut() {
+            membership.rekey(swarm_key, generation, Instant::now());
+        }
+    }
+}
+impl Clone for OVP {
+    fn clone(&self) -> Self {
+        // Create a new OVP instance with the same socket and drone ID
+        // Note: This creates a new receiver channel since we can't clone the existing one
+        let (_tx, rx) = std::sync::mpsc::channel();
+        
+        // Create a new OVP instance with the cloned socket and new receiver
+        OVP {
+            socket: self.socket.clone(),
+            my_drone_id: self.my_drone_id,
+            receiver_handle: None, // New instance won't have a receiver handle
             message_rx: rx,       // New receiver channel
+            config: self.config.clone(),
+            stats: self.stats.clone(),
+            membership: self.membership.clone(),
+            pending_join: None,
+            revoked: self.revoked.clone(),
+            replay_guard: self.replay_guard.clone(),
+            last_signature: self.last_signature,
+            pseudonym_cache: self.pseudonym_cache.clone(),
+            next_sequence: self.next_sequence,
+            awaiting: None,
+            inbox: PriorityQueue::default(),
+            delivered: Vec::new(),
+            next_broadcast_sequence: self.next_broadcast_sequence,
+            repair_buffer: self.repair_buffer.clone(),
+            next_heartbeat: self.next_heartbeat,
+            heartbeats_left: self.heartbeats_left,
+            streams: self.streams.clone(),
+            next_message_id: self.next_message_id,
+            fec_decoder: self.fec_decoder.clone(),
+            reassembler: self.reassembler.clone(),
+            dedup: self.dedup.clone(),
+            ordered_sequences: self.ordered_sequences.clone(),
+            ordering: self.ordering.clone(),
+            tx_queue: PriorityQueue::default(),
+            socket_priority: self.socket_priority,
+            rate_limiter: self.rate_limiter.clone(),
+            channel: self.channel,
+            next_beacon: self.next_beacon,
+            beacon_jitter: Jitter::new(),
+            multihop_seen: self.multihop_seen.clone(),
+            relay_queue: RelayQueue::default(),
+            relay_jitter: Jitter::new(),
+            position: self.position,
+            neighbor_table: self.neighbor_table.clone(),
+            next_hello: self.next_hello,
+            mac: self.mac,
+            hello_sequence: self.hello_sequence,
+            frame_rssi: None,
+            routing_table: self.routing_table.clone(),
+            route_sequence: self.route_sequence,
+            next_request_id: self.next_request_id,
+            geocast_seen: self.geocast_seen.clone(),
+            bundle_store: self.bundle_store.clone(),
+            summary_due: false,
+            groups: self.groups.clone(),
         }
     }
 }
@@ -636,12 +3458,22 @@ impl Clone for OVP {
 /// Option containing payload slice if frame is valid and intended for this drone,
 /// None if frame is invalid, malformed, or not intended for this drone.
 /// 
+/// A CRC-32C trailer, if present, is verified. Other trailers (e.g.
+/// signatures) are NOT verified here - use
+/// `OVP::receive_message` when provenance matters. For pseudonymous frames
+/// pass this drone's pseudonym for the frame's epoch as `my_id`. Frames
+/// addressed to multicast groups never match - see
+/// `parse_ovp_frame_fast_with_groups`. Protocol frames - control messages,
+/// FEC parity shards and fragments - never match either: they carry no
+/// application payload of their own.
+/// 
 /// # Performance Optimizations
 /// 
 /// - Early exit on insufficient frame length
 /// - Unsafe unaligned reads for maximum speed on modern CPUs
 /// - Fast-path broadcast detection (target_count == 0)
-/// - Efficient target ID scanning with pointer arithmetic
+/// - SIMD target ID scanning (AVX2/SSE2/NEON, detected at runtime)
+/// - Constant-cost matching of bitmap and Bloom filter target sets
 /// - Zero memory allocations - returns slice into original buffer
 /// 
 /// # Safety
@@ -650,6 +3482,27 @@ impl Clone for OVP {
 /// is performed before unsafe operations to ensure memory safety.
 #[inline(always)]
 pub fn parse_ovp_frame_fast(frame: &[u8], my_id: DroneId) -> Option<&[u8]> {
+    parse_ovp_frame_fast_with_groups(frame, my_id, &[])
+}
+
+/// Ultra-fast OVP frame parsing that also matches multicast group frames
+/// 
+/// Like `parse_ovp_frame_fast`, but frames addressed to groups are accepted
+/// if they name one of `groups` (the groups this drone joined). For
+/// pseudonymous frames pass the groups' pseudonyms for the frame's epoch.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// # use ovp::{group_id, parse_ovp_frame_fast_with_groups};
+/// let groups = [group_id("scouts"), group_id("team-alpha")];
+/// # let frame = [0u8; 12];
+/// if let Some(payload) = parse_ovp_frame_fast_with_groups(&frame, 42, &groups) {
+///     println!("{} bytes for us or our teams", payload.len());
+/// }
+/// ```
+#[inline(always)]
+pub fn parse_ovp_frame_fast_with_groups<'a>(frame: &'a [u8], my_id: DroneId, groups: &[GroupId]) -> Option<&'a [u8]> {
     // Quick length check - minimum OVP frame is 12 bytes (header only)
     if frame.len() < 12 { 
         return None; 
@@ -663,11 +3516,15 @@ pub fn parse_ovp_frame_fast(frame: &[u8], my_id: DroneId) -> Option<&[u8]> {
         }
         
         // Extract frame structure information
-        let target_count = u32::from_le(ptr::read_unaligned(frame.as_ptr().add(4) as *const u32)) as usize;
+        // High 16 bits of the count word are feature flags selecting optional
+        // sections (before the targets) and trailers (after the payload)
+        let count_word = u32::from_le(ptr::read_unaligned(frame.as_ptr().add(4) as *const u32));
+        let (target_field, flags) = frame::split_count_word(count_word);
+        let (encoding, target_count) = targets::split_field(target_field)?;
         let payload_len = u32::from_le(ptr::read_unaligned(frame.as_ptr().add(8) as *const u32)) as usize;
         
         // Calculate frame section boundaries
-        let targets_start = 12;
+        let targets_start = 12 + frame::section_size(flags);
         let targets_end = targets_start + (target_count * 8);
         let payload_start = targets_end;
         let payload_end = payload_start + payload_len;
@@ -677,24 +3534,59 @@ pub fn parse_ovp_frame_fast(frame: &[u8], my_id: DroneId) -> Option<&[u8]> {
             return None; 
         }
         
+        // Integrity check: CRC-32C trailer covers everything before it
+        if flags & frame::FLAG_CHECKSUM != 0 {
+            let covered = payload_end + frame::trailer_size(flags) - checksum::CHECKSUM_SIZE;
+            if frame.len() < covered + checksum::CHECKSUM_SIZE
+                || !checksum::verify_checksum(&frame[..covered], &frame[covered..covered + checksum::CHECKSUM_SIZE]) {
+                return None;
+            }
+        }
+        
+        // PROTOCOL TRAFFIC: control bodies, parity shards and fragments are
+        // not application payloads - only `OVP` interprets them
+        if flags & (frame::FLAG_CONTROL | frame::FLAG_FEC | frame::FLAG_FRAGMENT) != 0 {
+            return None;
+        }
+        
         // FAST PATH: Broadcast message (no specific targets)
         // If target_count is 0, message is for everyone in range
         if target_count == 0 {
             return Some(&frame[payload_start..payload_end]);
         }
         
-        // TARGETED MESSAGE: Check if this drone is in target list
-        // Use unaligned pointer reads for maximum scanning speed
-        let targets_ptr = frame.as_ptr().add(targets_start) as *const u64;
-        for i in 0..target_count {
-            let target_id = u64::from_le(ptr::read_unaligned(targets_ptr.add(i)));
-            if target_id == my_id {
-                return Some(&frame[payload_start..payload_end]);
-            }
+        // COMPACT TARGETS: bitmap or Bloom filter, a few bit tests whatever the set size
+        let targets = &frame[targets_start..targets_end];
+        if encoding != TargetEncoding::List {
+            let member = if flags & frame::FLAG_GROUP != 0 {
+                groups.iter().fold(false, |found, &group| found | targets::matches(encoding, targets, group))
+            } else {
+                targets::matches(encoding, targets, my_id)
+            };
+            return member.then(|| &frame[payload_start..payload_end]);
         }
         
-        // Message not intended for this drone
-        None
+        // GROUP MESSAGE: the list names groups, matched against ours
+        if flags & frame::FLAG_GROUP != 0 {
+            let member = if flags & frame::FLAG_PSEUDONYMOUS != 0 {
+                groups.iter().fold(false, |found, &group| found | frame::contains_target_ct(targets, group))
+            } else {
+                groups.iter().any(|&group| scan::contains_id(targets, group))
+            };
+            return member.then(|| &frame[payload_start..payload_end]);
+        }
+        
+        // PSEUDONYMOUS MESSAGE: `my_id` is our current pseudonym - scan the
+        // whole list in constant time so timing never reveals a match position
+        if flags & frame::FLAG_PSEUDONYMOUS != 0 {
+            return frame::contains_target_ct(targets, my_id)
+                .then(|| &frame[payload_start..payload_end]);
+        }
+        
+        // TARGETED MESSAGE: Check if this drone is in target list
+        // Vectorized scan, several IDs per compare (None = not for this drone)
+        scan::contains_id(targets, my_id)
+            .then(|| &frame[payload_start..payload_end])
     }
 }
 
@@ -754,4 +3646,899 @@ mod tests {
         let result = parse_ovp_frame_fast(&frame, 456);
         assert_eq!(result, None);
     }
+
+    /// Test swarm enrollment end to end
+    /// 
+    /// An authority and a joining drone share the loopback interface; the
+    /// drone proves its credential, receives the swarm key and its frames are
+    /// accepted by a node requiring membership.
+    #[test]
+    fn test_enrollment_over_loopback() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        
+        let credential = DeviceCredential::new([0x5A; CREDENTIAL_SIZE]);
+        let mut authority = EnrollmentAuthority::new([0xA5; SWARM_KEY_SIZE]);
+        authority.provision(910_002, credential.clone());
+        
+        let done = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = channel();
+        let authority_done = done.clone();
+        let handle = thread::spawn(move || {
+            let config = OvpConfig {
+                enrollment_authority: Some(authority),
+                require_membership: true,
+                ..OvpConfig::default()
+            };
+            let mut ovp = OVP::with_config("lo", 910_001, config).unwrap();
+            ready_tx.send(()).unwrap();
+            
+            let mut heard = Vec::new();
+            while !authority_done.load(Ordering::SeqCst) {
+                if !ovp.socket.wait_readable(Duration::from_millis(20)) {
+                    continue;
+                }
+                if let Some(payload) = ovp.try_receive() {
+                    heard.push(payload.to_vec());
+                }
+            }
+            heard
+        });
+        ready_rx.recv().unwrap();
+        
+        let config = OvpConfig { credential: Some(credential), ..OvpConfig::default() };
+        let mut drone = OVP::with_config("lo", 910_002, config).unwrap();
+        drone.enroll(Duration::from_secs(2)).unwrap();
+        assert!(drone.is_enrolled());
+        drone.emit(Some(&[910_001]), b"member hello").unwrap();
+        
+        thread::sleep(Duration::from_millis(100));
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert!(heard.iter().any(|payload| payload == b"member hello"));
+    }
+
+    /// Test that revocation rotates the swarm key
+    /// 
+    /// The revoked drone still holds the old swarm key, from which it could
+    /// derive any member's tag key. After the REKEY only the remaining member
+    /// holds the new key, and frames tagged under the old one are refused once
+    /// the grace period has passed.
+    #[test]
+    fn test_revocation_rekeys_over_loopback() {
+        let swarm_key = [0x71; SWARM_KEY_SIZE];
+        let member_credential = DeviceCredential::new([0x17; CREDENTIAL_SIZE]);
+        let revoked_credential = DeviceCredential::new([0x18; CREDENTIAL_SIZE]);
+        let mut enrollment_authority = EnrollmentAuthority::new(swarm_key);
+        enrollment_authority.provision(923_002, member_credential.clone());
+        enrollment_authority.provision(923_003, revoked_credential.clone());
+        
+        let config = OvpConfig {
+            enrollment_authority: Some(enrollment_authority),
+            require_membership: true,
+            signing_key: Some(SigningKey::from_seed([0x23; 32])),
+            ..OvpConfig::default()
+        };
+        let mut authority = OVP::with_config("lo", 923_001, config).unwrap();
+        let enrolled = |id, credential| {
+            let mut ovp = OVP::with_config("lo", id, OvpConfig { credential: Some(credential), ..OvpConfig::default() }).unwrap();
+            ovp.membership = Some(Membership::new(swarm_key, id, 0));
+            ovp
+        };
+        let mut member = enrolled(923_002, member_credential);
+        let mut revoked = enrolled(923_003, revoked_credential);
+        
+        authority.revoke(923_003).unwrap();
+        run_swarm(&mut [&mut member, &mut revoked], Duration::from_millis(100), b"");
+        assert_eq!(member.membership.as_ref().unwrap().generation(), 1);
+        assert_eq!(member.stats().rekeys, 1);
+        assert_eq!(revoked.membership.as_ref().unwrap().generation(), 0);
+        
+        // The revoked device impersonates the member with a tag under the old key
+        thread::sleep(enrollment::REKEY_GRACE);
+        let mut forger = OVP::new("lo", 923_002).unwrap();
+        forger.membership = Some(Membership::new(swarm_key, 923_002, 0));
+        forger.emit(Some(&[923_001]), b"forged").unwrap();
+        member.emit(Some(&[923_001]), b"genuine").unwrap();
+        
+        let heard = run_swarm(&mut [&mut authority], Duration::from_millis(100), b"").remove(0);
+        assert!(heard.iter().any(|payload| payload == b"genuine"));
+        assert!(!heard.iter().any(|payload| payload == b"forged"));
+        assert!(authority.stats().rejected_membership > 0);
+    }
+
+    /// Test that an authority rotates the key on another commander's revocation
+    /// 
+    /// Commander A revokes a drone; authority B, which trusts A, rotates the
+    /// swarm key so the revoked drone's tags under the old key are refused.
+    #[test]
+    fn test_relayed_revocation_rekeys_over_loopback() {
+        let swarm_key = [0x72; SWARM_KEY_SIZE];
+        let member_credential = DeviceCredential::new([0x19; CREDENTIAL_SIZE]);
+        let mut enrollment_authority = EnrollmentAuthority::new(swarm_key);
+        enrollment_authority.provision(924_003, member_credential.clone());
+        enrollment_authority.provision(924_004, DeviceCredential::new([0x1A; CREDENTIAL_SIZE]));
+        
+        let commander_key = SigningKey::from_seed([0x24; 32]);
+        let mut trust_store = TrustStore::new();
+        trust_store.add(commander_key.public_key());
+        let mut commander = OVP::with_config("lo", 924_001, OvpConfig { signing_key: Some(commander_key), ..OvpConfig::default() }).unwrap();
+        let config = OvpConfig {
+            enrollment_authority: Some(enrollment_authority),
+            require_membership: true,
+            trust_store,
+            ..OvpConfig::default()
+        };
+        let mut authority = OVP::with_config("lo", 924_002, config).unwrap();
+        let mut member = OVP::with_config("lo", 924_003, OvpConfig { credential: Some(member_credential), ..OvpConfig::default() }).unwrap();
+        member.membership = Some(Membership::new(swarm_key, 924_003, 0));
+        
+        commander.revoke(924_004).unwrap();
+        run_swarm(&mut [&mut authority, &mut member], Duration::from_millis(100), b"");
+        assert_eq!(member.membership.as_ref().unwrap().generation(), 1);
+        
+        thread::sleep(enrollment::REKEY_GRACE);
+        let mut revoked = OVP::new("lo", 924_004).unwrap();
+        revoked.membership = Some(Membership::new(swarm_key, 924_004, 0));
+        revoked.emit(Some(&[924_002]), b"own id").unwrap();
+        let mut forger = OVP::new("lo", 924_003).unwrap();
+        forger.membership = Some(Membership::new(swarm_key, 924_003, 0));
+        forger.emit(Some(&[924_002]), b"forged").unwrap();
+        member.emit(Some(&[924_002]), b"genuine").unwrap();
+        
+        let heard = run_swarm(&mut [&mut authority], Duration::from_millis(100), b"").remove(0);
+        assert!(heard.iter().any(|payload| payload == b"genuine"));
+        assert!(!heard.iter().any(|payload| payload == b"forged" || payload == b"own id"));
+        assert!(authority.stats().rejected_membership > 0);
+    }
+
+    /// Poll loopback OVPs for `time`, running their protocol timers
+    /// 
+    /// Returns, per OVP, every delivered payload starting with `prefix`.
+    fn run_swarm<O: std::borrow::BorrowMut<OVP>>(ovps: &mut [O], time: Duration, prefix: &[u8]) -> Vec<Vec<Vec<u8>>> {
+        let mut heard = vec![Vec::new(); ovps.len()];
+        let deadline = Instant::now() + time;
+        while Instant::now() < deadline {
+            for (ovp, heard) in ovps.iter_mut().zip(heard.iter_mut()) {
+                let ovp = ovp.borrow_mut();
+                ovp.service();
+                while ovp.has_pending() || ovp.socket.wait_readable(Duration::ZERO) {
+                    if let Some(payload) = ovp.try_receive().filter(|p| p.starts_with(prefix)) {
+                        heard.push(payload.to_vec());
+                    }
+                }
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        heard
+    }
+
+    /// Run a receiving OVP on loopback until the returned flag is set
+    /// 
+    /// Returns the flag and a handle yielding every payload delivered.
+    fn spawn_listener(
+        id: DroneId,
+        config: OvpConfig,
+    ) -> (std::sync::Arc<std::sync::atomic::AtomicBool>, thread::JoinHandle<Vec<Vec<u8>>>) {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        
+        let done = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = channel();
+        let listener_done = done.clone();
+        let handle = thread::spawn(move || {
+            let mut ovp = OVP::with_config("lo", id, config).unwrap();
+            ready_tx.send(()).unwrap();
+            
+            let mut heard = Vec::new();
+            while !listener_done.load(Ordering::SeqCst) {
+                if !ovp.has_pending() && !ovp.socket.wait_readable(Duration::from_millis(5)) {
+                    ovp.service();
+                    continue;
+                }
+                if let Some(payload) = ovp.try_receive() {
+                    heard.push(payload.to_vec());
+                }
+            }
+            heard
+        });
+        ready_rx.recv().unwrap();
+        (done, handle)
+    }
+
+    /// Test acknowledged unicast
+    /// 
+    /// A listening drone ACKs automatically; an absent drone exhausts the
+    /// retry budget; a drone refusing unauthenticated frames says so.
+    #[test]
+    fn test_reliable_unicast_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let (done, handle) = spawn_listener(920_002, OvpConfig::default());
+        let config = OvpConfig {
+            retransmit: RetransmitPolicy {
+                initial_timeout: Duration::from_millis(50),
+                max_timeout: Duration::from_millis(200),
+                max_retries: 2,
+            },
+            ..OvpConfig::default()
+        };
+        let mut sender = OVP::with_config("lo", 920_001, config).unwrap();
+        
+        assert_eq!(sender.emit_reliable(920_002, b"direct").unwrap(), DeliveryStatus::Delivered);
+        
+        let retransmissions = sender.stats().retransmissions;
+        assert_eq!(sender.emit_reliable(920_099, b"nobody").unwrap(), DeliveryStatus::TimedOut);
+        assert_eq!(sender.stats().retransmissions - retransmissions, 2);
+        
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert!(heard.iter().any(|payload| payload == b"direct"));
+        
+        let config = OvpConfig {
+            enrollment_authority: Some(EnrollmentAuthority::new([0x3C; SWARM_KEY_SIZE])),
+            require_membership: true,
+            ..OvpConfig::default()
+        };
+        let (done, handle) = spawn_listener(920_003, config);
+        assert_eq!(sender.emit_reliable(920_003, b"unenrolled").unwrap(), DeliveryStatus::Rejected);
+        done.store(true, Ordering::SeqCst);
+        assert!(handle.join().unwrap().is_empty());
+    }
+
+    /// Test that ACKs are held to the membership requirement
+    /// 
+    /// An outsider's untagged ACK must not count as delivery; a member's does.
+    #[test]
+    fn test_untagged_ack_rejected_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let swarm_key = [0x6E; SWARM_KEY_SIZE];
+        let member = || OvpConfig {
+            enrollment_authority: Some(EnrollmentAuthority::new(swarm_key)),
+            require_membership: true,
+            retransmit: RetransmitPolicy {
+                initial_timeout: Duration::from_millis(50),
+                max_timeout: Duration::from_millis(100),
+                max_retries: 1,
+            },
+            ..OvpConfig::default()
+        };
+        let mut sender = OVP::with_config("lo", 921_001, member()).unwrap();
+        
+        let (done, handle) = spawn_listener(921_002, OvpConfig::default());
+        assert_eq!(sender.emit_reliable(921_002, b"outsider").unwrap(), DeliveryStatus::TimedOut);
+        done.store(true, Ordering::SeqCst);
+        assert!(handle.join().unwrap().iter().any(|payload| payload == b"outsider"));
+        
+        let (done, handle) = spawn_listener(921_003, member());
+        assert_eq!(sender.emit_reliable(921_003, b"member").unwrap(), DeliveryStatus::Delivered);
+        done.store(true, Ordering::SeqCst);
+        assert!(handle.join().unwrap().iter().any(|payload| payload == b"member"));
+    }
+
+    /// Test that a recorded signed command cannot be played back
+    /// 
+    /// The same signed frame is transmitted twice; the drone delivers it once
+    /// (loopback echoes count as replays too).
+    #[test]
+    fn test_signed_replay_rejected_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let commander_key = SigningKey::from_seed([0x2B; 32]);
+        let mut trust_store = TrustStore::new();
+        trust_store.add(commander_key.public_key());
+        let (done, handle) = spawn_listener(922_002, OvpConfig { trust_store, ..OvpConfig::default() });
+        
+        let config = OvpConfig { signing_key: Some(commander_key), ..OvpConfig::default() };
+        let mut commander = OVP::with_config("lo", 922_001, config).unwrap();
+        let meta = FrameMeta { flags: FLAG_SIGNED, ..FrameMeta::default() };
+        let len = commander.build_frame(meta, &[922_002], b"EMERGENCY_STOP").unwrap();
+        commander.transmit(len, Priority::Normal).unwrap();
+        thread::sleep(Duration::from_millis(30));
+        commander.transmit(len, Priority::Normal).unwrap();
+        thread::sleep(Duration::from_millis(30));
+        
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert_eq!(heard, vec![b"EMERGENCY_STOP".to_vec()]);
+    }
+
+    /// Test reliable group multicast
+    /// 
+    /// Two present team members acknowledge, the absent one is retried alone
+    /// and reported as timed out.
+    #[test]
+    fn test_reliable_multicast_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let listeners = [930_002, 930_003].map(|id| spawn_listener(id, OvpConfig::default()));
+        let config = OvpConfig {
+            retransmit: RetransmitPolicy {
+                initial_timeout: Duration::from_millis(50),
+                max_timeout: Duration::from_millis(100),
+                max_retries: 1,
+            },
+            ..OvpConfig::default()
+        };
+        let mut sender = OVP::with_config("lo", 930_001, config).unwrap();
+        
+        let report = sender.emit_reliable_multicast(&[930_002, 930_003, 930_099], b"team").unwrap();
+        assert_eq!(report, vec![
+            (930_002, DeliveryStatus::Delivered),
+            (930_003, DeliveryStatus::Delivered),
+            (930_099, DeliveryStatus::TimedOut),
+        ]);
+        assert_eq!(sender.stats().retransmissions, 1);
+        assert!(sender.emit_reliable_multicast(&[], b"nobody").is_err());
+        
+        for (done, handle) in listeners {
+            done.store(true, Ordering::SeqCst);
+            assert!(handle.join().unwrap().iter().any(|payload| payload == b"team"));
+        }
+    }
+
+    /// Test FEC emission end to end
+    /// 
+    /// A multi-frame message is delivered once, whole, after K shards.
+    #[test]
+    fn test_fec_message_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let (done, handle) = spawn_listener(950_002, OvpConfig::default());
+        let mut sender = OVP::new("lo", 950_001).unwrap();
+        let tile: Vec<u8> = (0..2500u32).map(|i| (i % 251) as u8).collect();
+        sender.emit_fec(Some(&[950_002]), &tile, FecParams::new(3, 2)).unwrap();
+        assert!(sender.emit_fec(None, &tile, FecParams::new(1, 1)).is_err());
+        
+        thread::sleep(Duration::from_millis(100));
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert_eq!(heard.iter().filter(|payload| **payload == tile).count(), 1);
+    }
+
+    /// Test transparent fragmentation
+    /// 
+    /// `emit` accepts a payload several frames long; the receiver gets it
+    /// back whole, exactly once.
+    #[test]
+    fn test_fragmented_emit_over_loopback() {
+        use std::sync::atomic::Ordering;
+        
+        let (done, handle) = spawn_listener(960_002, OvpConfig::default());
+        let mut sender = OVP::new("lo", 960_001).unwrap();
+        let waypoints: Vec<u8> = (0..5000u32).map(|i| (i % 253) as u8).collect();
+        sender.emit(Some(&[960_002]), &waypoints).unwrap();
+        
+        thread::sleep(Duration::from_millis(100));
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert_eq!(heard.iter().filter(|payload| **payload == waypoints).count(), 1);
+    }
+
+    /// Test receiver-side duplicate suppression
+    /// 
+    /// The same frame emitted twice reaches the application once.
+    #[test]
+    fn test_duplicate_frames_suppressed() {
+        use std::sync::atomic::Ordering;
+        
+        let config = OvpConfig { dedup: Some(DedupConfig::default()), ..OvpConfig::default() };
+        let (done, handle) = spawn_listener(970_002, config);
+        let mut sender = OVP::new("lo", 970_001).unwrap();
+        for _ in 0..2 {
+            sender.emit(Some(&[970_002]), b"once only").unwrap();
+        }
+        
+        thread::sleep(Duration::from_millis(100));
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert_eq!(heard.iter().filter(|payload| *payload == b"once only").count(), 1);
+    }
+
+    /// Test priority classes end to end
+    ///
+    /// With several frames pending, the emergency frame sent last is
+    /// delivered before the bulk frames sent ahead of it.
+    #[test]
+    fn test_priority_frames_delivered_first() {
+        let mut receiver = OVP::new("lo", 990_002).unwrap();
+        let mut sender = OVP::new("lo", 990_001).unwrap();
+        for _ in 0..3 {
+            sender.emit_with_priority(Some(&[990_002]), b"prio-bulk", Priority::Bulk).unwrap();
+        }
+        sender.emit_with_priority(Some(&[990_002]), b"prio-stop", Priority::Emergency).unwrap();
+        assert_eq!(sender.socket_priority, Priority::Emergency);
+        thread::sleep(Duration::from_millis(20));
+
+        let mut heard = Vec::new();
+        for _ in 0..1000 {
+            if !receiver.has_pending() && !receiver.socket.wait_readable(Duration::from_millis(20)) {
+                break;
+            }
+            if let Some(payload) = receiver.try_receive().filter(|p| p.starts_with(b"prio-")) {
+                heard.push(payload.to_vec());
+            }
+        }
+        assert_eq!(heard.first().map(Vec::as_slice), Some(b"prio-stop".as_slice()));
+        assert!(heard.iter().filter(|p| *p == b"prio-bulk").count() >= 3);
+    }
+
+    /// Test transmit budgets in each throttle mode
+    ///
+    /// 100 frames/s with a 100 ms burst admits ten frames back to back; the
+    /// eleventh waits, is dropped or is refused depending on the mode.
+    #[test]
+    fn test_rate_limit_modes() {
+        let budget = |mode| OvpConfig {
+            rate_limit: RateLimitConfig { node: Some(RateLimit::new(100, 0)), mode, ..RateLimitConfig::default() },
+            ..OvpConfig::default()
+        };
+
+        let mut sender = OVP::with_config("lo", 995_001, budget(ThrottleMode::Reject)).unwrap();
+        for _ in 0..10 {
+            sender.emit(Some(&[995_002]), b"budget").unwrap();
+        }
+        let error = sender.emit(Some(&[995_002]), b"budget").unwrap_err();
+        assert!(error.downcast_ref::<WouldExceedBudget>().is_some_and(|e| e.retry_after > Duration::ZERO));
+        assert_eq!(sender.stats().throttled_rejected, 1);
+
+        let mut sender = OVP::with_config("lo", 995_001, budget(ThrottleMode::Drop)).unwrap();
+        for _ in 0..12 {
+            sender.emit(Some(&[995_002]), b"budget").unwrap();
+        }
+        assert_eq!(sender.stats().throttled_dropped, 2);
+
+        let mut sender = OVP::with_config("lo", 995_001, budget(ThrottleMode::Block)).unwrap();
+        let start = Instant::now();
+        for _ in 0..12 {
+            sender.emit(Some(&[995_002]), b"budget").unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(15));
+        assert_eq!(sender.stats().throttled_delayed, 2);
+    }
+
+    /// Test beacon pacing
+    ///
+    /// A beacon is sent at most once per beacon interval; an idle channel
+    /// keeps the interval at its minimum.
+    #[test]
+    fn test_beacon_pacing() {
+        let mut ovp = OVP::new("lo", 996_001).unwrap();
+        assert_eq!(ovp.channel_load(), 0.0);
+        assert_eq!(ovp.beacon_interval(), CongestionConfig::default().min_interval);
+
+        assert!(ovp.emit_beacon(b"beacon").unwrap());
+        assert!(!ovp.emit_beacon(b"beacon").unwrap());
+        thread::sleep(ovp.beacon_interval() * 11 / 10);
+        assert!(ovp.emit_beacon(b"beacon").unwrap());
+        assert_eq!(ovp.stats().beacons_sent, 2);
+    }
+
+    /// Test in-order delivery of ordered streams
+    ///
+    /// Frames emitted out of sequence are released in order under `Reorder`
+    /// (a gap that never fills is skipped after the timeout); `LatestWins`
+    /// drops the late frame instead.
+    #[test]
+    fn test_ordered_streams_over_loopback() {
+        use std::sync::atomic::Ordering;
+
+        let reorder = OrderingPolicy::Reorder { timeout: Duration::from_millis(30) };
+        let config = OvpConfig {
+            ordering: HashMap::from([(1, reorder), (2, OrderingPolicy::LatestWins)]),
+            ..OvpConfig::default()
+        };
+        let (done, handle) = spawn_listener(980_002, config);
+        let mut sender = OVP::new("lo", 980_001).unwrap();
+
+        let flags = FLAG_ORIGIN | FLAG_SEQUENCE | FLAG_ORDERED;
+        let sends: [(MessageType, u32, &[u8]); 7] = [
+            (1, 100, b"ord-a"), (1, 102, b"ord-c"), (1, 101, b"ord-b"), (1, 104, b"ord-e"),
+            (2, 500, b"ord-new"), (2, 498, b"ord-old"), (2, 501, b"ord-newer"),
+        ];
+        for (message_type, sequence, payload) in sends {
+            let meta = FrameMeta { flags, sequence, message_type, ..FrameMeta::default() };
+            sender.emit_frame(meta, &[980_002], payload).unwrap();
+        }
+
+        thread::sleep(Duration::from_millis(150));
+        done.store(true, Ordering::SeqCst);
+        let heard: Vec<Vec<u8>> = handle.join().unwrap().into_iter().filter(|p| p.starts_with(b"ord-")).collect();
+        let expected: [&[u8]; 6] = [b"ord-a", b"ord-b", b"ord-c", b"ord-new", b"ord-newer", b"ord-e"];
+        assert_eq!(heard, expected.map(<[u8]>::to_vec));
+    }
+
+    /// Serialises loopback tests with relays, HELLOs, route discovery or
+    /// positions, which would relay each other's frames or turn up among
+    /// each other's neighbours
+    static SWARM_TESTS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    
+    /// Hold the loopback swarm to this test (survives a panicking test)
+    fn swarm_guard() -> std::sync::MutexGuard<'static, ()> {
+        SWARM_TESTS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Test multi-hop relaying
+    /// 
+    /// A relay rebroadcasts a frame addressed to someone else exactly once;
+    /// the target delivers the first copy and drops the relayed one. A
+    /// frame with no TTL left is not relayed.
+    #[test]
+    fn test_multihop_relay_over_loopback() {
+        let _serial = swarm_guard();
+        let relay_config = OvpConfig { relay: Some(RelayConfig::default()), ..OvpConfig::default() };
+        let mut relay = OVP::with_config("lo", 997_002, relay_config).unwrap();
+        let mut target = OVP::new("lo", 997_003).unwrap();
+        let mut sender = OVP::new("lo", 997_001).unwrap();
+        
+        sender.emit_multihop(Some(&[997_003]), b"hop-far", 2).unwrap();
+        sender.emit_multihop(Some(&[997_003]), b"hop-near", 0).unwrap();
+        
+        let deadline = Instant::now() + Duration::from_millis(100);
+        while Instant::now() < deadline {
+            relay.service();
+            if relay.socket.wait_readable(Duration::from_millis(2)) {
+                assert!(relay.receive_message().is_none_or(|m| !m.payload.starts_with(b"hop-")));
+            }
+        }
+        assert_eq!(relay.stats().frames_relayed, 1);
+        
+        let mut heard = Vec::new();
+        while target.has_pending() || target.socket.wait_readable(Duration::from_millis(20)) {
+            if let Some(payload) = target.try_receive().filter(|p| p.starts_with(b"hop-")) {
+                heard.push(payload.to_vec());
+            }
+        }
+        assert_eq!(heard, vec![b"hop-far".to_vec(), b"hop-near".to_vec()]);
+        assert!(target.stats().duplicates_dropped >= 1);
+    }
+
+    /// Test distance-based relay suppression
+    /// 
+    /// A frame from a transmitter within `min_distance` is not worth
+    /// relaying; one from further away is relayed.
+    #[test]
+    fn test_distance_relay_policy() {
+        let _serial = swarm_guard();
+        let policy = RelayPolicy::Distance { min_distance: 50.0 };
+        let config = OvpConfig { relay: Some(RelayConfig { policy, ..RelayConfig::default() }), ..OvpConfig::default() };
+        let mut relay = OVP::with_config("lo", 998_002, config).unwrap();
+        relay.set_position(Position::new(0.0, 0.0, 30.0));
+        let mut sender = OVP::new("lo", 998_001).unwrap();
+        
+        sender.set_position(Position::new(10.0, 0.0, 30.0));
+        sender.emit_multihop(None, b"near-hop", 3).unwrap();
+        sender.set_position(Position::new(500.0, 0.0, 30.0));
+        sender.emit_multihop(None, b"far-hop", 3).unwrap();
+        
+        let deadline = Instant::now() + Duration::from_millis(100);
+        while Instant::now() < deadline {
+            relay.service();
+            if relay.socket.wait_readable(Duration::from_millis(2)) {
+                relay.receive_message();
+            }
+        }
+        assert_eq!((relay.stats().frames_relayed, relay.stats().relays_suppressed), (1, 1));
+    }
+
+    /// Test neighbour discovery
+    /// 
+    /// Two drones exchanging HELLOs list each other with position, link
+    /// quality and ETX; a drone that falls silent is removed after the expiry.
+    #[test]
+    fn test_neighbor_discovery_over_loopback() {
+        let _serial = swarm_guard();
+        let discovery = NeighborConfig { hello_interval: Duration::from_millis(20), expiry: Duration::from_millis(150) };
+        let config = OvpConfig { neighbors: Some(discovery), ..OvpConfig::default() };
+        let mut a = OVP::with_config("lo", 999_001, config.clone()).unwrap();
+        let mut b = OVP::with_config("lo", 999_002, config).unwrap();
+        b.set_position(Position::new(5.0, 5.0, 20.0));
+        
+        run_swarm(&mut [&mut a, &mut b], Duration::from_millis(150), b"");
+        
+        let neighbor = a.neighbors().into_iter().find(|n| n.id == 999_002).unwrap();
+        assert_eq!(neighbor.position, Some(Position::new(5.0, 5.0, 20.0)));
+        assert!(neighbor.link_quality > 0.5 && neighbor.frame_rate >= 0.0);
+        assert!(neighbor.inbound_delivery > 0.5 && neighbor.outbound_delivery.is_some_and(|r| r > 0.5));
+        assert!(neighbor.etx.is_some_and(|etx| etx < 2.0) && neighbor.rssi.is_none());
+        assert!(b.neighbors().iter().any(|n| n.id == 999_001 && n.position.is_none()));
+        let events: Vec<_> = std::iter::from_fn(|| a.next_neighbor_event()).collect();
+        assert!(events.contains(&NeighborEvent::Added(999_002)));
+        assert!(a.stats().hellos_sent >= 5);
+        
+        drop(b);
+        run_swarm(&mut [&mut a], Duration::from_millis(250), b"");
+        assert!(a.neighbors().iter().all(|n| n.id != 999_002));
+        assert!(std::iter::from_fn(|| a.next_neighbor_event()).any(|e| e == NeighborEvent::Removed(999_002)));
+    }
+
+    /// Test on-demand routing
+    /// 
+    /// A route request finds the destination and the reply releases the
+    /// queued frame; a frame steered through an intermediate drone is
+    /// forwarded, and a forwarder without a route breaks the origin's route.
+    #[test]
+    fn test_routing_over_loopback() {
+        let _serial = swarm_guard();
+        let routing = RoutingConfig { discovery_timeout: Duration::from_millis(50), ..RoutingConfig::default() };
+        let config = OvpConfig { routing: Some(routing), ..OvpConfig::default() };
+        let mut a = OVP::with_config("lo", 985_001, config.clone()).unwrap();
+        let mut b = OVP::with_config("lo", 985_002, config.clone()).unwrap();
+        let mut c = OVP::with_config("lo", 985_003, config).unwrap();
+        let run = |ovps: &mut [&mut OVP]| run_swarm(ovps, Duration::from_millis(100), b"route-");
+        
+        // On loopback the destination answers the request directly
+        a.emit_routed(985_003, b"route-direct").unwrap();
+        let heard = run(&mut [&mut a, &mut b, &mut c]);
+        assert_eq!(heard[2], vec![b"route-direct".to_vec()]);
+        assert!(heard[1].is_empty());
+        assert!(a.routes().iter().any(|r| r.destination == 985_003 && r.next_hop == 985_003 && r.hops == 1 && r.valid));
+        assert!(c.routes().iter().any(|r| r.destination == 985_001 && r.valid));
+        assert_eq!(a.stats().route_requests_sent, 1);
+        
+        // Steer the route through b
+        let expires = Instant::now() + Duration::from_secs(3);
+        a.routing_table.update(985_003, 985_002, 2, 100, expires);
+        b.routing_table.update(985_003, 985_003, 1, 100, expires);
+        a.emit_routed(985_003, b"route-via-b").unwrap();
+        let heard = run(&mut [&mut a, &mut b, &mut c]);
+        assert_eq!(heard[2], vec![b"route-via-b".to_vec()]);
+        assert!(heard[1].is_empty());
+        assert_eq!(b.stats().frames_routed, 1);
+        
+        // b loses its route: the frame is dropped and a's route through b breaks
+        b.routing_table.break_link(985_003);
+        a.emit_routed(985_003, b"route-lost").unwrap();
+        let heard = run(&mut [&mut a, &mut b, &mut c]);
+        assert!(heard[2].is_empty());
+        assert_eq!(b.stats().routing_failures, 1);
+        assert!(a.routes().iter().any(|r| r.destination == 985_003 && !r.valid));
+    }
+
+    /// Test geographic forwarding
+    /// 
+    /// Three drones in a line: a frame for the far end's surroundings goes
+    /// straight there; one for a point beyond the line walks the perimeter
+    /// and is delivered by the drone nearest the point.
+    #[test]
+    fn test_geo_forwarding_over_loopback() {
+        let _serial = swarm_guard();
+        let discovery = NeighborConfig { hello_interval: Duration::from_millis(20), expiry: Duration::from_secs(1) };
+        let config = OvpConfig { geo: Some(GeoConfig::default()), neighbors: Some(discovery), ..OvpConfig::default() };
+        let mut ovps: Vec<OVP> = (0..3u64)
+            .map(|i| {
+                let mut ovp = OVP::with_config("lo", 986_001 + i, config.clone()).unwrap();
+                ovp.set_position(Position::new(i as f32 * 50.0, 0.0, 10.0));
+                ovp
+            })
+            .collect();
+        let run = |ovps: &mut [OVP]| run_swarm(ovps, Duration::from_millis(120), b"geo-");
+        run(&mut ovps);
+        
+        let zone = GeoDestination::sphere(Position::new(100.0, 0.0, 10.0), 5.0);
+        ovps[0].emit_geo(zone, b"geo-zone").unwrap();
+        let heard = run(&mut ovps);
+        assert_eq!(heard, vec![Vec::new(), Vec::new(), vec![b"geo-zone".to_vec()]]);
+        
+        // Around the line and back to the drone nearest the point
+        let point = GeoDestination::point(Position::new(200.0, 0.0, 10.0));
+        ovps[0].emit_geo(point, b"geo-point").unwrap();
+        let heard = run(&mut ovps);
+        assert_eq!(heard, vec![Vec::new(), Vec::new(), vec![b"geo-point".to_vec()]]);
+        assert_eq!(ovps[1].stats().geo_forwarded, 2);
+        assert!(ovps[0].emit_geo(GeoDestination::sphere(Position::new(0.0, 0.0, 10.0), 1.0), b"geo-here").is_err());
+    }
+
+    /// Test delay-tolerant store-and-forward
+    /// 
+    /// A drone that was not around when a bundle was sent receives it from
+    /// the drones carrying it once it shows up, exactly once.
+    #[test]
+    fn test_store_and_forward_over_loopback() {
+        let _serial = swarm_guard();
+        let discovery = NeighborConfig { hello_interval: Duration::from_millis(20), expiry: Duration::from_secs(1) };
+        let config = OvpConfig { dtn: Some(DtnConfig::default()), neighbors: Some(discovery), ..OvpConfig::default() };
+        let join = |id: DroneId| OVP::with_config("lo", id, config.clone()).unwrap();
+        let run = |ovps: &mut [OVP]| run_swarm(ovps, Duration::from_millis(150), b"dtn-");
+        
+        let mut ovps = vec![join(988_001), join(988_002)];
+        run(&mut ovps);
+        ovps[0].emit_stored(None, b"dtn-mission", Priority::High, Duration::from_secs(60)).unwrap();
+        let heard = run(&mut ovps);
+        assert_eq!(heard, vec![Vec::new(), vec![b"dtn-mission".to_vec()]]);
+        assert_eq!(ovps[1].stats().bundles_stored, 1);
+        
+        // A scout coming back into range
+        ovps.push(join(988_003));
+        let heard = run(&mut ovps);
+        assert_eq!(heard, vec![Vec::new(), Vec::new(), vec![b"dtn-mission".to_vec()]]);
+        assert_eq!(ovps[2].stats().bundles_stored, 1);
+        assert!(ovps[0].stats().bundles_forwarded + ovps[1].stats().bundles_forwarded >= 2);
+        assert!(OVP::new("lo", 988_004).unwrap().emit_stored(None, b"dtn-off", Priority::Normal, Duration::from_secs(1)).is_err());
+    }
+
+    /// Test multicast group addressing
+    /// 
+    /// Only members receive a group frame, which carries one 8-byte entry
+    /// however many members the group has.
+    #[test]
+    fn test_groups_over_loopback() {
+        let alpha = group_id("team-alpha");
+        let config = OvpConfig { dedup: Some(DedupConfig::default()), ..OvpConfig::default() };
+        let mut ovps: Vec<OVP> = (0..3u64).map(|i| OVP::with_config("lo", 989_001 + i, config.clone()).unwrap()).collect();
+        ovps[1].join_group(alpha);
+        ovps[2].join_group(group_id("team-bravo"));
+        assert_eq!(ovps[1].groups(), vec![alpha]);
+        
+        let run = |ovps: &mut [OVP]| run_swarm(ovps, Duration::from_millis(50), b"group-");
+        ovps[0].emit_to_groups(&[alpha], b"group-hold").unwrap();
+        assert_eq!(run(&mut ovps), vec![Vec::new(), vec![b"group-hold".to_vec()], Vec::new()]);
+        
+        assert!(ovps[1].leave_group(alpha));
+        assert!(!ovps[1].leave_group(alpha));
+        ovps[0].emit_to_groups(&[alpha], b"group-again").unwrap();
+        assert_eq!(run(&mut ovps), vec![Vec::<Vec<u8>>::new(); 3]);
+        assert!(ovps[0].emit_to_groups(&[], b"group-none").is_err());
+    }
+
+    /// Test geocast delivery and relaying
+    /// 
+    /// Only the drone inside the region delivers the frame, and it relays it
+    /// once; the drone outside and farther away neither delivers nor relays.
+    #[test]
+    fn test_geocast_over_loopback() {
+        let _serial = swarm_guard();
+        let config = OvpConfig { geocast: Some(GeocastConfig::default()), ..OvpConfig::default() };
+        let mut ovps: Vec<OVP> = [0.0, 10.0, 100.0].iter().enumerate()
+            .map(|(i, &x)| {
+                let mut ovp = OVP::with_config("lo", 987_001 + i as u64, config.clone()).unwrap();
+                ovp.set_position(Position::new(x, 0.0, 10.0));
+                ovp
+            })
+            .collect();
+        
+        let region = GeoRegion::Sphere { center: Position::new(10.0, 0.0, 10.0), radius: 20.0 };
+        ovps[0].emit_geocast(&region, b"geocast-land").unwrap();
+        let heard = run_swarm(&mut ovps, Duration::from_millis(100), b"geocast-");
+        
+        assert_eq!(heard, vec![Vec::new(), vec![b"geocast-land".to_vec()], Vec::new()]);
+        assert_eq!(ovps[1].stats().geocasts_relayed, 1);
+        assert_eq!(ovps[2].stats().geocasts_relayed, 0);
+        assert!(ovps[0].emit_geocast(&GeoRegion::Polygon { vertices: vec![(0.0, 0.0)], floor: 0.0, ceiling: 1.0 }, b"geocast-bad").is_err());
+    }
+
+    /// Test ghost mode end to end
+    /// 
+    /// Between enrolled drones a pseudonymous frame is delivered to its
+    /// target, neither drone's real ID appears in the raw frame, and a
+    /// revoked drone is recognised by its pseudonymous origin.
+    #[test]
+    fn test_ghost_mode_over_loopback() {
+        let config = OvpConfig {
+            pseudonyms: Some(PseudonymScheme::new([0x33; 32], Duration::from_secs(60))),
+            dedup: Some(DedupConfig::default()),
+            ..OvpConfig::default()
+        };
+        let mut sender = OVP::with_config("lo", 926_001, config.clone()).unwrap();
+        let mut receiver = OVP::with_config("lo", 926_002, config).unwrap();
+        sender.membership = Some(Membership::new([0x34; SWARM_KEY_SIZE], 926_001, 0));
+        receiver.membership = Some(Membership::new([0x34; SWARM_KEY_SIZE], 926_002, 0));
+        let mut sniffer = OVP::new("lo", 926_009).unwrap();
+        
+        sender.emit(Some(&[926_002]), b"ghost-hello").unwrap();
+        let mut captured = Vec::new();
+        while sniffer.socket.wait_readable(Duration::from_millis(20)) {
+            let frame = sniffer.socket.receive_frame().unwrap();
+            if frame.windows(11).any(|w| w == b"ghost-hello") {
+                captured.push(frame.to_vec());
+            }
+        }
+        assert!(!captured.is_empty());
+        let view = frame::parse_frame(&captured[0]).unwrap();
+        assert!(view.epoch.is_some() && view.source.is_some_and(|source| source != 926_001));
+        for id in [926_001 as DroneId, 926_002] {
+            assert!(captured.iter().all(|frame| !frame.windows(8).any(|w| w == id.to_le_bytes())));
+        }
+        let heard = run_swarm(&mut [&mut receiver], Duration::from_millis(50), b"ghost-");
+        assert_eq!(heard[0], vec![b"ghost-hello".to_vec()]);
+        
+        receiver.apply_revocation(926_001);
+        sender.emit(Some(&[926_002]), b"ghost-revoked").unwrap();
+        let heard = run_swarm(&mut [&mut receiver], Duration::from_millis(50), b"ghost-");
+        assert!(heard[0].is_empty());
+        assert!(receiver.stats().revoked_frames >= 1);
+    }
+
+    /// Test NACK-based reliable broadcast
+    /// 
+    /// A broadcast that never reaches the air leaves a gap; the receiver
+    /// NACKs it and the sender repairs it from its buffer.
+    #[test]
+    fn test_reliable_broadcast_repairs_gap() {
+        use std::sync::atomic::Ordering;
+        
+        let (done, handle) = spawn_listener(940_002, OvpConfig::default());
+        let mut sender = OVP::new("lo", 940_001).unwrap();
+        
+        sender.emit_reliable_broadcast(b"mission-1").unwrap();
+        // Simulate losing mission-2 on the air
+        let lost = sender.next_broadcast_sequence;
+        sender.next_broadcast_sequence += 1;
+        sender.repair_buffer.push(lost, b"mission-2", 16, Instant::now());
+        sender.emit_reliable_broadcast(b"mission-3").unwrap();
+        
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while sender.stats().repairs_sent == 0 && Instant::now() < deadline {
+            if sender.socket.wait_readable(Duration::from_millis(10)) {
+                sender.receive_message();
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+        
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert_eq!(sender.stats().repairs_sent, 1);
+        for mission in [b"mission-1", b"mission-2", b"mission-3"] {
+            assert_eq!(heard.iter().filter(|payload| *payload == mission).count(), 1);
+        }
+    }
+
+    /// Test repair of the last frame of a burst
+    /// 
+    /// No later broadcast reveals the loss; the sender's session heartbeat
+    /// does, and the receiver NACKs it.
+    #[test]
+    fn test_reliable_broadcast_repairs_final_frame() {
+        use std::sync::atomic::Ordering;
+        
+        let (done, handle) = spawn_listener(940_012, OvpConfig::default());
+        let nack = NackConfig { heartbeat_interval: Duration::from_millis(20), ..NackConfig::default() };
+        let mut sender = OVP::with_config("lo", 940_011, OvpConfig { nack, ..OvpConfig::default() }).unwrap();
+        
+        sender.emit_reliable_broadcast(b"update-1").unwrap();
+        // Simulate losing the final update on the air
+        let lost = sender.next_broadcast_sequence;
+        sender.next_broadcast_sequence += 1;
+        sender.repair_buffer.push(lost, b"update-2", 16, Instant::now());
+        
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while sender.stats().repairs_sent == 0 && Instant::now() < deadline {
+            if sender.socket.wait_readable(Duration::from_millis(5)) {
+                sender.receive_message();
+            } else {
+                sender.service();
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+        
+        done.store(true, Ordering::SeqCst);
+        let heard = handle.join().unwrap();
+        assert!(sender.stats().heartbeats_sent >= 1);
+        assert_eq!(sender.stats().repairs_sent, 1);
+        for update in [b"update-1", b"update-2"] {
+            assert_eq!(heard.iter().filter(|payload| *payload == update).count(), 1);
+        }
+    }
+
+    /// Test that session heartbeats stop once the sender has been quiet a while
+    /// 
+    /// The repair buffer keeps the broadcast, but only `max_heartbeats`
+    /// announcements go out for it, at doubling intervals.
+    #[test]
+    fn test_session_heartbeats_stop_when_quiet() {
+        let nack = NackConfig { heartbeat_interval: Duration::from_millis(5), max_heartbeats: 3, ..NackConfig::default() };
+        let mut sender = OVP::with_config("lo", 940_021, OvpConfig { nack, ..OvpConfig::default() }).unwrap();
+        
+        sender.emit_reliable_broadcast(b"status").unwrap();
+        // Heartbeats are due after 5, 15 and 35 ms, then never again
+        run_swarm(&mut [&mut sender], Duration::from_millis(200), b"");
+        assert_eq!(sender.stats().heartbeats_sent, 3);
+        assert!(sender.repair_buffer.newest().is_some());
+        
+        // A new broadcast renews the announcements
+        sender.emit_reliable_broadcast(b"status").unwrap();
+        run_swarm(&mut [&mut sender], Duration::from_millis(100), b"");
+        assert_eq!(sender.stats().heartbeats_sent, 6);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,438 @@
+//! # NACK-Based Reliable Broadcast
+//!
+//! Swarm-wide dissemination without ACK implosion, in the style of PGM/NORM.
+//!
+//! Reliable broadcasts carry the sender's origin and a sequence number from a
+//! dedicated per-sender stream. Receivers track the next expected number per
+//! sender; a jump reveals a gap. Each missing number gets a NACK timer drawn
+//! at random, and a receiver that overhears someone else's NACK for the same
+//! frame postpones its own, so one request usually speaks for the whole
+//! neighbourhood. The sender keeps a bounded ring of recent frames and
+//! rebroadcasts those that are asked for.
+//!
+//! A loss at the very end of a burst leaves no later frame to reveal it, so
+//! after each broadcast the sender also sends a few session heartbeats with
+//! its newest sequence number: the first after `heartbeat_interval` of quiet,
+//! then at doubling intervals until `max_heartbeats` have gone out. Receivers
+//! treat every number up to it that they have not seen as a gap.
+//!
+//! ```text
+//! NACK body:    [nacked_source:8][count:1][sequence:4]*count
+//! SESSION body: [newest_sequence:4]
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::random::Jitter;
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Most sequence numbers requested by one NACK
+pub(crate) const MAX_NACK_SEQUENCES: usize = 255;
+
+/// Most senders whose streams are tracked at once (least recently heard evicted)
+const MAX_STREAMS: usize = 256;
+
+/// Tuning for NACK-based reliable broadcast
+#[derive(Debug, Copy, Clone)]
+pub struct NackConfig {
+    /// Recent broadcasts the sender keeps for repair
+    pub repair_buffer: usize,
+
+    /// Upper bound of the random delay before a receiver NACKs a gap
+    pub max_nack_delay: Duration,
+
+    /// NACKs sent for one missing frame before the receiver gives up on it
+    pub max_nack_attempts: u32,
+
+    /// Quiet time after which a sender announces its newest sequence number
+    /// (doubling after every heartbeat)
+    pub heartbeat_interval: Duration,
+
+    /// Heartbeats sent after a broadcast before the sender falls silent
+    pub max_heartbeats: u32,
+}
+
+impl Default for NackConfig {
+    fn default() -> Self {
+        NackConfig {
+            repair_buffer: 256,
+            max_nack_delay: Duration::from_millis(20),
+            max_nack_attempts: 5,
+            heartbeat_interval: Duration::from_millis(250),
+            max_heartbeats: 4,
+        }
+    }
+}
+
+//==============================================================================
+// NACK ENCODING
+//==============================================================================
+
+/// Decoded NACK control body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Nack {
+    /// Origin of the stream with gaps, as it appeared on the wire
+    pub source: DroneId,
+
+    /// Missing sequence numbers
+    pub sequences: Vec<u32>,
+}
+
+impl Nack {
+    /// Encode as a control body (at most `MAX_NACK_SEQUENCES` numbers)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = self.sequences.len().min(MAX_NACK_SEQUENCES);
+        let mut body = Vec::with_capacity(9 + count * 4);
+        body.extend_from_slice(&self.source.to_le_bytes());
+        body.push(count as u8);
+        for sequence in &self.sequences[..count] {
+            body.extend_from_slice(&sequence.to_le_bytes());
+        }
+        body
+    }
+
+    /// Decode a control body
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() < 9 || body.len() != 9 + body[8] as usize * 4 {
+            return None;
+        }
+        Some(Nack {
+            source: DroneId::from_le_bytes(body[..8].try_into().unwrap()),
+            sequences: body[9..]
+                .chunks_exact(4)
+                .map(|s| u32::from_le_bytes(s.try_into().unwrap()))
+                .collect(),
+        })
+    }
+}
+
+//==============================================================================
+// SENDER STATE
+//==============================================================================
+
+/// A broadcast kept for repair
+#[derive(Debug, Clone)]
+struct RepairEntry {
+    /// Stream sequence number
+    sequence: u32,
+
+    /// Original payload
+    payload: Vec<u8>,
+
+    /// Last (re)transmission, used to coalesce NACKs for the same frame
+    last_sent: Instant,
+}
+
+/// Ring of recent reliable broadcasts, oldest first
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RepairBuffer {
+    /// Consecutive sequence numbers
+    entries: VecDeque<RepairEntry>,
+}
+
+impl RepairBuffer {
+    /// Remember a broadcast, evicting the oldest beyond `capacity`
+    pub fn push(&mut self, sequence: u32, payload: &[u8], capacity: usize, now: Instant) {
+        if capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(RepairEntry { sequence, payload: payload.to_vec(), last_sent: now });
+    }
+
+    /// Sequence number of the newest frame kept, None while empty
+    pub fn newest(&self) -> Option<u32> {
+        self.entries.back().map(|entry| entry.sequence)
+    }
+
+    /// Payload to rebroadcast for `sequence`
+    ///
+    /// None if the frame was evicted or already (re)sent within `holdoff` -
+    /// several receivers NACKing the same loss get a single repair.
+    pub fn repair(&mut self, sequence: u32, now: Instant, holdoff: Duration) -> Option<Vec<u8>> {
+        let first = self.entries.front()?.sequence;
+        let entry = self.entries.get_mut(sequence.wrapping_sub(first) as usize)?;
+        if now.duration_since(entry.last_sent) < holdoff {
+            return None;
+        }
+        entry.last_sent = now;
+        Some(entry.payload.clone())
+    }
+}
+
+//==============================================================================
+// RECEIVER STATE
+//==============================================================================
+
+/// A missing frame awaiting repair
+#[derive(Debug, Copy, Clone)]
+struct Gap {
+    /// When to NACK it (pushed back whenever another receiver asks first)
+    due: Instant,
+
+    /// NACKs sent so far
+    attempts: u32,
+}
+
+/// Reception state of one sender's stream
+#[derive(Debug, Clone)]
+struct Stream {
+    /// Sequence number following the newest frame seen
+    next_expected: u32,
+
+    /// Missing frames older than `next_expected`
+    missing: HashMap<u32, Gap>,
+
+    /// Last frame from this sender, for eviction
+    last_heard: Instant,
+}
+
+impl Stream {
+    /// Move `next_expected` on by `ahead`, scheduling NACKs for the skipped
+    /// numbers - or forgetting all gaps if the jump exceeds the sender's
+    /// repair buffer and cannot be repaired
+    fn advance(&mut self, ahead: u32, now: Instant, config: &NackConfig, jitter: &mut Jitter) {
+        if ahead as usize > config.repair_buffer {
+            self.missing.clear();
+        } else {
+            for skipped in 0..ahead {
+                let due = now + jitter.below(config.max_nack_delay);
+                self.missing.insert(self.next_expected.wrapping_add(skipped), Gap { due, attempts: 0 });
+            }
+        }
+        self.next_expected = self.next_expected.wrapping_add(ahead);
+    }
+}
+
+/// Gap detection and NACK timers for every reliable-broadcast sender in range
+#[derive(Debug, Clone)]
+pub(crate) struct StreamTracker {
+    /// Streams keyed by wire source ID
+    streams: HashMap<DroneId, Stream>,
+
+    /// NACK timer randomisation
+    jitter: Jitter,
+
+    /// Frames currently missing across all streams
+    outstanding: usize,
+}
+
+impl StreamTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        StreamTracker { streams: HashMap::new(), jitter: Jitter::new(), outstanding: 0 }
+    }
+
+    /// Account for a received broadcast, returning false for duplicates
+    ///
+    /// The first frame heard from a sender starts its stream. A jump ahead
+    /// schedules NACKs for the skipped numbers; a jump larger than the
+    /// sender's repair buffer cannot be repaired and resynchronises instead.
+    pub fn observe(&mut self, source: DroneId, sequence: u32, now: Instant, config: &NackConfig) -> bool {
+        if !self.streams.contains_key(&source) && self.streams.len() >= MAX_STREAMS {
+            self.evict_stalest();
+        }
+        let stream = self.streams.entry(source).or_insert_with(|| Stream {
+            next_expected: sequence,
+            missing: HashMap::new(),
+            last_heard: now,
+        });
+        stream.last_heard = now;
+
+        let ahead = sequence.wrapping_sub(stream.next_expected);
+        if ahead > u32::MAX / 2 {
+            // Older than the newest frame: a repair we asked for, or a duplicate
+            let repaired = stream.missing.remove(&sequence).is_some();
+            self.outstanding -= repaired as usize;
+            return repaired;
+        }
+
+        let before = stream.missing.len();
+        stream.advance(ahead, now, config, &mut self.jitter);
+        self.outstanding = self.outstanding + stream.missing.len() - before;
+        stream.next_expected = sequence.wrapping_add(1);
+        true
+    }
+
+    /// Account for a session heartbeat announcing the sender's newest sequence number
+    ///
+    /// Numbers up to `newest` not seen yet become gaps, so the last frames
+    /// of a burst are repaired too. Streams not started yet are left alone.
+    pub fn heartbeat(&mut self, source: DroneId, newest: u32, now: Instant, config: &NackConfig) {
+        let Some(stream) = self.streams.get_mut(&source) else {
+            return;
+        };
+        stream.last_heard = now;
+        let ahead = newest.wrapping_add(1).wrapping_sub(stream.next_expected);
+        if ahead == 0 || ahead > u32::MAX / 2 {
+            return;
+        }
+        let before = stream.missing.len();
+        stream.advance(ahead, now, config, &mut self.jitter);
+        self.outstanding = self.outstanding + stream.missing.len() - before;
+    }
+
+    /// Postpone our NACKs for frames someone else just requested
+    pub fn suppress(&mut self, source: DroneId, sequences: &[u32], now: Instant, config: &NackConfig) {
+        let Some(stream) = self.streams.get_mut(&source) else {
+            return;
+        };
+        for sequence in sequences {
+            if let Some(gap) = stream.missing.get_mut(sequence) {
+                gap.due = gap.due.max(now + config.max_nack_delay + self.jitter.below(config.max_nack_delay));
+            }
+        }
+    }
+
+    /// Collect NACKs whose timers expired and re-arm them
+    ///
+    /// Returns the NACKs to send and the number of frames given up on after
+    /// `max_nack_attempts`.
+    pub fn due_nacks(&mut self, now: Instant, config: &NackConfig) -> (Vec<Nack>, usize) {
+        let mut nacks = Vec::new();
+        let mut abandoned = 0;
+        if self.outstanding == 0 {
+            return (nacks, abandoned);
+        }
+
+        for (&source, stream) in self.streams.iter_mut() {
+            let mut sequences = Vec::new();
+            stream.missing.retain(|&sequence, gap| {
+                if gap.due > now {
+                    return true;
+                }
+                if gap.attempts >= config.max_nack_attempts {
+                    abandoned += 1;
+                    return false;
+                }
+                if sequences.len() < MAX_NACK_SEQUENCES {
+                    sequences.push(sequence);
+                    gap.attempts += 1;
+                    // Leave the sender a full delay window to answer
+                    gap.due = now + config.max_nack_delay + self.jitter.below(config.max_nack_delay);
+                }
+                true
+            });
+            if !sequences.is_empty() {
+                sequences.sort_unstable_by_key(|s| s.wrapping_sub(stream.next_expected));
+                nacks.push(Nack { source, sequences });
+            }
+        }
+        self.outstanding -= abandoned;
+        (nacks, abandoned)
+    }
+
+    /// Forget the sender heard from least recently
+    fn evict_stalest(&mut self) {
+        let stalest = self.streams.iter().min_by_key(|(_, stream)| stream.last_heard).map(|(&id, _)| id);
+        if let Some(stream) = stalest.and_then(|id| self.streams.remove(&id)) {
+            self.outstanding -= stream.missing.len();
+        }
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gaps are NACKed once due, repairs are delivered once, duplicates never
+    #[test]
+    fn test_gap_detection_and_repair() {
+        let config = NackConfig { max_nack_delay: Duration::from_millis(10), ..NackConfig::default() };
+        let mut tracker = StreamTracker::new();
+        let now = Instant::now();
+
+        assert!(tracker.observe(7, 100, now, &config));
+        assert!(tracker.observe(7, 103, now, &config));
+        assert!(!tracker.observe(7, 103, now, &config));
+        assert_eq!(tracker.due_nacks(now, &config).0, vec![]);
+
+        // Someone else asked for 101 - only 102 is still ours to request
+        tracker.suppress(7, &[101], now, &config);
+        let (nacks, _) = tracker.due_nacks(now + Duration::from_millis(10), &config);
+        assert_eq!(nacks, vec![Nack { source: 7, sequences: vec![102] }]);
+
+        assert!(tracker.observe(7, 101, now, &config));
+        assert!(tracker.observe(7, 102, now, &config));
+        assert!(!tracker.observe(7, 101, now, &config));
+        assert_eq!(tracker.outstanding, 0);
+    }
+
+    /// Unanswered gaps are abandoned after the attempt limit
+    #[test]
+    fn test_gap_abandoned_after_attempts() {
+        let config = NackConfig { max_nack_attempts: 2, max_nack_delay: Duration::from_millis(1), ..NackConfig::default() };
+        let mut tracker = StreamTracker::new();
+        let mut now = Instant::now();
+        tracker.observe(7, u32::MAX, now, &config);
+        tracker.observe(7, 1, now, &config);
+
+        let mut sent = 0;
+        let mut abandoned = 0;
+        for _ in 0..4 {
+            now += Duration::from_millis(2);
+            let (nacks, gone) = tracker.due_nacks(now, &config);
+            sent += nacks.len();
+            abandoned += gone;
+        }
+        assert_eq!((sent, abandoned), (2, 1));
+    }
+
+    /// Heartbeats reveal losses at the end of a burst, and are ignored for
+    /// unknown streams or numbers already seen
+    #[test]
+    fn test_heartbeat_opens_trailing_gaps() {
+        let config = NackConfig { max_nack_delay: Duration::from_millis(10), ..NackConfig::default() };
+        let mut tracker = StreamTracker::new();
+        let now = Instant::now();
+
+        tracker.heartbeat(8, 5, now, &config);
+        assert!(tracker.streams.is_empty());
+
+        assert!(tracker.observe(7, 100, now, &config));
+        tracker.heartbeat(7, 100, now, &config);
+        tracker.heartbeat(7, 90, now, &config);
+        assert_eq!(tracker.outstanding, 0);
+
+        tracker.heartbeat(7, 102, now, &config);
+        tracker.heartbeat(7, 102, now, &config);
+        let (nacks, _) = tracker.due_nacks(now + Duration::from_millis(10), &config);
+        assert_eq!(nacks, vec![Nack { source: 7, sequences: vec![101, 102] }]);
+
+        assert!(tracker.observe(7, 102, now, &config));
+        assert!(tracker.observe(7, 103, now, &config));
+        assert_eq!(tracker.outstanding, 1);
+    }
+
+    /// The repair buffer answers once per holdoff and forgets evicted frames
+    #[test]
+    fn test_repair_buffer() {
+        let mut buffer = RepairBuffer::default();
+        let now = Instant::now();
+        let holdoff = Duration::from_millis(5);
+        for sequence in 10..14 {
+            buffer.push(sequence, &[sequence as u8], 3, now - holdoff);
+        }
+
+        assert_eq!(buffer.repair(10, now, holdoff), None);
+        assert_eq!(buffer.repair(12, now, holdoff), Some(vec![12]));
+        assert_eq!(buffer.repair(12, now, holdoff), None);
+        assert_eq!(buffer.repair(14, now, holdoff), None);
+        assert_eq!(buffer.newest(), Some(13));
+        assert_eq!(RepairBuffer::default().newest(), None);
+
+        let nack = Nack { source: 9, sequences: vec![1, 2] };
+        assert_eq!(Nack::parse(&nack.to_bytes()), Some(nack));
+    }
+}
@@ -0,0 +1,607 @@
+//! # Neighbour Discovery
+//!
+//! Periodic HELLO beacons and a table of the drones currently in range.
+//!
+//! With `OvpConfig::neighbors` set, every node broadcasts a small HELLO
+//! control message once per `hello_interval` (plus up to 10% jitter):
+//!
+//! ```text
+//! HELLO body: [interval_ms:4][mac:6][position:12][sequence:2][count:1]
+//!             ([id:8][ratio:1]) * count
+//! ```
+//!
+//! Any frame heard directly from a drone - HELLO or not, relayed copies
+//! excepted - puts it in the table and counts toward its frame rate. HELLOs
+//! add the hardware address, position and a link quality: the share of the
+//! HELLOs announced in the last `QUALITY_WINDOW` intervals that arrived.
+//! Entries not heard from for `expiry` are removed. Additions and removals
+//! are queued as [`NeighborEvent`]s.
+//!
+//! ## Link Estimation
+//!
+//! HELLOs are numbered, so gaps in the sequence give the share of a
+//! neighbour's last `QUALITY_WINDOW` HELLOs that reached us (inbound delivery
+//! ratio). Each HELLO reports that ratio, scaled to 0-255, for the drones its
+//! sender hears, so a neighbour's HELLO tells us how well it hears ours
+//! (outbound delivery ratio). Together they give the expected transmission
+//! count of the link, `ETX = 1 / (inbound * outbound)`, smoothed over HELLOs
+//! and capped at `MAX_ETX`. On monitor-mode interfaces the signal strength of
+//! each frame is averaged into an RSSI estimate as well.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::position::{Position, POSITION_SECTION_SIZE};
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of a HELLO control body without reception reports
+const HELLO_SIZE: usize = 13 + POSITION_SECTION_SIZE;
+
+/// Size of one reception report
+const REPORT_SIZE: usize = 9;
+
+/// Most reception reports in one HELLO (most recently heard neighbours first)
+const MAX_REPORTS: usize = 64;
+
+/// Sequence jump taken as a restarted sender rather than lost HELLOs
+const MAX_SEQUENCE_GAP: u16 = 1024;
+
+/// ETX of a link that delivers nothing in one direction
+const MAX_ETX: f64 = 16.0;
+
+/// Weight of a new sample in the smoothed ETX and RSSI
+const SMOOTHING: f64 = 0.25;
+
+/// HELLO intervals over which link quality is measured
+const QUALITY_WINDOW: u32 = 8;
+
+/// Length of one frame rate sample
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Most neighbours tracked at once (least recently heard evicted)
+const MAX_NEIGHBORS: usize = 256;
+
+/// Most undelivered events kept (oldest dropped first)
+const MAX_EVENTS: usize = 256;
+
+/// HELLO pacing and neighbour expiry
+#[derive(Debug, Copy, Clone)]
+pub struct NeighborConfig {
+    /// Time between our HELLO beacons
+    pub hello_interval: Duration,
+
+    /// Silence after which a neighbour is removed
+    pub expiry: Duration,
+}
+
+impl Default for NeighborConfig {
+    fn default() -> Self {
+        NeighborConfig { hello_interval: Duration::from_secs(1), expiry: Duration::from_secs(3) }
+    }
+}
+
+/// Change in the set of drones in range
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NeighborEvent {
+    /// A drone was heard for the first time (or again after expiring)
+    Added(DroneId),
+
+    /// A drone was not heard from for `NeighborConfig::expiry`
+    Removed(DroneId),
+}
+
+/// Snapshot of one drone in range
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Neighbor {
+    /// Drone identifier as seen on the wire (a pseudonym in ghost mode)
+    pub id: DroneId,
+
+    /// Hardware address of its interface, once a HELLO was heard
+    pub mac: Option<[u8; 6]>,
+
+    /// Last frame heard from it
+    pub last_heard: Instant,
+
+    /// Smoothed frames per second heard from it
+    pub frame_rate: f64,
+
+    /// Share of its recent HELLOs received, from 0 to 1 (0 until a HELLO is heard)
+    pub link_quality: f64,
+
+    /// Position from its last HELLO, if it reported one
+    pub position: Option<Position>,
+
+    /// Share of its recent HELLOs that reached us, from sequence gaps (0 until a HELLO is heard)
+    pub inbound_delivery: f64,
+
+    /// Share of our recent HELLOs that reached it, as it last reported
+    pub outbound_delivery: Option<f64>,
+
+    /// Smoothed expected transmissions per delivered frame (1 to 16), once both ratios are known
+    pub etx: Option<f64>,
+
+    /// Smoothed received signal strength in dBm, where the interface reports it
+    pub rssi: Option<f64>,
+}
+
+//==============================================================================
+// HELLO ENCODING
+//==============================================================================
+
+/// Decoded HELLO control body
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Hello {
+    /// Sender's HELLO interval
+    pub interval: Duration,
+
+    /// Sender's hardware address (all zero if unknown)
+    pub mac: [u8; 6],
+
+    /// Sender's position, if known
+    pub position: Option<Position>,
+
+    /// Sender's HELLO counter
+    pub sequence: u16,
+
+    /// Inbound delivery ratio (scaled to 0-255) of each drone the sender hears
+    pub reports: Vec<(DroneId, u8)>,
+}
+
+impl Hello {
+    /// Encode as a control body
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let reports = &self.reports[..self.reports.len().min(MAX_REPORTS)];
+        let mut body = Vec::with_capacity(HELLO_SIZE + reports.len() * REPORT_SIZE);
+        body.extend_from_slice(&(self.interval.as_millis().min(u32::MAX as u128) as u32).to_le_bytes());
+        body.extend_from_slice(&self.mac);
+        body.extend_from_slice(&Position::to_wire(self.position));
+        body.extend_from_slice(&self.sequence.to_le_bytes());
+        body.push(reports.len() as u8);
+        for &(id, ratio) in reports {
+            body.extend_from_slice(&id.to_le_bytes());
+            body.push(ratio);
+        }
+        body
+    }
+
+    /// Decode a control body
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() < HELLO_SIZE {
+            return None;
+        }
+        let fixed = 10 + POSITION_SECTION_SIZE;
+        let count = body[HELLO_SIZE - 1] as usize;
+        if body.len() != HELLO_SIZE + count * REPORT_SIZE {
+            return None;
+        }
+        let reports = body[HELLO_SIZE..].chunks_exact(REPORT_SIZE)
+            .map(|report| (DroneId::from_le_bytes(report[..8].try_into().unwrap()), report[8]))
+            .collect();
+        Some(Hello {
+            interval: Duration::from_millis(u32::from_le_bytes(body[..4].try_into().unwrap()) as u64),
+            mac: body[4..10].try_into().unwrap(),
+            position: Position::from_wire(&body[10..fixed]),
+            sequence: u16::from_le_bytes([body[fixed], body[fixed + 1]]),
+            reports,
+        })
+    }
+
+    /// Our outbound delivery ratio as the sender reports it
+    ///
+    /// A sender that does not list us hears none of our HELLOs, unless its
+    /// list was full and we may have been left out (None).
+    pub fn report_for(&self, is_us: impl Fn(DroneId) -> bool) -> Option<f64> {
+        match self.reports.iter().find(|&&(id, _)| is_us(id)) {
+            Some(&(_, ratio)) => Some(ratio as f64 / u8::MAX as f64),
+            None => (self.reports.len() < MAX_REPORTS).then_some(0.0),
+        }
+    }
+}
+
+/// Hardware address of a network interface, read from sysfs
+pub(crate) fn interface_mac(interface: &str) -> Option<[u8; 6]> {
+    let text = std::fs::read_to_string(format!("/sys/class/net/{interface}/address")).ok()?;
+    let mut mac = [0u8; 6];
+    let mut octets = text.trim().split(':');
+    for byte in mac.iter_mut() {
+        *byte = u8::from_str_radix(octets.next()?, 16).ok()?;
+    }
+    octets.next().is_none().then_some(mac)
+}
+
+//==============================================================================
+// NEIGHBOUR TABLE
+//==============================================================================
+
+/// What we know about one neighbour
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Hardware address from its HELLOs
+    mac: Option<[u8; 6]>,
+
+    /// Position from its last HELLO
+    position: Option<Position>,
+
+    /// Last frame heard
+    last_heard: Instant,
+
+    /// Start of the current frame rate sample
+    window_start: Instant,
+
+    /// Frames heard in the current sample
+    window_frames: u32,
+
+    /// Smoothed frame rate as of `window_start`
+    rate: f64,
+
+    /// HELLO interval it announced
+    interval: Option<Duration>,
+
+    /// First HELLO heard since it was added
+    first_hello: Option<Instant>,
+
+    /// Arrival of its recent HELLOs, oldest first
+    hellos: VecDeque<Instant>,
+
+    /// First HELLO sequence number counted
+    first_sequence: u16,
+
+    /// Sequence numbers of its recent HELLOs, oldest first
+    sequences: VecDeque<u16>,
+
+    /// Its last report of our outbound delivery ratio
+    outbound: Option<f64>,
+
+    /// Smoothed expected transmission count
+    etx: Option<f64>,
+
+    /// Smoothed signal strength in dBm
+    rssi: Option<f64>,
+}
+
+impl Entry {
+    /// Neighbour first heard at `now`
+    fn new(now: Instant) -> Self {
+        Entry {
+            mac: None,
+            position: None,
+            last_heard: now,
+            window_start: now,
+            window_frames: 0,
+            rate: 0.0,
+            interval: None,
+            first_hello: None,
+            hellos: VecDeque::new(),
+            first_sequence: 0,
+            sequences: VecDeque::new(),
+            outbound: None,
+            etx: None,
+            rssi: None,
+        }
+    }
+
+    /// Frame rate as of `now`
+    ///
+    /// Each finished sample is averaged into the estimate, so idle samples
+    /// halve it.
+    fn frame_rate(&self, now: Instant) -> f64 {
+        let samples = samples_since(self.window_start, now);
+        if samples == 0 {
+            return self.rate;
+        }
+        let rate = (self.rate + self.window_frames as f64 / RATE_WINDOW.as_secs_f64()) / 2.0;
+        rate / 2f64.powi(samples.min(64) as i32 - 1)
+    }
+
+    /// Count a frame heard at `now`, received at `rssi` dBm if known
+    fn observe_frame(&mut self, now: Instant, rssi: Option<i8>) {
+        if let Some(sample) = rssi {
+            self.rssi = Some(smooth(self.rssi, sample as f64));
+        }
+        let samples = samples_since(self.window_start, now);
+        if samples > 0 {
+            self.rate = self.frame_rate(now);
+            self.window_start += RATE_WINDOW * samples;
+            self.window_frames = 0;
+        }
+        self.window_frames += 1;
+        self.last_heard = now;
+    }
+
+    /// Share of the HELLOs announced over the quality window that arrived
+    fn link_quality(&self, now: Instant) -> f64 {
+        let (Some(interval), Some(first)) = (self.interval, self.first_hello) else {
+            return 0.0;
+        };
+        if interval.is_zero() {
+            return 1.0;
+        }
+        let window = interval * QUALITY_WINDOW;
+        let received = self.hellos.iter().filter(|&&at| now.saturating_duration_since(at) < window).count();
+        let since_first = now.saturating_duration_since(first).as_nanos() / interval.as_nanos();
+        let expected = (since_first + 1).min(QUALITY_WINDOW as u128) as u32;
+        (received as f64 / expected as f64).min(1.0)
+    }
+
+    /// Share of the last `QUALITY_WINDOW` HELLO sequence numbers received
+    fn inbound_delivery(&self) -> f64 {
+        let Some(&latest) = self.sequences.back() else {
+            return 0.0;
+        };
+        let span = latest.wrapping_sub(self.first_sequence) as u32 + 1;
+        let expected = span.min(QUALITY_WINDOW);
+        let received = self.sequences.iter().filter(|&&seq| (latest.wrapping_sub(seq) as u32) < expected).count();
+        received as f64 / expected as f64
+    }
+
+    /// Count a HELLO sequence number (duplicates ignored, large jumps restart the count)
+    fn observe_sequence(&mut self, sequence: u16) {
+        match self.sequences.back() {
+            Some(&latest) if sequence == latest => return,
+            Some(&latest) if sequence.wrapping_sub(latest) <= MAX_SEQUENCE_GAP => {}
+            _ => {
+                self.sequences.clear();
+                self.first_sequence = sequence;
+            }
+        }
+        self.sequences.push_back(sequence);
+        if self.sequences.len() > QUALITY_WINDOW as usize {
+            self.sequences.pop_front();
+        }
+    }
+
+    /// Fold a fresh ETX sample into the estimate, once the outbound ratio is known
+    fn update_etx(&mut self) {
+        let Some(outbound) = self.outbound else {
+            return;
+        };
+        let delivery = self.inbound_delivery() * outbound;
+        let sample = if delivery > 0.0 { (1.0 / delivery).min(MAX_ETX) } else { MAX_ETX };
+        self.etx = Some(smooth(self.etx, sample));
+    }
+}
+
+/// Exponentially weighted average of `sample` into `estimate`
+fn smooth(estimate: Option<f64>, sample: f64) -> f64 {
+    match estimate {
+        Some(estimate) => estimate + SMOOTHING * (sample - estimate),
+        None => sample,
+    }
+}
+
+/// Whole frame rate samples between `start` and `now`
+fn samples_since(start: Instant, now: Instant) -> u32 {
+    (now.saturating_duration_since(start).as_nanos() / RATE_WINDOW.as_nanos()).min(u32::MAX as u128) as u32
+}
+
+/// Drones heard recently, with pending add/remove events
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NeighborTable {
+    /// Entries keyed by wire source
+    entries: HashMap<DroneId, Entry>,
+
+    /// Events not yet handed to the application
+    events: VecDeque<NeighborEvent>,
+}
+
+impl NeighborTable {
+    /// Count a frame heard directly from `id`, received at `rssi` dBm if known
+    ///
+    /// Returns true if `id` was not in range before.
+    pub fn observe_frame(&mut self, id: DroneId, now: Instant, rssi: Option<i8>) -> bool {
+        let added = !self.entries.contains_key(&id);
+        if added {
+            if self.entries.len() >= MAX_NEIGHBORS {
+                let stalest = self.entries.iter().min_by_key(|(_, e)| e.last_heard).map(|(&id, _)| id);
+                if let Some(stalest) = stalest {
+                    self.entries.remove(&stalest);
+                    self.push_event(NeighborEvent::Removed(stalest));
+                }
+            }
+            self.entries.insert(id, Entry::new(now));
+            self.push_event(NeighborEvent::Added(id));
+        }
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.observe_frame(now, rssi);
+        }
+        added
+    }
+
+    /// Record a HELLO from `id` (its frame was counted already)
+    ///
+    /// `outbound` is the delivery ratio of our HELLOs it reports, if any.
+    pub fn observe_hello(&mut self, id: DroneId, hello: &Hello, outbound: Option<f64>, now: Instant) {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+        entry.mac = Some(hello.mac).filter(|mac| *mac != [0; 6]);
+        entry.position = hello.position;
+        entry.interval = Some(hello.interval);
+        entry.first_hello.get_or_insert(now);
+        entry.hellos.push_back(now);
+        let window = hello.interval * QUALITY_WINDOW;
+        while entry.hellos.front().is_some_and(|&at| now.saturating_duration_since(at) >= window) {
+            entry.hellos.pop_front();
+        }
+        while entry.hellos.len() > QUALITY_WINDOW as usize * 2 {
+            entry.hellos.pop_front();
+        }
+        entry.observe_sequence(hello.sequence);
+        if outbound.is_some() {
+            entry.outbound = outbound;
+        }
+        entry.update_etx();
+    }
+
+    /// Inbound delivery ratio of the neighbours we heard HELLOs from, most recently heard first
+    pub fn reception_reports(&self) -> Vec<(DroneId, u8)> {
+        let mut heard: Vec<(&DroneId, &Entry)> = self.entries.iter().filter(|(_, e)| !e.sequences.is_empty()).collect();
+        heard.sort_by_key(|&(_, entry)| std::cmp::Reverse(entry.last_heard));
+        heard.into_iter()
+            .take(MAX_REPORTS)
+            .map(|(&id, entry)| (id, (entry.inbound_delivery() * u8::MAX as f64).round() as u8))
+            .collect()
+    }
+
+    /// Remove neighbours silent for `expiry`, returning them
+    pub fn expire(&mut self, now: Instant, expiry: Duration) -> Vec<DroneId> {
+        let expired: Vec<DroneId> = self.entries.iter()
+            .filter(|(_, entry)| now.saturating_duration_since(entry.last_heard) >= expiry)
+            .map(|(&id, _)| id)
+            .collect();
+        for &id in &expired {
+            self.entries.remove(&id);
+            self.push_event(NeighborEvent::Removed(id));
+        }
+        expired
+    }
+
+    /// Snapshots of every neighbour as of `now`, by identifier
+    pub fn snapshot(&self, now: Instant) -> Vec<Neighbor> {
+        let mut neighbors: Vec<Neighbor> = self.entries.iter()
+            .map(|(&id, entry)| Neighbor {
+                id,
+                mac: entry.mac,
+                last_heard: entry.last_heard,
+                frame_rate: entry.frame_rate(now),
+                link_quality: entry.link_quality(now),
+                position: entry.position,
+                inbound_delivery: entry.inbound_delivery(),
+                outbound_delivery: entry.outbound,
+                etx: entry.etx,
+                rssi: entry.rssi,
+            })
+            .collect();
+        neighbors.sort_by_key(|neighbor| neighbor.id);
+        neighbors
+    }
+
+    /// Oldest event not yet handed out
+    pub fn next_event(&mut self) -> Option<NeighborEvent> {
+        self.events.pop_front()
+    }
+
+    /// Queue an event, dropping the oldest when full
+    fn push_event(&mut self, event: NeighborEvent) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn hello() -> Hello {
+        Hello {
+            interval: INTERVAL,
+            mac: [2, 0, 0, 0, 0, 7],
+            position: Some(Position::new(1.0, 2.0, 3.0)),
+            sequence: 0,
+            reports: vec![(1, 255), (3, 128)],
+        }
+    }
+
+    /// HELLO bodies survive encoding; malformed ones are rejected
+    #[test]
+    fn test_hello_roundtrip() {
+        let body = hello().to_bytes();
+        assert_eq!(Hello::parse(&body), Some(hello()));
+        assert_eq!(Hello::parse(&body[1..]), None);
+        assert_eq!(Hello::parse(&body[..body.len() - REPORT_SIZE]), None);
+        assert_eq!(hello().report_for(|id| id == 3), Some(128.0 / 255.0));
+        assert_eq!(hello().report_for(|id| id == 5), Some(0.0));
+    }
+
+    /// Neighbours appear on first contact, gain HELLO details and expire
+    #[test]
+    fn test_table_add_and_expire() {
+        let mut table = NeighborTable::default();
+        let now = Instant::now();
+        table.observe_frame(7, now, None);
+        table.observe_hello(7, &hello(), None, now);
+        table.observe_frame(9, now + INTERVAL, None);
+        assert_eq!(table.next_event(), Some(NeighborEvent::Added(7)));
+        assert_eq!(table.next_event(), Some(NeighborEvent::Added(9)));
+        assert_eq!(table.next_event(), None);
+
+        let neighbors = table.snapshot(now + INTERVAL);
+        assert_eq!(neighbors.iter().map(|n| n.id).collect::<Vec<_>>(), vec![7, 9]);
+        assert_eq!(neighbors[0].mac, Some([2, 0, 0, 0, 0, 7]));
+        assert_eq!(neighbors[0].position, Some(Position::new(1.0, 2.0, 3.0)));
+        assert_eq!(neighbors[1].link_quality, 0.0);
+
+        table.expire(now + Duration::from_millis(1050), Duration::from_secs(1));
+        assert_eq!(table.next_event(), Some(NeighborEvent::Removed(7)));
+        assert_eq!(table.snapshot(now).len(), 1);
+    }
+
+    /// Link quality is the share of announced HELLOs received; rates decay when idle
+    #[test]
+    fn test_link_quality_and_rate() {
+        let mut table = NeighborTable::default();
+        let start = Instant::now();
+        // Every other HELLO lost over eight intervals
+        for i in 0..QUALITY_WINDOW {
+            let at = start + INTERVAL * i;
+            table.observe_frame(7, at, None);
+            if i % 2 == 0 {
+                table.observe_hello(7, &hello(), None, at);
+            }
+        }
+        let now = start + INTERVAL * (QUALITY_WINDOW - 1);
+        let neighbor = table.snapshot(now)[0];
+        assert_eq!(neighbor.link_quality, 0.5);
+
+        // 8 frames in the first second, then silence
+        let rate = table.snapshot(start + RATE_WINDOW)[0].frame_rate;
+        assert_eq!(rate, 4.0);
+        assert_eq!(table.snapshot(start + RATE_WINDOW * 3)[0].frame_rate, 1.0);
+    }
+
+    /// Sequence gaps and reports give both delivery ratios, ETX and RSSI
+    #[test]
+    fn test_link_estimation() {
+        let mut table = NeighborTable::default();
+        let now = Instant::now();
+        // Every other HELLO lost; the neighbour hears half of ours
+        for sequence in (0..QUALITY_WINDOW as u16).step_by(2) {
+            table.observe_frame(7, now, Some(-60));
+            let hello = Hello { sequence, ..hello() };
+            table.observe_hello(7, &hello, hello.report_for(|id| id == 3), now);
+        }
+        let neighbor = table.snapshot(now)[0];
+        assert_eq!(neighbor.inbound_delivery, 4.0 / 7.0);
+        assert_eq!(neighbor.outbound_delivery, Some(128.0 / 255.0));
+        assert!((2.0..4.0).contains(&neighbor.etx.unwrap()));
+        assert_eq!(neighbor.rssi, Some(-60.0));
+        assert_eq!(table.reception_reports(), vec![(7, 146)]);
+
+        // A restarted sender starts a fresh count; duplicates are ignored
+        let restarted = Hello { sequence: 30_000, ..hello() };
+        table.observe_hello(7, &restarted, None, now);
+        table.observe_hello(7, &restarted, None, now);
+        assert_eq!(table.snapshot(now)[0].inbound_delivery, 1.0);
+        table.observe_frame(7, now, Some(-80));
+        assert_eq!(table.snapshot(now)[0].rssi, Some(-65.0));
+    }
+
+    /// Interface addresses parse from their sysfs form
+    #[test]
+    fn test_interface_mac() {
+        assert_eq!(interface_mac("lo"), Some([0; 6]));
+        assert_eq!(interface_mac("no-such-interface"), None);
+    }
+}
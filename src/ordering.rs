@@ -0,0 +1,280 @@
+//! # In-Order Delivery
+//!
+//! Optional per-sender ordering on top of sequence numbers.
+//!
+//! `OVP::emit_ordered` stamps frames with a message type and a sequence number
+//! counted per `(sender, message type)`. Receivers pick a policy per message
+//! type:
+//!
+//! - **Reorder** - frames ahead of a gap are held until the gap fills or a
+//!   timeout passes, then released in sequence order.
+//! - **Latest wins** - anything older than the newest frame seen is dropped,
+//!   so a stale position update never overrides a fresh one.
+//!
+//! A frame far behind the newest (more than `RESTART_WINDOW`) is taken as the
+//! sender restarting its counter and resets the stream.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::priority::Priority;
+use crate::{Authority, DroneId};
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Application-defined message class carried by ordered frames
+pub type MessageType = u16;
+
+/// Size of the ordering section in bytes
+pub(crate) const ORDERING_SECTION_SIZE: usize = 2;
+
+/// Frames held per stream before the oldest gap is skipped
+const MAX_HELD: usize = 64;
+
+/// Most `(sender, message type)` streams tracked at once
+const MAX_STREAMS: usize = 256;
+
+/// Distance behind the newest frame beyond which a sender is assumed restarted
+const RESTART_WINDOW: u32 = 1024;
+
+/// Receive-side ordering policy for one message type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OrderingPolicy {
+    /// Hold frames behind a gap until it fills or `timeout` passes
+    Reorder {
+        /// Longest a frame waits for the frames before it
+        timeout: Duration,
+    },
+
+    /// Drop frames older than the newest one delivered
+    LatestWins,
+}
+
+//==============================================================================
+// RECEIVER STATE
+//==============================================================================
+
+/// Verdict on an ordered frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Ordered {
+    /// Deliver now (then collect any frames it unblocked via `ready`)
+    Deliver,
+
+    /// Buffered behind a gap
+    Held,
+
+    /// Older than what was already delivered
+    Stale,
+}
+
+/// A frame waiting behind a gap
+#[derive(Debug, Clone)]
+struct HeldFrame {
+    /// Message payload
+    payload: Vec<u8>,
+
+    /// Provenance, delivered with the payload
+    authority: Authority,
+
+    /// Traffic class, for the receive queue
+    priority: Priority,
+
+    /// Arrival, for the reorder timeout
+    arrived: Instant,
+}
+
+/// Ordering state of one `(sender, message type)` stream
+#[derive(Debug, Clone)]
+struct Stream {
+    /// Policy fixed when the stream started
+    policy: OrderingPolicy,
+
+    /// Sequence number expected (reorder) or accepted (latest wins) next
+    next: u32,
+
+    /// Frames ahead of `next`, reorder policy only
+    held: HashMap<u32, HeldFrame>,
+
+    /// Last frame, for eviction
+    last_heard: Instant,
+}
+
+/// Messages released in order: payload, provenance and traffic class
+pub(crate) type Released = Vec<(Vec<u8>, Authority, Priority)>;
+
+/// Ordering state for every ordered stream in range
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OrderingBuffer {
+    /// Streams keyed by wire source and message type
+    streams: HashMap<(DroneId, MessageType), Stream>,
+}
+
+impl OrderingBuffer {
+    /// Classify an arriving frame, holding it if it is ahead of a gap
+    #[allow(clippy::too_many_arguments)]
+    pub fn accept(
+        &mut self,
+        source: DroneId,
+        message_type: MessageType,
+        sequence: u32,
+        policy: OrderingPolicy,
+        payload: &[u8],
+        authority: Authority,
+        priority: Priority,
+        now: Instant,
+    ) -> Ordered {
+        let key = (source, message_type);
+        if !self.streams.contains_key(&key) && self.streams.len() >= MAX_STREAMS {
+            let stalest = self.streams.iter().min_by_key(|(_, s)| s.last_heard).map(|(&k, _)| k);
+            if let Some(stalest) = stalest {
+                self.streams.remove(&stalest);
+            }
+        }
+        let stream = self.streams.entry(key).or_insert_with(|| Stream {
+            policy,
+            next: sequence,
+            held: HashMap::new(),
+            last_heard: now,
+        });
+        stream.last_heard = now;
+
+        let behind = stream.next.wrapping_sub(sequence);
+        if (1..=RESTART_WINDOW).contains(&behind) {
+            return Ordered::Stale;
+        }
+        if behind > RESTART_WINDOW && behind < u32::MAX / 2 {
+            // Far behind: the sender restarted its counter
+            stream.held.clear();
+            stream.next = sequence;
+        }
+
+        match stream.policy {
+            OrderingPolicy::LatestWins => {
+                stream.next = sequence.wrapping_add(1);
+                Ordered::Deliver
+            }
+            OrderingPolicy::Reorder { .. } if sequence == stream.next => {
+                stream.next = sequence.wrapping_add(1);
+                Ordered::Deliver
+            }
+            OrderingPolicy::Reorder { .. } => {
+                if stream.held.contains_key(&sequence) {
+                    return Ordered::Stale;
+                }
+                stream.held.insert(sequence, HeldFrame { payload: payload.to_vec(), authority, priority, arrived: now });
+                Ordered::Held
+            }
+        }
+    }
+
+    /// Frames of one stream that are now in sequence
+    ///
+    /// The second value counts gaps given up on along the way.
+    pub fn ready(&mut self, source: DroneId, message_type: MessageType, now: Instant) -> (Released, usize) {
+        let mut out = Vec::new();
+        let skipped = match self.streams.get_mut(&(source, message_type)) {
+            Some(stream) => drain(stream, now, &mut out),
+            None => 0,
+        };
+        (out, skipped)
+    }
+
+    /// Frames of every stream released by expired reorder timeouts
+    ///
+    /// The second value counts the gaps that timed out.
+    pub fn expire(&mut self, now: Instant) -> (Released, usize) {
+        let mut out = Vec::new();
+        let skipped = self.streams.values_mut().map(|stream| drain(stream, now, &mut out)).sum();
+        (out, skipped)
+    }
+}
+
+/// Release consecutive held frames, skipping gaps that timed out
+///
+/// Returns the number of gaps skipped.
+fn drain(stream: &mut Stream, now: Instant, out: &mut Released) -> usize {
+    let OrderingPolicy::Reorder { timeout } = stream.policy else {
+        return 0;
+    };
+    let mut skipped = 0;
+    loop {
+        if let Some(frame) = stream.held.remove(&stream.next) {
+            out.push((frame.payload, frame.authority, frame.priority));
+            stream.next = stream.next.wrapping_add(1);
+            continue;
+        }
+        let expired = stream.held.values().any(|frame| now.duration_since(frame.arrived) >= timeout);
+        if stream.held.is_empty() || !(expired || stream.held.len() > MAX_HELD) {
+            return skipped;
+        }
+        // Give up on the gap: jump to the oldest frame held
+        let next = stream.next;
+        stream.next = *stream.held.keys().min_by_key(|&&s| s.wrapping_sub(next)).unwrap();
+        skipped += 1;
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REORDER: OrderingPolicy = OrderingPolicy::Reorder { timeout: Duration::from_millis(50) };
+
+    fn accept(buffer: &mut OrderingBuffer, policy: OrderingPolicy, sequence: u32, now: Instant) -> Ordered {
+        buffer.accept(1, 7, sequence, policy, &sequence.to_le_bytes(), Authority::Unsigned, Priority::Normal, now)
+    }
+
+    fn payloads((released, _): (Released, usize)) -> Vec<u32> {
+        released.iter().map(|(p, _, _)| u32::from_le_bytes(p[..4].try_into().unwrap())).collect()
+    }
+
+    /// Frames behind a gap wait for it to fill, then come out in order
+    #[test]
+    fn test_reorder_fills_gap() {
+        let mut buffer = OrderingBuffer::default();
+        let now = Instant::now();
+
+        assert_eq!(accept(&mut buffer, REORDER, 10, now), Ordered::Deliver);
+        assert_eq!(accept(&mut buffer, REORDER, 12, now), Ordered::Held);
+        assert_eq!(accept(&mut buffer, REORDER, 13, now), Ordered::Held);
+        assert!(buffer.ready(1, 7, now).0.is_empty());
+        assert_eq!(accept(&mut buffer, REORDER, 11, now), Ordered::Deliver);
+        assert_eq!(payloads(buffer.ready(1, 7, now)), vec![12, 13]);
+        assert_eq!(accept(&mut buffer, REORDER, 12, now), Ordered::Stale);
+    }
+
+    /// A gap that never fills is skipped after the timeout
+    #[test]
+    fn test_reorder_timeout_skips_gap() {
+        let mut buffer = OrderingBuffer::default();
+        let now = Instant::now();
+
+        accept(&mut buffer, REORDER, 10, now);
+        accept(&mut buffer, REORDER, 13, now);
+        accept(&mut buffer, REORDER, 12, now);
+        assert!(buffer.expire(now + Duration::from_millis(10)).0.is_empty());
+        let (released, skipped) = buffer.expire(now + Duration::from_millis(50));
+        assert_eq!((payloads((released, 0)), skipped), (vec![12, 13], 1));
+        assert_eq!(accept(&mut buffer, REORDER, 11, now), Ordered::Stale);
+    }
+
+    /// Latest wins drops anything older; a far jump back is a restart
+    #[test]
+    fn test_latest_wins() {
+        let mut buffer = OrderingBuffer::default();
+        let now = Instant::now();
+
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 5, now), Ordered::Deliver);
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 9, now), Ordered::Deliver);
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 7, now), Ordered::Stale);
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 9, now), Ordered::Stale);
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 9u32.wrapping_sub(5000), now), Ordered::Deliver);
+        assert_eq!(accept(&mut buffer, OrderingPolicy::LatestWins, 9, now), Ordered::Deliver);
+    }
+}
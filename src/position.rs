@@ -0,0 +1,79 @@
+//! # Positions
+//!
+//! Drone positions as the protocol sees them: metres in a local east-north-up
+//! frame the swarm agrees on (e.g. centred on the launch point). The
+//! application reports its own position with `OVP::set_position`; frames can
+//! carry the position of the drone that transmitted them:
+//!
+//! ```text
+//! [x:4][y:4][z:4]   (little-endian f32, NaN = unknown)
+//! ```
+
+//==============================================================================
+// POSITION
+//==============================================================================
+
+/// Size of the position section in bytes
+pub(crate) const POSITION_SECTION_SIZE: usize = 12;
+
+/// Point in the swarm's local frame, in metres
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Position {
+    /// East
+    pub x: f32,
+
+    /// North
+    pub y: f32,
+
+    /// Up
+    pub z: f32,
+}
+
+impl Position {
+    /// Point at `(x, y, z)`
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Position { x, y, z }
+    }
+
+    /// Straight-line distance to `other` in metres
+    pub fn distance(&self, other: &Position) -> f32 {
+        let (dx, dy, dz) = (self.x - other.x, self.y - other.y, self.z - other.z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    /// Encode an optional position as a position section
+    pub(crate) fn to_wire(position: Option<Position>) -> [u8; POSITION_SECTION_SIZE] {
+        let Position { x, y, z } = position.unwrap_or(Position::new(f32::NAN, f32::NAN, f32::NAN));
+        let mut bytes = [0u8; POSITION_SECTION_SIZE];
+        bytes[0..4].copy_from_slice(&x.to_le_bytes());
+        bytes[4..8].copy_from_slice(&y.to_le_bytes());
+        bytes[8..12].copy_from_slice(&z.to_le_bytes());
+        bytes
+    }
+
+    /// Decode a position section (None if unknown or not finite)
+    pub(crate) fn from_wire(bytes: &[u8]) -> Option<Position> {
+        let coordinate = |at: usize| f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let position = Position::new(coordinate(0), coordinate(4), coordinate(8));
+        [position.x, position.y, position.z].iter().all(|c| c.is_finite()).then_some(position)
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Known positions survive the wire; unknown and garbage ones decode as None
+    #[test]
+    fn test_position_wire_roundtrip() {
+        let position = Position::new(3.0, -4.0, 12.5);
+        assert_eq!(Position::from_wire(&Position::to_wire(Some(position))), Some(position));
+        assert_eq!(Position::from_wire(&Position::to_wire(None)), None);
+        assert_eq!(Position::from_wire(&[0xFF; POSITION_SECTION_SIZE]), None);
+        assert_eq!(position.distance(&Position::new(0.0, 0.0, 12.5)), 5.0);
+    }
+}
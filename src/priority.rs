@@ -0,0 +1,152 @@
+//! # Priority Classes
+//!
+//! Four traffic classes so an emergency stop never waits behind telemetry.
+//!
+//! Frames above or below `Normal` carry a one-byte priority section. On
+//! transmit each class maps to a Linux skb priority (`SO_PRIORITY`), which
+//! qdiscs and 802.11 WMM access categories honour. Frames the socket cannot
+//! take right away wait in a per-class queue that is drained strictly highest
+//! class first. On receive, when several frames are pending, higher classes
+//! are handed to the application first.
+
+use std::collections::VecDeque;
+
+//==============================================================================
+// PRIORITY CLASSES
+//==============================================================================
+
+/// Size of the priority section in bytes
+pub(crate) const PRIORITY_SECTION_SIZE: usize = 1;
+
+/// Number of priority classes
+const LEVELS: usize = 4;
+
+/// Traffic class of a frame, lowest first
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Bulk transfers that may wait (logs, map tiles)
+    Bulk = 0,
+
+    /// Regular traffic - the class of every frame without a priority section
+    #[default]
+    Normal = 1,
+
+    /// Latency-sensitive coordination
+    High = 2,
+
+    /// Safety-critical commands (emergency stop, return-to-home)
+    Emergency = 3,
+}
+
+impl Priority {
+    /// Decode the priority section
+    pub(crate) fn from_wire(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Priority::Bulk),
+            1 => Some(Priority::Normal),
+            2 => Some(Priority::High),
+            3 => Some(Priority::Emergency),
+            _ => None,
+        }
+    }
+
+    /// Linux skb priority for this class (TC_PRIO_* values)
+    pub(crate) fn socket_priority(self) -> i32 {
+        match self {
+            Priority::Bulk => 2,      // TC_PRIO_BULK
+            Priority::Normal => 0,    // TC_PRIO_BESTEFFORT
+            Priority::High => 6,      // TC_PRIO_INTERACTIVE
+            Priority::Emergency => 7, // TC_PRIO_CONTROL
+        }
+    }
+}
+
+//==============================================================================
+// PRIORITY QUEUE
+//==============================================================================
+
+/// One FIFO per class, served strictly highest class first
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityQueue<T> {
+    /// FIFOs indexed by `Priority as usize`
+    queues: [VecDeque<T>; LEVELS],
+}
+
+impl<T> Default for PriorityQueue<T> {
+    fn default() -> Self {
+        PriorityQueue { queues: Default::default() }
+    }
+}
+
+impl<T> PriorityQueue<T> {
+    /// Append an item to its class, keeping at most `capacity` items
+    ///
+    /// When full, the oldest item of the lowest class present makes room -
+    /// unless that class is above `priority`, in which case the new item is
+    /// refused. Returns the number of items dropped (0 or 1).
+    pub fn push_bounded(&mut self, priority: Priority, item: T, capacity: usize) -> usize {
+        if self.len() < capacity {
+            self.queues[priority as usize].push_back(item);
+            return 0;
+        }
+        match self.queues.iter_mut().take(priority as usize + 1).find(|queue| !queue.is_empty()) {
+            Some(lowest) => {
+                lowest.pop_front();
+                self.queues[priority as usize].push_back(item);
+            }
+            None => drop(item),
+        }
+        1
+    }
+
+    /// Put an item back at the head of its class
+    pub fn push_front(&mut self, priority: Priority, item: T) {
+        self.queues[priority as usize].push_front(item);
+    }
+
+    /// Oldest item of the highest non-empty class
+    pub fn pop(&mut self) -> Option<(Priority, T)> {
+        let level = self.queues.iter().rposition(|queue| !queue.is_empty())?;
+        let priority = Priority::from_wire(level as u8)?;
+        self.queues[level].pop_front().map(|item| (priority, item))
+    }
+
+    /// Items queued across all classes
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// True if no class holds an item
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Higher classes leave first, FIFO within a class; overflow drops the lowest
+    #[test]
+    fn test_strict_priority_queue() {
+        let mut queue = PriorityQueue::default();
+        assert_eq!(queue.push_bounded(Priority::Normal, "telemetry-1", 3), 0);
+        assert_eq!(queue.push_bounded(Priority::Bulk, "log", 3), 0);
+        assert_eq!(queue.push_bounded(Priority::Normal, "telemetry-2", 3), 0);
+
+        // Full: the bulk frame makes room for the emergency stop
+        assert_eq!(queue.push_bounded(Priority::Emergency, "stop", 3), 1);
+        // Full of normal traffic and above: a bulk frame is refused
+        assert_eq!(queue.push_bounded(Priority::Bulk, "log-2", 3), 1);
+
+        assert_eq!(queue.pop(), Some((Priority::Emergency, "stop")));
+        queue.push_front(Priority::Normal, "retry");
+        let rest: Vec<_> = std::iter::from_fn(|| queue.pop()).map(|(_, item)| item).collect();
+        assert_eq!(rest, vec!["retry", "telemetry-1", "telemetry-2"]);
+        assert!(queue.is_empty());
+    }
+}
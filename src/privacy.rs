@@ -0,0 +1,159 @@
+//! # Ghost Mode Pseudonyms
+//!
+//! Rotating pseudonymous identifiers for the target list and origin section.
+//!
+//! Plain frames carry raw 64-bit drone IDs, so a passive listener can map out
+//! exactly who talks to whom. In privacy mode every ID on the wire is replaced
+//! by a per-epoch pseudonym:
+//!
+//! ```text
+//! pseudonym(id, epoch) = HMAC-SHA256(swarm_secret, "ovp-pseudonym" ‖ id ‖ epoch)[..8]
+//! ```
+//!
+//! Only holders of the swarm secret can compute (or recognise) a pseudonym, and
+//! the mapping changes every epoch so observations cannot be linked over time.
+//! Receivers match their own pseudonym in constant time.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac_sha256::HMAC;
+
+use crate::DroneId;
+
+//==============================================================================
+// PSEUDONYM SCHEME
+//==============================================================================
+
+/// Swarm-wide pseudonym derivation parameters
+///
+/// All drones must share the same secret and epoch length, and keep their
+/// clocks within one epoch of each other (GPS time is more than sufficient).
+#[derive(Clone)]
+pub struct PseudonymScheme {
+    /// Secret shared by every swarm member
+    secret: [u8; 32],
+
+    /// Epoch length in seconds (minimum 1)
+    epoch_secs: u64,
+}
+
+impl PseudonymScheme {
+    /// Create a scheme rotating pseudonyms every `epoch_length`
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - Swarm secret used to derive pseudonyms
+    /// * `epoch_length` - How long a pseudonym stays valid (rounded down to whole seconds)
+    pub fn new(secret: [u8; 32], epoch_length: Duration) -> Self {
+        PseudonymScheme { secret, epoch_secs: epoch_length.as_secs().max(1) }
+    }
+
+    /// Epoch number for the current wall-clock time
+    pub fn current_epoch(&self) -> u32 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        (now.as_secs() / self.epoch_secs) as u32
+    }
+
+    /// Pseudonym of `id` during `epoch`
+    pub fn pseudonym(&self, id: DroneId, epoch: u32) -> DroneId {
+        let mut mac = HMAC::new(self.secret);
+        mac.update(b"ovp-pseudonym");
+        mac.update(id.to_le_bytes());
+        mac.update(epoch.to_le_bytes());
+        DroneId::from_le_bytes(mac.finalize()[..8].try_into().unwrap())
+    }
+
+    /// Check that a received epoch is within one epoch of ours
+    ///
+    /// Tolerates clock skew across an epoch boundary while refusing frames
+    /// replayed from long ago.
+    pub(crate) fn is_fresh(&self, epoch: u32) -> bool {
+        self.current_epoch().abs_diff(epoch) <= 1
+    }
+
+    /// Pseudonyms of `targets` for `epoch`, to be encoded in their place
+    ///
+    /// Computed before encoding since Bloom filters hash the IDs on the wire.
+    pub(crate) fn pseudonymize_targets(&self, targets: &[DroneId], epoch: u32) -> Vec<DroneId> {
+        targets.iter().map(|&id| self.pseudonym(id, epoch)).collect()
+    }
+}
+
+//==============================================================================
+// PSEUDONYM CACHE
+//==============================================================================
+
+/// Recently used pseudonyms of the local drone
+///
+/// Receivers see frames from the previous, current and next epoch; caching
+/// one entry per epoch keeps HMAC work off the per-frame path.
+#[derive(Clone, Default)]
+pub(crate) struct PseudonymCache {
+    /// `(epoch, pseudonym)` slots, replaced round-robin
+    entries: [Option<(u32, DroneId)>; 3],
+
+    /// Next slot to overwrite
+    next: usize,
+}
+
+impl PseudonymCache {
+    /// Pseudonym of `id` for `epoch`, computed at most once per epoch
+    pub fn get(&mut self, scheme: &PseudonymScheme, id: DroneId, epoch: u32) -> DroneId {
+        if let Some((_, pseudonym)) = self.entries.iter().flatten().find(|(e, _)| *e == epoch) {
+            return *pseudonym;
+        }
+        let pseudonym = scheme.pseudonym(id, epoch);
+        self.entries[self.next] = Some((epoch, pseudonym));
+        self.next = (self.next + 1) % self.entries.len();
+        pseudonym
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{parse_frame, write_frame, FrameMeta, FLAG_PSEUDONYMOUS};
+
+    /// Pseudonyms are stable within an epoch and unlinkable across epochs
+    #[test]
+    fn test_pseudonyms_rotate_per_epoch() {
+        let scheme = PseudonymScheme::new([4; 32], Duration::from_secs(60));
+        let other = PseudonymScheme::new([5; 32], Duration::from_secs(60));
+
+        assert_eq!(scheme.pseudonym(42, 7), scheme.pseudonym(42, 7));
+        assert_ne!(scheme.pseudonym(42, 7), scheme.pseudonym(42, 8));
+        assert_ne!(scheme.pseudonym(42, 7), scheme.pseudonym(43, 7));
+        assert_ne!(scheme.pseudonym(42, 7), other.pseudonym(42, 7));
+        assert!(scheme.is_fresh(scheme.current_epoch() + 1));
+        assert!(!scheme.is_fresh(scheme.current_epoch() + 2));
+    }
+
+    /// Raw IDs never appear on the wire and receivers still match themselves
+    #[test]
+    fn test_pseudonymous_frame_matches_only_pseudonym() {
+        let scheme = PseudonymScheme::new([4; 32], Duration::from_secs(60));
+        let epoch = 1234;
+
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_PSEUDONYMOUS, epoch, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &scheme.pseudonymize_targets(&[42, 43], epoch), b"rv").unwrap();
+
+        let view = parse_frame(&buf[..len]).unwrap();
+        assert_eq!(view.epoch, Some(epoch));
+        assert!(!view.is_addressed_to(42));
+        assert!(view.is_addressed_to(scheme.pseudonym(42, epoch)));
+        assert!(view.is_addressed_to(scheme.pseudonym(43, epoch)));
+        assert!(!view.is_addressed_to(scheme.pseudonym(42, epoch + 1)));
+
+        let mine = scheme.pseudonym(43, epoch);
+        assert_eq!(crate::parse_ovp_frame_fast(&buf[..len], mine), Some(b"rv".as_slice()));
+
+        let mut cache = PseudonymCache::default();
+        assert_eq!(cache.get(&scheme, 43, epoch), mine);
+        assert_eq!(cache.get(&scheme, 43, epoch), mine);
+    }
+}
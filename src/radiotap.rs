@@ -0,0 +1,139 @@
+//! # Radiotap Metadata
+//!
+//! Wi-Fi interfaces in monitor mode hand raw sockets each frame with a
+//! radiotap header in front of the 802.11 header, reporting how the frame was
+//! received. The OVP frame follows the 802.11 header (after an LLC/SNAP header
+//! if the sender added one). Locating it lets such interfaces carry OVP
+//! unchanged, and the header's antenna signal field gives the received signal
+//! strength of each frame.
+//!
+//! Interfaces that deliver OVP frames directly - Ethernet, loopback, Wi-Fi in
+//! station or mesh mode - carry no such metadata, and RSSI stays unknown.
+
+use crate::OVP_MAGIC;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Bit of the `present` word marking an extended bitmap
+const PRESENT_EXTENDED: u32 = 1 << 31;
+
+/// Radiotap field holding the antenna signal in dBm
+const FIELD_ANTENNA_SIGNAL: usize = 5;
+
+/// Size and alignment of the fields up to the antenna signal, by bit
+const FIELD_LAYOUT: [(usize, usize); FIELD_ANTENNA_SIGNAL + 1] = [
+    (8, 8), // TSFT
+    (1, 1), // flags
+    (1, 1), // rate
+    (4, 2), // channel
+    (2, 1), // FHSS
+    (1, 1), // antenna signal
+];
+
+/// Size of an 802.11 data header with three addresses
+const DOT11_HEADER_SIZE: usize = 24;
+
+/// Size of an LLC/SNAP header
+const SNAP_HEADER_SIZE: usize = 8;
+
+//==============================================================================
+// PARSING
+//==============================================================================
+
+/// Offset of the OVP frame inside a radiotap capture and its signal strength
+///
+/// None if `buf` is not a radiotap capture of an 802.11 data frame carrying
+/// an OVP frame (in particular when it already starts with one).
+pub(crate) fn locate_frame(buf: &[u8]) -> Option<(usize, Option<i8>)> {
+    if buf.len() < 8 || buf[0] != 0 || buf.starts_with(&OVP_MAGIC.to_le_bytes()) {
+        return None;
+    }
+    let header_len = u16::from_le_bytes([buf[2], buf[3]]) as usize;
+    if header_len < 8 || header_len > buf.len() {
+        return None;
+    }
+    let word = |at: usize| buf.get(at..at + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+
+    // Fields start after the last present word
+    let present = word(4)?;
+    let mut offset = 8;
+    let mut last = present;
+    while last & PRESENT_EXTENDED != 0 {
+        last = word(offset)?;
+        offset += 4;
+    }
+    let mut rssi = None;
+    for (bit, &(size, align)) in FIELD_LAYOUT.iter().enumerate() {
+        if present & (1 << bit) == 0 {
+            continue;
+        }
+        offset = offset.next_multiple_of(align);
+        if offset + size > header_len {
+            return None;
+        }
+        if bit == FIELD_ANTENNA_SIGNAL {
+            rssi = Some(buf[offset] as i8);
+        }
+        offset += size;
+    }
+
+    // 802.11 data header: a fourth address between access points, QoS control on QoS subtypes
+    let dot11 = buf.get(header_len..header_len + DOT11_HEADER_SIZE)?;
+    let (control, direction) = (dot11[0], dot11[1]);
+    if (control >> 2) & 0b11 != 0b10 {
+        return None;
+    }
+    let mut start = header_len + DOT11_HEADER_SIZE;
+    if direction & 0b11 == 0b11 {
+        start += 6;
+    }
+    if control & 0x80 != 0 {
+        start += 2;
+    }
+    [start, start + SNAP_HEADER_SIZE].into_iter()
+        .find(|&at| buf.get(at..at + 4) == Some(&OVP_MAGIC.to_le_bytes()[..]))
+        .map(|at| (at, rssi))
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Radiotap header with TSFT, flags and antenna signal, then a QoS data header
+    fn capture(rssi: i8, snap: bool) -> Vec<u8> {
+        let mut buf = vec![0, 0, 0, 0];
+        buf.extend_from_slice(&0b10_0011u32.to_le_bytes());
+        buf.extend_from_slice(&[0; 8]);         // TSFT
+        buf.push(0);                            // flags
+        buf.push(rssi as u8);                   // antenna signal
+        let len = buf.len() as u16;
+        buf[2..4].copy_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&[0x88, 0x00]);   // QoS data, no DS bits
+        buf.extend_from_slice(&[0; 22]);
+        buf.extend_from_slice(&[0; 2]);         // QoS control
+        if snap {
+            buf.extend_from_slice(&[0xAA, 0xAA, 0x03, 0, 0, 0, 0x88, 0xB5]);
+        }
+        buf.extend_from_slice(&OVP_MAGIC.to_le_bytes());
+        buf
+    }
+
+    /// The OVP frame is found behind the headers together with the signal strength
+    #[test]
+    fn test_locate_frame() {
+        let plain = capture(-52, false);
+        assert_eq!(locate_frame(&plain), Some((plain.len() - 4, Some(-52))));
+        let snap = capture(-71, true);
+        assert_eq!(locate_frame(&snap), Some((snap.len() - 4, Some(-71))));
+
+        assert_eq!(locate_frame(&OVP_MAGIC.to_le_bytes().repeat(4)), None);
+        assert_eq!(locate_frame(&plain[..plain.len() - 1]), None);
+        assert_eq!(locate_frame(&[0; 64]), None);
+    }
+}
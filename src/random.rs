@@ -0,0 +1,57 @@
+//! # Entropy Source
+//!
+//! Nonces and keys are drawn from the kernel CSPRNG via `/dev/urandom`, keeping
+//! the crate free of RNG dependencies. Protocol timers that only need to be
+//! decorrelated between drones use a cheap seeded generator instead.
+
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+/// Fill `buf` with cryptographically secure random bytes
+pub(crate) fn fill_random(buf: &mut [u8]) -> Result<(), Box<dyn std::error::Error>> {
+    File::open("/dev/urandom")?.read_exact(buf)?;
+    Ok(())
+}
+
+/// Non-cryptographic xorshift64* generator for timer jitter
+///
+/// Never use for keys or nonces - only to spread out retransmissions so
+/// neighbouring drones do not answer in lockstep.
+#[derive(Debug, Clone)]
+pub(crate) struct Jitter(u64);
+
+impl Jitter {
+    /// Seed from the kernel CSPRNG (falls back to a fixed odd seed)
+    pub fn new() -> Self {
+        let mut seed = [0u8; 8];
+        let seed = match fill_random(&mut seed) {
+            Ok(()) => u64::from_le_bytes(seed) | 1,
+            Err(_) => 0x9E37_79B9_7F4A_7C15,
+        };
+        Jitter(seed)
+    }
+
+    /// Next raw 64-bit value
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform duration in `[0, max)`
+    pub fn below(&mut self, max: Duration) -> Duration {
+        let nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+        if nanos == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.next_u64() % nanos)
+    }
+
+    /// True with probability `probability` (values outside `[0, 1]` act as the nearest bound)
+    pub fn chance(&mut self, probability: f64) -> bool {
+        // 53 random bits give a uniform value in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
@@ -0,0 +1,242 @@
+//! # Transmit Rate Limiting
+//!
+//! Token buckets that keep one drone from monopolising the shared channel.
+//!
+//! Each bucket refills at a frames-per-second and a bytes-per-second rate
+//! and holds at most `burst` worth of either. A node-wide bucket covers every
+//! frame; per-class buckets cover one priority class each. A frame leaves
+//! only when every bucket that applies can pay for it. Protocol control
+//! frames (ACKs, NACKs, enrollment) are exempt so rate limits never break
+//! the protocol itself.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::priority::Priority;
+use crate::MAX_FRAME_SIZE;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Refill rates and depth of one token bucket
+#[derive(Debug, Copy, Clone)]
+pub struct RateLimit {
+    /// Sustained frames per second (0 = unlimited)
+    pub frames_per_sec: u32,
+
+    /// Sustained bytes per second (0 = unlimited)
+    pub bytes_per_sec: u32,
+
+    /// Budget that may build up while idle, as time at the sustained rate
+    pub burst: Duration,
+}
+
+impl RateLimit {
+    /// Limit with a 100 ms burst allowance
+    pub fn new(frames_per_sec: u32, bytes_per_sec: u32) -> Self {
+        RateLimit { frames_per_sec, bytes_per_sec, burst: Duration::from_millis(100) }
+    }
+}
+
+/// What an emission does when it would exceed its budget
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ThrottleMode {
+    /// Wait until the budget allows the frame
+    #[default]
+    Block,
+
+    /// Discard the frame and report success
+    Drop,
+
+    /// Fail with a [`WouldExceedBudget`] error
+    Reject,
+}
+
+/// Transmit budgets of a node
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Budget shared by every frame this node sends
+    pub node: Option<RateLimit>,
+
+    /// Additional budget per priority class
+    pub classes: HashMap<Priority, RateLimit>,
+
+    /// Behaviour once a budget is exhausted
+    pub mode: ThrottleMode,
+}
+
+/// Error returned in `ThrottleMode::Reject` when a frame is over budget
+///
+/// Emission methods return it boxed; recover it with `downcast_ref`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WouldExceedBudget {
+    /// Time until the budget allows the frame
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for WouldExceedBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Emission would exceed transmit budget - retry after {:?}", self.retry_after)
+    }
+}
+
+impl std::error::Error for WouldExceedBudget {}
+
+//==============================================================================
+// TOKEN BUCKETS
+//==============================================================================
+
+/// Frame and byte tokens of one budget
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    /// Rates and depth
+    limit: RateLimit,
+
+    /// Frame tokens available
+    frames: f64,
+
+    /// Byte tokens available
+    bytes: f64,
+
+    /// Last refill
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    /// Full bucket
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        let mut bucket = TokenBucket { limit, frames: 0.0, bytes: 0.0, refilled: now };
+        bucket.frames = bucket.frame_capacity();
+        bucket.bytes = bucket.byte_capacity();
+        bucket
+    }
+
+    /// Most frame tokens held (always room for one frame)
+    fn frame_capacity(&self) -> f64 {
+        (self.limit.frames_per_sec as f64 * self.limit.burst.as_secs_f64()).max(1.0)
+    }
+
+    /// Most byte tokens held (always room for one full frame)
+    fn byte_capacity(&self) -> f64 {
+        (self.limit.bytes_per_sec as f64 * self.limit.burst.as_secs_f64()).max(MAX_FRAME_SIZE as f64)
+    }
+
+    /// Add the tokens earned since the last refill
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.refilled = now;
+        self.frames = (self.frames + self.limit.frames_per_sec as f64 * elapsed).min(self.frame_capacity());
+        self.bytes = (self.bytes + self.limit.bytes_per_sec as f64 * elapsed).min(self.byte_capacity());
+    }
+
+    /// Time until a frame of `bytes` can be paid for (zero if it can now)
+    fn wait(&self, bytes: usize) -> Duration {
+        // Tolerance so waiting exactly the returned time always suffices
+        let deficit = |tokens: f64, cost: f64, rate: u32| {
+            if rate == 0 || tokens + 1e-6 >= cost { 0.0 } else { (cost - tokens) / rate as f64 }
+        };
+        let frames = deficit(self.frames, 1.0, self.limit.frames_per_sec);
+        let bytes = deficit(self.bytes, bytes as f64, self.limit.bytes_per_sec);
+        Duration::from_nanos((frames.max(bytes) * 1e9).ceil() as u64)
+    }
+
+    /// Pay for a frame of `bytes`
+    fn spend(&mut self, bytes: usize) {
+        if self.limit.frames_per_sec > 0 {
+            self.frames -= 1.0;
+        }
+        if self.limit.bytes_per_sec > 0 {
+            self.bytes -= bytes as f64;
+        }
+    }
+}
+
+/// Node-wide and per-class buckets
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    /// Budget of every frame
+    node: Option<TokenBucket>,
+
+    /// Budget per priority class
+    classes: HashMap<Priority, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Full buckets for `config`
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let now = Instant::now();
+        RateLimiter {
+            node: config.node.map(|limit| TokenBucket::new(limit, now)),
+            classes: config.classes.iter().map(|(&class, &limit)| (class, TokenBucket::new(limit, now))).collect(),
+        }
+    }
+
+    /// True if no budget is configured
+    pub fn is_unlimited(&self) -> bool {
+        self.node.is_none() && self.classes.is_empty()
+    }
+
+    /// Pay for a frame if every applicable budget allows it
+    ///
+    /// Returns `Err` with the time until it would be allowed otherwise;
+    /// nothing is spent then.
+    pub fn try_spend(&mut self, priority: Priority, bytes: usize, now: Instant) -> Result<(), Duration> {
+        let mut wait = Duration::ZERO;
+        for bucket in self.node.iter_mut().chain(self.classes.get_mut(&priority)) {
+            bucket.refill(now);
+            wait = wait.max(bucket.wait(bytes));
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        for bucket in self.node.iter_mut().chain(self.classes.get_mut(&priority)) {
+            bucket.spend(bytes);
+        }
+        Ok(())
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A burst is allowed up to the bucket depth, then frames wait for refill
+    #[test]
+    fn test_frame_budget() {
+        let config = RateLimitConfig { node: Some(RateLimit::new(100, 0)), ..RateLimitConfig::default() };
+        let mut limiter = RateLimiter::new(&config);
+        let now = Instant::now();
+
+        // 100 frames/s with a 100 ms burst: 10 frames back to back
+        for _ in 0..10 {
+            assert_eq!(limiter.try_spend(Priority::Normal, 64, now), Ok(()));
+        }
+        let wait = limiter.try_spend(Priority::Normal, 64, now).unwrap_err();
+        assert!(wait > Duration::from_millis(9) && wait <= Duration::from_millis(10));
+        assert_eq!(limiter.try_spend(Priority::Normal, 64, now + wait), Ok(()));
+    }
+
+    /// Class budgets apply to their class only, on top of the node budget
+    #[test]
+    fn test_class_and_byte_budgets() {
+        let config = RateLimitConfig {
+            classes: HashMap::from([(Priority::Bulk, RateLimit::new(0, 10_000))]),
+            ..RateLimitConfig::default()
+        };
+        let mut limiter = RateLimiter::new(&config);
+        let now = Instant::now();
+
+        // 10 kB/s with a 100 ms burst still admits one full frame
+        assert_eq!(limiter.try_spend(Priority::Bulk, 1500, now), Ok(()));
+        let wait = limiter.try_spend(Priority::Bulk, 100, now).unwrap_err();
+        assert!(wait > Duration::from_millis(9) && wait <= Duration::from_millis(10));
+        assert_eq!(limiter.try_spend(Priority::Emergency, 1500, now), Ok(()));
+        assert!(!limiter.is_unlimited());
+    }
+}
@@ -0,0 +1,291 @@
+//! # Multi-Hop Relaying
+//!
+//! Controlled flooding so frames reach drones beyond the sender's radio range.
+//!
+//! Frames sent with `OVP::emit_multihop` carry a hop section:
+//!
+//! ```text
+//! [ttl:1][hops:1]
+//! ```
+//!
+//! `ttl` is the number of further relays allowed and `hops` the number made so
+//! far. A node with relaying enabled rebroadcasts every multi-hop frame it has
+//! not seen before - addressed to it or not - with `ttl` decremented and `hops`
+//! incremented, after a random delay so neighbours that heard the same frame
+//! do not all transmit at once. Every node remembers the multi-hop frames it
+//! has seen (hop section zeroed), so each copy is relayed and delivered once.
+//!
+//! In a dense swarm plain flooding has every drone rebroadcast every frame.
+//! A [`RelayPolicy`] cuts the redundant rebroadcasts:
+//!
+//! - **Gossip** - relay with probability `p`.
+//! - **Counter** - give up if `k` copies were heard while the relay waited
+//!   out its jitter delay; the neighbourhood is covered already.
+//! - **Distance** - give up if any copy came from a transmitter closer than
+//!   a minimum distance; a rebroadcast from here would add little coverage.
+//!   Multi-hop frames carry their transmitter's position when it is known
+//!   (see `OVP::set_position`).
+//!
+//! Relays rewrite the hop and position sections, so the membership tag and
+//! signature cover them zeroed; the CRC-32C trailer covers them as sent and
+//! is recomputed by each relay.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::checksum::{append_checksum, CHECKSUM_SIZE};
+use crate::frame::{self, FLAG_CHECKSUM, FLAG_POSITION};
+use crate::position::Position;
+use crate::priority::Priority;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of the hop section in bytes
+pub(crate) const HOP_SECTION_SIZE: usize = 2;
+
+/// Most relays waiting for their jitter delay (oldest dropped first)
+const MAX_PENDING: usize = 256;
+
+/// Rule deciding whether a frame heard for the first time is rebroadcast
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum RelayPolicy {
+    /// Rebroadcast every new frame
+    #[default]
+    Flood,
+
+    /// Rebroadcast with the given probability
+    Gossip {
+        /// Chance of relaying, from 0 to 1
+        probability: f64,
+    },
+
+    /// Give up once this many copies (the first included) were heard
+    /// during the jitter delay
+    Counter {
+        /// Copies that make a rebroadcast redundant
+        max_copies: u32,
+    },
+
+    /// Give up if a copy came from a transmitter closer than this, in metres
+    /// (frames without positions, or while our own is unknown, are relayed)
+    Distance {
+        /// Least distance to every transmitter worth a rebroadcast
+        min_distance: f32,
+    },
+}
+
+/// Relay behaviour of a node
+#[derive(Debug, Copy, Clone)]
+pub struct RelayConfig {
+    /// Longest random delay before rebroadcasting a frame
+    pub max_jitter: Duration,
+
+    /// Suppression of redundant rebroadcasts
+    pub policy: RelayPolicy,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        RelayConfig { max_jitter: Duration::from_millis(10), policy: RelayPolicy::Flood }
+    }
+}
+
+/// Hop section fields carried by multi-hop frames
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct HopCount {
+    /// Further relays allowed
+    pub ttl: u8,
+
+    /// Relays made so far
+    pub hops: u8,
+}
+
+impl HopCount {
+    /// Decode the hop section
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        HopCount { ttl: bytes[0], hops: bytes[1] }
+    }
+
+    /// Encode the hop section
+    pub fn to_bytes(self) -> [u8; HOP_SECTION_SIZE] {
+        [self.ttl, self.hops]
+    }
+
+    /// Hop section after one more relay (None once the TTL is spent)
+    pub fn next(self) -> Option<Self> {
+        Some(HopCount { ttl: self.ttl.checked_sub(1)?, hops: self.hops.saturating_add(1) })
+    }
+}
+
+//==============================================================================
+// RELAY QUEUE
+//==============================================================================
+
+/// Copy of a received frame ready to go out again
+///
+/// `frame` holds the frame with its relay sections zeroed and any padding
+/// cut off; the new hop section and our position are written and the
+/// checksum recomputed.
+pub(crate) fn forward(frame: &[u8], flags: u16, hop: HopCount, position: Option<Position>) -> Vec<u8> {
+    let mut out = frame.to_vec();
+    frame::write_hop_count(&mut out, flags, hop);
+    if flags & FLAG_POSITION != 0 {
+        frame::write_position(&mut out, flags, position);
+    }
+    if flags & FLAG_CHECKSUM != 0 {
+        let covered = out.len() - CHECKSUM_SIZE;
+        append_checksum(&mut out, covered);
+    }
+    out
+}
+
+/// A rebroadcast waiting for its jitter delay
+#[derive(Debug, Clone)]
+struct PendingRelay {
+    /// Earliest time to send
+    due: Instant,
+
+    /// Multi-hop cache key shared by every copy of the frame
+    key: u64,
+
+    /// Copies heard so far, the first included
+    copies: u32,
+
+    /// Distance to the nearest transmitter of a copy, where known
+    nearest: Option<f32>,
+
+    /// Frame as it goes out
+    frame: Vec<u8>,
+
+    /// Traffic class of the original frame
+    priority: Priority,
+}
+
+/// Rebroadcasts scheduled but not sent yet
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelayQueue {
+    /// Pending relays in scheduling order
+    pending: VecDeque<PendingRelay>,
+}
+
+impl RelayQueue {
+    /// Schedule `frame` for `due`, returning the number of relays dropped (0 or 1)
+    ///
+    /// `distance` is how far the transmitter of the first copy was, if known.
+    pub fn schedule(&mut self, key: u64, frame: Vec<u8>, priority: Priority, due: Instant, distance: Option<f32>) -> usize {
+        let dropped = if self.pending.len() >= MAX_PENDING {
+            self.pending.pop_front();
+            1
+        } else {
+            0
+        };
+        self.pending.push_back(PendingRelay { due, key, copies: 1, nearest: distance, frame, priority });
+        dropped
+    }
+
+    /// Note another copy of a frame that may be waiting to be relayed
+    pub fn heard_again(&mut self, key: u64, distance: Option<f32>) {
+        if let Some(relay) = self.pending.iter_mut().find(|relay| relay.key == key) {
+            relay.copies = relay.copies.saturating_add(1);
+            relay.nearest = match (relay.nearest, distance) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+
+    /// Remove every relay due at `now`, returning those `policy` still wants sent
+    ///
+    /// The second value counts the relays suppressed as redundant.
+    pub fn due(&mut self, now: Instant, policy: RelayPolicy) -> (Vec<(Vec<u8>, Priority)>, usize) {
+        let mut out = Vec::new();
+        let mut suppressed = 0;
+        self.pending.retain_mut(|relay| {
+            if relay.due > now {
+                return true;
+            }
+            let redundant = match policy {
+                RelayPolicy::Counter { max_copies } => relay.copies >= max_copies,
+                RelayPolicy::Distance { min_distance } => relay.nearest.is_some_and(|d| d < min_distance),
+                RelayPolicy::Flood | RelayPolicy::Gossip { .. } => false,
+            };
+            if redundant {
+                suppressed += 1;
+            } else {
+                out.push((std::mem::take(&mut relay.frame), relay.priority));
+            }
+            false
+        });
+        (out, suppressed)
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{parse_frame, write_frame, FrameMeta, FLAG_MULTIHOP};
+
+    /// A forwarded copy spends one TTL, counts one hop and keeps a valid CRC
+    #[test]
+    fn test_forward_rewrites_hop_section() {
+        let mut buf = [0u8; 128];
+        let meta = FrameMeta { flags: FLAG_MULTIHOP | FLAG_CHECKSUM, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, &[42], b"far away").unwrap();
+        let len = append_checksum(&mut buf, len);
+
+        let hop = HopCount { ttl: 3, hops: 0 }.next().unwrap();
+        let relayed = forward(&buf[..len], meta.flags, hop, None);
+        let view = parse_frame(&relayed).unwrap();
+        assert!(view.checksum_ok);
+        assert_eq!(view.hop, Some(HopCount { ttl: 2, hops: 1 }));
+        assert_eq!(view.payload, b"far away");
+        assert_eq!(HopCount { ttl: 0, hops: 5 }.next(), None);
+    }
+
+    /// Relays leave after their delay; the queue stays bounded
+    #[test]
+    fn test_relay_queue() {
+        let mut queue = RelayQueue::default();
+        let now = Instant::now();
+        assert_eq!(queue.schedule(1, b"late".to_vec(), Priority::Normal, now + Duration::from_millis(5), None), 0);
+        assert_eq!(queue.schedule(2, b"soon".to_vec(), Priority::High, now, None), 0);
+        assert_eq!(queue.due(now, RelayPolicy::Flood), (vec![(b"soon".to_vec(), Priority::High)], 0));
+        assert_eq!(queue.due(now + Duration::from_millis(5), RelayPolicy::Flood).0.len(), 1);
+
+        for key in 0..MAX_PENDING as u64 {
+            queue.schedule(key, Vec::new(), Priority::Normal, now, None);
+        }
+        assert_eq!(queue.schedule(0, Vec::new(), Priority::Normal, now, None), 1);
+        assert_eq!(queue.due(now, RelayPolicy::Flood).0.len(), MAX_PENDING);
+    }
+
+    /// Copies heard while waiting suppress the relay under counter and distance policies
+    #[test]
+    fn test_relay_suppression() {
+        let mut queue = RelayQueue::default();
+        let now = Instant::now();
+        let counter = RelayPolicy::Counter { max_copies: 3 };
+        let distance = RelayPolicy::Distance { min_distance: 50.0 };
+
+        queue.schedule(7, b"x".to_vec(), Priority::Normal, now, None);
+        queue.heard_again(7, None);
+        assert_eq!(queue.due(now, counter).0.len(), 1);
+        queue.schedule(7, b"x".to_vec(), Priority::Normal, now, None);
+        queue.heard_again(7, None);
+        queue.heard_again(7, None);
+        assert_eq!(queue.due(now, counter), (Vec::new(), 1));
+
+        queue.schedule(8, b"y".to_vec(), Priority::Normal, now, Some(120.0));
+        queue.heard_again(8, None);
+        assert_eq!(queue.due(now, distance).0.len(), 1);
+        queue.schedule(8, b"y".to_vec(), Priority::Normal, now, Some(120.0));
+        queue.heard_again(8, Some(20.0));
+        assert_eq!(queue.due(now, distance), (Vec::new(), 1));
+    }
+}
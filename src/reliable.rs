@@ -0,0 +1,227 @@
+//! # Acknowledged Delivery
+//!
+//! Reliable unicast and multicast on top of fire-and-forget emission.
+//!
+//! A reliable frame carries the sender's origin, a sequence number and the
+//! `FLAG_ACK_REQUEST` bit. Every listed target answers with an ACK control
+//! message keyed by `(source, sequence)`; the sender retransmits with
+//! exponential backoff, addressed only to the targets still silent, until
+//! all have answered or the retry limit is hit.
+//!
+//! ```text
+//! ACK body: [acked_source:8][sequence:4][status:1]
+//! ```
+//!
+//! ACKs are broadcast and name the acknowledged sender in the body, so they
+//! work unchanged in ghost mode where that sender is only known by pseudonym.
+
+use std::time::Duration;
+
+use crate::DroneId;
+
+//==============================================================================
+// ACK MESSAGE LAYOUT
+//==============================================================================
+
+/// Size of an ACK control body
+pub(crate) const ACK_BODY_SIZE: usize = 8 + 4 + 1;
+
+/// ACK status: the frame passed every receive check
+const ACK_ACCEPTED: u8 = 0;
+
+/// ACK status: the frame arrived but failed signature or membership checks
+const ACK_REJECTED: u8 = 1;
+
+//==============================================================================
+// PUBLIC TYPES
+//==============================================================================
+
+/// Outcome of a reliable emission
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// The target acknowledged the frame
+    Delivered,
+
+    /// No acknowledgement arrived within the retry budget
+    TimedOut,
+
+    /// The target received the frame but refused it (bad signature or membership)
+    Rejected,
+}
+
+/// Retransmission schedule for reliable emissions
+///
+/// Attempt `n` waits `initial_timeout * 2^n` (capped at `max_timeout`) for an
+/// acknowledgement before the frame is sent again.
+#[derive(Debug, Copy, Clone)]
+pub struct RetransmitPolicy {
+    /// ACK wait after the first transmission
+    pub initial_timeout: Duration,
+
+    /// Upper bound on a single ACK wait
+    pub max_timeout: Duration,
+
+    /// Retransmissions after the first attempt (0 = send once)
+    pub max_retries: u32,
+}
+
+impl Default for RetransmitPolicy {
+    fn default() -> Self {
+        RetransmitPolicy {
+            initial_timeout: Duration::from_millis(20),
+            max_timeout: Duration::from_millis(500),
+            max_retries: 5,
+        }
+    }
+}
+
+impl RetransmitPolicy {
+    /// ACK wait for the given attempt (0 = first transmission)
+    pub(crate) fn timeout(&self, attempt: u32) -> Duration {
+        self.initial_timeout
+            .saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .min(self.max_timeout)
+    }
+}
+
+//==============================================================================
+// ACK ENCODING
+//==============================================================================
+
+/// Decoded ACK control body
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Ack {
+    /// Origin of the acknowledged frame as it appeared on the wire
+    pub acked_source: DroneId,
+
+    /// Sequence number of the acknowledged frame
+    pub sequence: u32,
+
+    /// False if the receiver refused the frame
+    pub accepted: bool,
+}
+
+impl Ack {
+    /// Encode as a control body
+    pub fn to_bytes(self) -> [u8; ACK_BODY_SIZE] {
+        let mut body = [0u8; ACK_BODY_SIZE];
+        body[..8].copy_from_slice(&self.acked_source.to_le_bytes());
+        body[8..12].copy_from_slice(&self.sequence.to_le_bytes());
+        body[12] = if self.accepted { ACK_ACCEPTED } else { ACK_REJECTED };
+        body
+    }
+
+    /// Decode a control body, rejecting unknown status codes
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() != ACK_BODY_SIZE {
+            return None;
+        }
+        let accepted = match body[12] {
+            ACK_ACCEPTED => true,
+            ACK_REJECTED => false,
+            _ => return None,
+        };
+        Some(Ack {
+            acked_source: DroneId::from_le_bytes(body[..8].try_into().unwrap()),
+            sequence: u32::from_le_bytes(body[8..12].try_into().unwrap()),
+            accepted,
+        })
+    }
+
+    /// Delivery outcome this ACK reports
+    pub fn status(&self) -> DeliveryStatus {
+        if self.accepted { DeliveryStatus::Delivered } else { DeliveryStatus::Rejected }
+    }
+}
+
+//==============================================================================
+// SENDER STATE
+//==============================================================================
+
+/// Outstanding reliable emission waiting for its ACKs
+#[derive(Debug, Clone)]
+pub(crate) struct AckWaiter {
+    /// Sequence number of the frame in flight
+    pub sequence: u32,
+
+    /// Real IDs of the targets, each with its outcome once its ACK arrived
+    pub targets: Vec<(DroneId, Option<DeliveryStatus>)>,
+}
+
+impl AckWaiter {
+    /// Start waiting for every drone in `targets` to acknowledge `sequence`
+    pub fn new(sequence: u32, targets: &[DroneId]) -> Self {
+        AckWaiter { sequence, targets: targets.iter().map(|&id| (id, None)).collect() }
+    }
+
+    /// Targets that have not answered yet - the next retransmission's target list
+    pub fn pending(&self) -> Vec<DroneId> {
+        self.targets.iter().filter(|(_, outcome)| outcome.is_none()).map(|&(id, _)| id).collect()
+    }
+
+    /// True once every target has answered
+    pub fn is_complete(&self) -> bool {
+        self.targets.iter().all(|(_, outcome)| outcome.is_some())
+    }
+
+    /// Final per-target report; targets that never answered timed out
+    pub fn finish(self) -> Vec<(DroneId, DeliveryStatus)> {
+        self.targets
+            .into_iter()
+            .map(|(id, outcome)| (id, outcome.unwrap_or(DeliveryStatus::TimedOut)))
+            .collect()
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ACK bodies round-trip and malformed bodies are refused
+    #[test]
+    fn test_ack_roundtrip() {
+        let ack = Ack { acked_source: 0x1122_3344_5566_7788, sequence: 9, accepted: false };
+        assert_eq!(Ack::parse(&ack.to_bytes()), Some(ack));
+
+        let mut body = ack.to_bytes();
+        body[12] = 7;
+        assert_eq!(Ack::parse(&body), None);
+        assert_eq!(Ack::parse(&body[..12]), None);
+    }
+
+    /// The pending set shrinks as ACKs arrive; silent targets time out
+    #[test]
+    fn test_waiter_tracks_each_target() {
+        let mut waiter = AckWaiter::new(5, &[1, 2, 3]);
+        assert_eq!(waiter.pending(), vec![1, 2, 3]);
+
+        waiter.targets[0].1 = Some(DeliveryStatus::Delivered);
+        waiter.targets[2].1 = Some(DeliveryStatus::Rejected);
+        assert_eq!(waiter.pending(), vec![2]);
+        assert!(!waiter.is_complete());
+
+        assert_eq!(waiter.finish(), vec![
+            (1, DeliveryStatus::Delivered),
+            (2, DeliveryStatus::TimedOut),
+            (3, DeliveryStatus::Rejected),
+        ]);
+    }
+
+    /// Backoff doubles per attempt and saturates at the cap
+    #[test]
+    fn test_backoff_schedule() {
+        let policy = RetransmitPolicy {
+            initial_timeout: Duration::from_millis(10),
+            max_timeout: Duration::from_millis(50),
+            max_retries: 3,
+        };
+        assert_eq!(policy.timeout(0), Duration::from_millis(10));
+        assert_eq!(policy.timeout(2), Duration::from_millis(40));
+        assert_eq!(policy.timeout(3), Duration::from_millis(50));
+        assert_eq!(policy.timeout(40), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,494 @@
+//! # Unicast Routing
+//!
+//! On-demand route discovery in the style of AODV, so `OVP::emit_routed`
+//! reaches drones several hops away without flooding every message.
+//!
+//! A node with no route to a destination queues the message and broadcasts a
+//! route request. Every node that hears it for the first time records a
+//! reverse route to the requester through the drone it heard it from, then
+//! rebroadcasts it. The destination answers with a route reply, passed back
+//! hop by hop along the reverse routes; each node on the way records the
+//! forward route. Requests are retried `discovery_retries` times before the
+//! queued messages are dropped.
+//!
+//! ```text
+//! ROUTE_REQUEST body: [request_id:4][origin:8][origin_seq:4][destination:8][hops:1][ttl:1]
+//! ROUTE_REPLY body:   [origin:8][destination:8][destination_seq:4][hops:1]
+//! ROUTE_ERROR body:   [count:1] ([destination:8][destination_seq:4]) * count
+//! ROUTED body:        [destination:8][inner frame]
+//! ```
+//!
+//! Destination sequence numbers keep routes loop-free: a route is replaced
+//! only by a fresher one, or an equally fresh one with fewer hops.
+//!
+//! Messages travel as a complete multi-hop frame addressed to the
+//! destination (TTL 0, so it is never flooded), wrapped in a `ROUTED` control
+//! frame addressed to the next hop. Each forwarder counts a hop in the inner
+//! frame and rewraps it for its own next hop, once per frame however many
+//! copies it hears; the destination unwraps it and accepts it like any other
+//! frame, so tags, signatures and duplicate suppression work end to end.
+//!
+//! Routes expire after `route_timeout` unused. When a neighbour drops out of
+//! the neighbour table (see `OvpConfig::neighbors`), or a node has no route
+//! for a message it should forward, it broadcasts a route error; nodes routing
+//! through the sender invalidate those routes and pass the error on, and the
+//! next message from the origin discovers a new route. Routing bodies carry
+//! real drone IDs, so routing is unavailable in ghost mode.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::dedup::{DedupConfig, DuplicateCache};
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Size of a route request body
+const REQUEST_SIZE: usize = 26;
+
+/// Size of a route reply body
+const REPLY_SIZE: usize = 21;
+
+/// Size of one route error entry
+const ERROR_ENTRY_SIZE: usize = 12;
+
+/// Most destinations in one route error
+pub(crate) const MAX_ERROR_ENTRIES: usize = 64;
+
+/// Size of the `ROUTED` header in front of the inner frame
+pub(crate) const ROUTED_HEADER_SIZE: usize = 8;
+
+/// Most messages queued per destination awaiting discovery (oldest dropped first)
+const MAX_QUEUED: usize = 16;
+
+/// Most discoveries in progress at once
+const MAX_DISCOVERIES: usize = 64;
+
+/// Most routes kept (stalest dropped first)
+const MAX_ROUTES: usize = 1024;
+
+/// Route discovery and maintenance
+#[derive(Debug, Copy, Clone)]
+pub struct RoutingConfig {
+    /// Lifetime of a route that carries no traffic
+    pub route_timeout: Duration,
+
+    /// Wait for a route reply before retrying the request
+    pub discovery_timeout: Duration,
+
+    /// Requests repeated before queued messages are given up
+    pub discovery_retries: u32,
+
+    /// Longest route, in hops
+    pub max_hops: u8,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        RoutingConfig {
+            route_timeout: Duration::from_secs(3),
+            discovery_timeout: Duration::from_millis(500),
+            discovery_retries: 2,
+            max_hops: 16,
+        }
+    }
+}
+
+/// Routing table entry, as exposed for debugging
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Drone the route leads to
+    pub destination: DroneId,
+
+    /// Neighbour frames for it are handed to
+    pub next_hop: DroneId,
+
+    /// Hops to the destination
+    pub hops: u8,
+
+    /// Destination sequence number the route was learnt with
+    pub sequence: u32,
+
+    /// False once the route broke (kept to remember its sequence number)
+    pub valid: bool,
+
+    /// When the route is forgotten unless used
+    pub expires: Instant,
+}
+
+//==============================================================================
+// MESSAGE ENCODING
+//==============================================================================
+
+/// Broadcast search for a destination
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct RouteRequest {
+    /// Per-origin request counter, identifying rebroadcast copies
+    pub request_id: u32,
+
+    /// Drone looking for a route
+    pub origin: DroneId,
+
+    /// Origin's sequence number, freshening the reverse route
+    pub origin_seq: u32,
+
+    /// Drone being looked for
+    pub destination: DroneId,
+
+    /// Hops travelled so far
+    pub hops: u8,
+
+    /// Rebroadcasts left
+    pub ttl: u8,
+}
+
+impl RouteRequest {
+    /// Encode as a control body
+    pub fn to_bytes(self) -> [u8; REQUEST_SIZE] {
+        let mut body = [0u8; REQUEST_SIZE];
+        body[0..4].copy_from_slice(&self.request_id.to_le_bytes());
+        body[4..12].copy_from_slice(&self.origin.to_le_bytes());
+        body[12..16].copy_from_slice(&self.origin_seq.to_le_bytes());
+        body[16..24].copy_from_slice(&self.destination.to_le_bytes());
+        body[24] = self.hops;
+        body[25] = self.ttl;
+        body
+    }
+
+    /// Decode a control body
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() != REQUEST_SIZE {
+            return None;
+        }
+        Some(RouteRequest {
+            request_id: u32::from_le_bytes(body[0..4].try_into().unwrap()),
+            origin: DroneId::from_le_bytes(body[4..12].try_into().unwrap()),
+            origin_seq: u32::from_le_bytes(body[12..16].try_into().unwrap()),
+            destination: DroneId::from_le_bytes(body[16..24].try_into().unwrap()),
+            hops: body[24],
+            ttl: body[25],
+        })
+    }
+}
+
+/// Answer from the destination, passed back toward the origin
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct RouteReply {
+    /// Drone that asked
+    pub origin: DroneId,
+
+    /// Drone that answered
+    pub destination: DroneId,
+
+    /// Destination's sequence number for the route
+    pub destination_seq: u32,
+
+    /// Hops from the destination so far
+    pub hops: u8,
+}
+
+impl RouteReply {
+    /// Encode as a control body
+    pub fn to_bytes(self) -> [u8; REPLY_SIZE] {
+        let mut body = [0u8; REPLY_SIZE];
+        body[0..8].copy_from_slice(&self.origin.to_le_bytes());
+        body[8..16].copy_from_slice(&self.destination.to_le_bytes());
+        body[16..20].copy_from_slice(&self.destination_seq.to_le_bytes());
+        body[20] = self.hops;
+        body
+    }
+
+    /// Decode a control body
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() != REPLY_SIZE {
+            return None;
+        }
+        Some(RouteReply {
+            origin: DroneId::from_le_bytes(body[0..8].try_into().unwrap()),
+            destination: DroneId::from_le_bytes(body[8..16].try_into().unwrap()),
+            destination_seq: u32::from_le_bytes(body[16..20].try_into().unwrap()),
+            hops: body[20],
+        })
+    }
+}
+
+/// Encode a route error listing unreachable destinations (at most `MAX_ERROR_ENTRIES`)
+pub(crate) fn route_error_to_bytes(unreachable: &[(DroneId, u32)]) -> Vec<u8> {
+    let unreachable = &unreachable[..unreachable.len().min(MAX_ERROR_ENTRIES)];
+    let mut body = Vec::with_capacity(1 + unreachable.len() * ERROR_ENTRY_SIZE);
+    body.push(unreachable.len() as u8);
+    for &(destination, sequence) in unreachable {
+        body.extend_from_slice(&destination.to_le_bytes());
+        body.extend_from_slice(&sequence.to_le_bytes());
+    }
+    body
+}
+
+/// Decode a route error body
+pub(crate) fn parse_route_error(body: &[u8]) -> Option<Vec<(DroneId, u32)>> {
+    let (&count, entries) = body.split_first()?;
+    if entries.len() != count as usize * ERROR_ENTRY_SIZE {
+        return None;
+    }
+    Some(entries.chunks_exact(ERROR_ENTRY_SIZE)
+        .map(|entry| {
+            let destination = DroneId::from_le_bytes(entry[0..8].try_into().unwrap());
+            (destination, u32::from_le_bytes(entry[8..12].try_into().unwrap()))
+        })
+        .collect())
+}
+
+/// True if sequence number `a` is newer than `b` (wrapping)
+fn newer(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
+
+//==============================================================================
+// ROUTING TABLE
+//==============================================================================
+
+/// Messages waiting for a route to one destination
+#[derive(Debug, Clone)]
+struct Discovery {
+    /// When to repeat the request or give up
+    deadline: Instant,
+
+    /// Requests still allowed after the current one
+    retries_left: u32,
+
+    /// Inner frames to send once a route exists
+    queued: VecDeque<Vec<u8>>,
+}
+
+/// Discovery that needs attention in `OVP::service`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DiscoveryStep {
+    /// Broadcast another request for the destination
+    Retry(DroneId),
+
+    /// Give up on the destination; this many queued messages are lost
+    Failed(DroneId, usize),
+}
+
+/// Known routes, pending discoveries and recently seen requests
+#[derive(Debug, Clone)]
+pub(crate) struct RoutingTable {
+    /// Routes by destination
+    routes: HashMap<DroneId, Route>,
+
+    /// Discoveries in progress by destination
+    discoveries: HashMap<DroneId, Discovery>,
+
+    /// Requests already handled, by origin and request ID
+    seen_requests: DuplicateCache,
+}
+
+impl Default for RoutingTable {
+    fn default() -> Self {
+        RoutingTable {
+            routes: HashMap::new(),
+            discoveries: HashMap::new(),
+            seen_requests: DuplicateCache::new(DedupConfig::default()),
+        }
+    }
+}
+
+impl RoutingTable {
+    /// Learn a route, keeping the current one unless the new one is fresher or shorter
+    pub fn update(&mut self, destination: DroneId, next_hop: DroneId, hops: u8, sequence: u32, expires: Instant) {
+        if let Some(route) = self.routes.get(&destination)
+            && route.valid
+            && !newer(sequence, route.sequence)
+            && !(sequence == route.sequence && hops < route.hops) {
+            return;
+        }
+        if !self.routes.contains_key(&destination) && self.routes.len() >= MAX_ROUTES {
+            let stalest = self.routes.values().min_by_key(|route| route.expires).map(|route| route.destination);
+            if let Some(stalest) = stalest {
+                self.routes.remove(&stalest);
+            }
+        }
+        self.routes.insert(destination, Route { destination, next_hop, hops, sequence, valid: true, expires });
+    }
+
+    /// Next hop toward `destination`, extending the route's life to `expires`
+    pub fn next_hop(&mut self, destination: DroneId, now: Instant, expires: Instant) -> Option<DroneId> {
+        let route = self.routes.get_mut(&destination).filter(|route| route.valid && route.expires > now)?;
+        route.expires = route.expires.max(expires);
+        Some(route.next_hop)
+    }
+
+    /// Last sequence number known for `destination` (0 if none)
+    pub fn sequence(&self, destination: DroneId) -> u32 {
+        self.routes.get(&destination).map_or(0, |route| route.sequence)
+    }
+
+    /// True the first time a request is seen
+    pub fn first_request(&mut self, origin: DroneId, request_id: u32, now: Instant) -> bool {
+        let key = self.seen_requests.sequenced_key(origin, request_id, 0);
+        !self.seen_requests.check(key, now)
+    }
+
+    /// Invalidate every route through `neighbor`, returning the destinations lost
+    pub fn break_link(&mut self, neighbor: DroneId) -> Vec<(DroneId, u32)> {
+        let mut lost = Vec::new();
+        for route in self.routes.values_mut().filter(|route| route.valid && route.next_hop == neighbor) {
+            route.valid = false;
+            route.sequence = route.sequence.wrapping_add(1);
+            lost.push((route.destination, route.sequence));
+        }
+        lost
+    }
+
+    /// Apply a route error heard from `transmitter`, returning the routes it broke here
+    pub fn apply_error(&mut self, transmitter: DroneId, unreachable: &[(DroneId, u32)]) -> Vec<(DroneId, u32)> {
+        let mut lost = Vec::new();
+        for &(destination, sequence) in unreachable {
+            if let Some(route) = self.routes.get_mut(&destination)
+                && route.valid
+                && route.next_hop == transmitter {
+                route.valid = false;
+                if newer(sequence, route.sequence) {
+                    route.sequence = sequence;
+                }
+                lost.push((destination, route.sequence));
+            }
+        }
+        lost
+    }
+
+    /// Queue an inner frame for `destination`
+    ///
+    /// Returns whether a new discovery started (so a request must be sent)
+    /// and the number of frames dropped to make room.
+    pub fn queue(&mut self, destination: DroneId, frame: Vec<u8>, deadline: Instant, retries: u32) -> (bool, usize) {
+        let mut dropped = 0;
+        let started = !self.discoveries.contains_key(&destination);
+        if started && self.discoveries.len() >= MAX_DISCOVERIES {
+            let oldest = self.discoveries.iter().min_by_key(|(_, d)| d.deadline).map(|(&id, _)| id);
+            if let Some(discovery) = oldest.and_then(|id| self.discoveries.remove(&id)) {
+                dropped += discovery.queued.len();
+            }
+        }
+        let discovery = self.discoveries.entry(destination)
+            .or_insert_with(|| Discovery { deadline, retries_left: retries, queued: VecDeque::new() });
+        if discovery.queued.len() >= MAX_QUEUED {
+            discovery.queued.pop_front();
+            dropped += 1;
+        }
+        discovery.queued.push_back(frame);
+        (started, dropped)
+    }
+
+    /// End the discovery for `destination`, returning its queued frames
+    pub fn take_queued(&mut self, destination: DroneId) -> Vec<Vec<u8>> {
+        self.discoveries.remove(&destination).map(|d| d.queued.into()).unwrap_or_default()
+    }
+
+    /// Discoveries whose deadline passed, each retried (deadline moved) or ended
+    pub fn due_discoveries(&mut self, now: Instant, timeout: Duration) -> Vec<DiscoveryStep> {
+        let mut steps = Vec::new();
+        self.discoveries.retain(|&destination, discovery| {
+            if discovery.deadline > now {
+                return true;
+            }
+            if discovery.retries_left == 0 {
+                steps.push(DiscoveryStep::Failed(destination, discovery.queued.len()));
+                return false;
+            }
+            discovery.retries_left -= 1;
+            discovery.deadline = now + timeout;
+            steps.push(DiscoveryStep::Retry(destination));
+            true
+        });
+        steps
+    }
+
+    /// Forget routes past their expiry
+    pub fn expire(&mut self, now: Instant) {
+        self.routes.retain(|_, route| route.expires > now);
+    }
+
+    /// Every route, by destination
+    pub fn snapshot(&self) -> Vec<Route> {
+        let mut routes: Vec<Route> = self.routes.values().copied().collect();
+        routes.sort_by_key(|route| route.destination);
+        routes
+    }
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Routing bodies survive encoding; malformed ones are rejected
+    #[test]
+    fn test_routing_messages_roundtrip() {
+        let request = RouteRequest { request_id: 9, origin: 1, origin_seq: 4, destination: 7, hops: 2, ttl: 14 };
+        assert_eq!(RouteRequest::parse(&request.to_bytes()), Some(request));
+        assert_eq!(RouteRequest::parse(&request.to_bytes()[1..]), None);
+
+        let reply = RouteReply { origin: 1, destination: 7, destination_seq: 3, hops: 1 };
+        assert_eq!(RouteReply::parse(&reply.to_bytes()), Some(reply));
+
+        let error = route_error_to_bytes(&[(7, 3), (8, 1)]);
+        assert_eq!(parse_route_error(&error), Some(vec![(7, 3), (8, 1)]));
+        assert_eq!(parse_route_error(&error[..error.len() - 1]), None);
+    }
+
+    /// Fresher or shorter routes win; broken links and errors invalidate routes
+    #[test]
+    fn test_route_updates_and_breaks() {
+        let mut table = RoutingTable::default();
+        let now = Instant::now();
+        let later = now + Duration::from_secs(3);
+        table.update(7, 2, 3, 5, later);
+        table.update(7, 3, 4, 5, later);
+        assert_eq!(table.next_hop(7, now, later), Some(2));
+        table.update(7, 3, 2, 5, later);
+        assert_eq!(table.next_hop(7, now, later), Some(3));
+        table.update(7, 4, 6, 6, later);
+        assert_eq!(table.next_hop(7, now, later), Some(4));
+        table.update(7, 2, 1, 4, later);
+        assert_eq!(table.next_hop(7, now, later), Some(4));
+
+        table.update(8, 4, 1, 1, later);
+        table.update(9, 2, 1, 1, later);
+        let mut lost = table.break_link(4);
+        lost.sort();
+        assert_eq!(lost, vec![(7, 7), (8, 2)]);
+        assert_eq!(table.next_hop(7, now, later), None);
+        assert_eq!(table.apply_error(3, &[(9, 2)]), Vec::new());
+        assert_eq!(table.apply_error(2, &[(9, 2)]), vec![(9, 2)]);
+        table.update(9, 5, 3, 1, later);
+        assert_eq!(table.next_hop(9, now, later), Some(5));
+
+        table.expire(later);
+        assert!(table.snapshot().is_empty());
+    }
+
+    /// Queued frames wait for a route; discoveries retry, then fail
+    #[test]
+    fn test_discovery_queue() {
+        let mut table = RoutingTable::default();
+        let now = Instant::now();
+        let timeout = Duration::from_millis(100);
+        assert_eq!(table.queue(7, b"a".to_vec(), now + timeout, 1), (true, 0));
+        assert_eq!(table.queue(7, b"b".to_vec(), now + timeout, 1), (false, 0));
+        assert_eq!(table.due_discoveries(now, timeout), Vec::new());
+        assert_eq!(table.due_discoveries(now + timeout, timeout), vec![DiscoveryStep::Retry(7)]);
+        assert_eq!(table.due_discoveries(now + timeout * 2, timeout), vec![DiscoveryStep::Failed(7, 2)]);
+
+        table.queue(8, b"c".to_vec(), now + timeout, 1);
+        assert_eq!(table.take_queued(8), vec![b"c".to_vec()]);
+        assert!(table.take_queued(8).is_empty());
+        assert!(table.first_request(1, 5, now));
+        assert!(!table.first_request(1, 5, now));
+    }
+}
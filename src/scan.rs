@@ -0,0 +1,193 @@
+//! # Vectorized Target Scanning
+//!
+//! Every received frame with a target list is scanned for our ID before
+//! anything else happens to it, which makes the scan the hottest loop on the
+//! receiver once lists grow past a handful of drones. `contains_id` compares
+//! several 64-bit IDs per instruction: four with AVX2, two with SSE2 or NEON,
+//! picked at runtime, with a scalar fallback for other CPUs.
+//!
+//! The scan exits at the first match, so its timing depends on where our ID
+//! sits in the list. Pseudonymous frames keep the constant-time scan in
+//! `frame::contains_target_ct`.
+
+use crate::DroneId;
+
+//==============================================================================
+// DISPATCH
+//==============================================================================
+
+/// Lists shorter than this (4 IDs) are scanned faster by the scalar loop
+/// than by feature detection plus a vector pass
+const SIMD_MIN_BYTES: usize = 32;
+
+/// Check whether a raw little-endian target list contains `id`
+///
+/// Trailing bytes that do not make up a whole ID are ignored.
+#[inline]
+pub(crate) fn contains_id(targets: &[u8], id: DroneId) -> bool {
+    if targets.len() < SIMD_MIN_BYTES {
+        return contains_id_scalar(targets, id);
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: the required CPU feature was detected at runtime
+            return unsafe { contains_id_avx2(targets, id) };
+        }
+        // SAFETY: SSE2 is part of the x86_64 baseline
+        unsafe { contains_id_sse2(targets, id) }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            // SAFETY: the required CPU feature was detected at runtime
+            return unsafe { contains_id_neon(targets, id) };
+        }
+        contains_id_scalar(targets, id)
+    }
+}
+
+/// Portable scan, one ID at a time
+#[inline]
+fn contains_id_scalar(targets: &[u8], id: DroneId) -> bool {
+    targets.chunks_exact(8).any(|target| u64::from_le_bytes(target.try_into().unwrap()) == id)
+}
+
+/// Our ID as the lanes hold it: wire bytes loaded in native order
+#[inline(always)]
+fn needle(id: DroneId) -> u64 {
+    u64::from_ne_bytes(id.to_le_bytes())
+}
+
+//==============================================================================
+// x86_64
+//==============================================================================
+
+/// AVX2 scan, 8 IDs per step in two 4-lane compares
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn contains_id_avx2(targets: &[u8], id: DroneId) -> bool {
+    use std::arch::x86_64::{__m256i, _mm256_cmpeq_epi64, _mm256_loadu_si256, _mm256_movemask_epi8, _mm256_or_si256, _mm256_set1_epi64x};
+
+    let needle = _mm256_set1_epi64x(needle(id) as i64);
+    let mut chunks = targets.chunks_exact(64);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_ptr() as *const __m256i;
+        // SAFETY: the chunk holds 64 readable bytes; loads are unaligned
+        let (low, high) = unsafe { (_mm256_loadu_si256(ptr), _mm256_loadu_si256(ptr.add(1))) };
+        let hits = _mm256_or_si256(_mm256_cmpeq_epi64(low, needle), _mm256_cmpeq_epi64(high, needle));
+        if _mm256_movemask_epi8(hits) != 0 {
+            return true;
+        }
+    }
+    // SAFETY: SSE2 is part of the x86_64 baseline
+    unsafe { contains_id_sse2(chunks.remainder(), id) }
+}
+
+/// SSE2 scan, 2 IDs per compare
+///
+/// SSE2 only compares 32-bit lanes, so an ID matches when both of its
+/// halves do: the compare mask is AND-ed with itself halves swapped.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn contains_id_sse2(targets: &[u8], id: DroneId) -> bool {
+    use std::arch::x86_64::{__m128i, _mm_and_si128, _mm_cmpeq_epi32, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi64x, _mm_shuffle_epi32};
+
+    let needle = _mm_set1_epi64x(needle(id) as i64);
+    let mut chunks = targets.chunks_exact(16);
+    for chunk in &mut chunks {
+        // SAFETY: the chunk holds 16 readable bytes; the load is unaligned
+        let lanes = unsafe { _mm_loadu_si128(chunk.as_ptr() as *const __m128i) };
+        let halves = _mm_cmpeq_epi32(lanes, needle);
+        let hits = _mm_and_si128(halves, _mm_shuffle_epi32::<0b10_11_00_01>(halves));
+        if _mm_movemask_epi8(hits) != 0 {
+            return true;
+        }
+    }
+    contains_id_scalar(chunks.remainder(), id)
+}
+
+//==============================================================================
+// aarch64
+//==============================================================================
+
+/// NEON scan, 2 IDs per compare
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn contains_id_neon(targets: &[u8], id: DroneId) -> bool {
+    use std::arch::aarch64::{vceqq_u64, vdupq_n_u64, vld1q_u8, vmaxvq_u32, vreinterpretq_u32_u64, vreinterpretq_u64_u8};
+
+    let needle = vdupq_n_u64(needle(id));
+    let mut chunks = targets.chunks_exact(16);
+    for chunk in &mut chunks {
+        // SAFETY: the chunk holds 16 readable bytes; byte loads need no alignment
+        let lanes = vreinterpretq_u64_u8(unsafe { vld1q_u8(chunk.as_ptr()) });
+        if vmaxvq_u32(vreinterpretq_u32_u64(vceqq_u64(lanes, needle))) != 0 {
+            return true;
+        }
+    }
+    contains_id_scalar(chunks.remainder(), id)
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scan implementation under test
+    type Scan = fn(&[u8], DroneId) -> bool;
+
+    /// Encode IDs as a wire target list, shifted by `offset` bytes so loads are unaligned
+    fn list(ids: &[DroneId], offset: usize) -> Vec<u8> {
+        let mut out = vec![0xEE; offset];
+        ids.iter().for_each(|id| out.extend_from_slice(&id.to_le_bytes()));
+        out
+    }
+
+    /// Every available path agrees with the scalar scan at every length and position
+    #[test]
+    fn test_paths_match_scalar() {
+        let mut paths: Vec<(&str, Scan)> = vec![("dispatch", contains_id), ("scalar", contains_id_scalar)];
+        #[cfg(target_arch = "x86_64")]
+        {
+            paths.push(("sse2", |targets, id| unsafe { contains_id_sse2(targets, id) }));
+            if std::is_x86_feature_detected!("avx2") {
+                paths.push(("avx2", |targets, id| unsafe { contains_id_avx2(targets, id) }));
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            paths.push(("neon", |targets, id| unsafe { contains_id_neon(targets, id) }));
+        }
+
+        for len in 0..40u64 {
+            let ids: Vec<DroneId> = (0..len).map(|i| 0x0100_0000_0000_0000 | (i * 7)).collect();
+            for offset in [0, 3] {
+                let section = &list(&ids, offset)[offset..];
+                for (name, scan) in &paths {
+                    for &id in &ids {
+                        assert!(scan(section, id), "{name} len {len} id {id:#x}");
+                    }
+                    assert!(!scan(section, 5), "{name} len {len}");
+                    assert!(!scan(section, 0x0100_0000_0000_0001), "{name} len {len}");
+                }
+            }
+        }
+    }
+
+    /// IDs sharing one 32-bit half with ours are not matches
+    #[test]
+    fn test_half_matches_rejected() {
+        let id: DroneId = 0x1122_3344_5566_7788;
+        let decoys = [0x1122_3344_0000_0000, 0x0000_0000_5566_7788, 0x5566_7788_1122_3344, 0x1122_3344_5566_7789];
+        let section = list(&decoys.repeat(5), 0);
+        assert!(!contains_id(&section, id));
+        assert!(!contains_id_scalar(&section, id));
+        // A partial trailing ID is ignored
+        assert!(!contains_id(&list(&[id], 0)[..7], id));
+    }
+}
@@ -0,0 +1,378 @@
+//! # Signed Command Frames
+//!
+//! Non-repudiable authority for commands such as emergency stop, return-to-home
+//! or geofence updates. A shared secret lets every drone forge every message;
+//! an Ed25519 signature can only be produced by the holder of the commander key.
+//!
+//! Signing is opt-in per frame (`OVP::emit_signed`). Receivers verify signed
+//! frames against the [`TrustStore`] supplied at construction and either deliver
+//! them as [`Authority::Signed`] or drop them.
+//!
+//! A valid signature alone does not stop a recorded EMERGENCY_STOP from being
+//! played back later, so every signature also covers the signer's wall-clock
+//! timestamp in microseconds, strictly increasing per node. Receivers drop
+//! signed frames whose timestamp is more than the trust store's `max_age` away
+//! from their own clock, and timestamps they already accepted from that key
+//! within the window. Commanders and drones therefore need clocks agreeing to
+//! well within `max_age` (GNSS time is typical).
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ed25519_compact::{KeyPair, PublicKey, Seed, Signature};
+
+use crate::frame::FrameView;
+
+//==============================================================================
+// SIGNATURE CONSTANTS
+//==============================================================================
+
+/// Size of an Ed25519 public key in bytes
+pub const PUBLIC_KEY_SIZE: usize = 32;
+
+/// Size of an Ed25519 signature in bytes
+const SIGNATURE_SIZE: usize = 64;
+
+/// Signature trailer: `[key_id:8][timestamp_us:8][signature:64]`
+pub(crate) const SIGNATURE_TRAILER_SIZE: usize = 8 + 8 + SIGNATURE_SIZE;
+
+/// Default freshness window of signed frames
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30);
+
+//==============================================================================
+// KEY TYPES
+//==============================================================================
+
+/// Short identifier of a commander public key
+///
+/// The first 8 bytes of the public key, little-endian. Carried in every signed
+/// frame so receivers can select the verification key without trial and error.
+pub type KeyId = u64;
+
+/// Provenance of a delivered message
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Authority {
+    /// Frame carried no signature - authenticity unknown
+    Unsigned,
+
+    /// Frame was signed by a key present in the trust store
+    Signed {
+        /// Identifier of the verifying commander key
+        by: KeyId,
+    },
+}
+
+/// Derive the [`KeyId`] of a raw Ed25519 public key
+#[inline]
+pub fn key_id_of(public_key: &[u8; PUBLIC_KEY_SIZE]) -> KeyId {
+    KeyId::from_le_bytes(public_key[..8].try_into().unwrap())
+}
+
+/// Ed25519 signing key held by a commander node
+#[derive(Clone)]
+pub struct SigningKey {
+    /// Underlying Ed25519 key pair
+    key_pair: KeyPair,
+
+    /// Cached identifier of the public half
+    key_id: KeyId,
+}
+
+impl SigningKey {
+    /// Derive a signing key from a 32-byte secret seed
+    ///
+    /// The seed is the only secret; store it in the drone's secure element or
+    /// provisioning file and never transmit it.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let key_pair = KeyPair::from_seed(Seed::new(seed));
+        let key_id = key_id_of(&key_pair.pk);
+        SigningKey { key_pair, key_id }
+    }
+
+    /// Public half of the key, suitable for distribution to trust stores
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        *self.key_pair.pk
+    }
+
+    /// Identifier carried in frames signed by this key
+    pub fn key_id(&self) -> KeyId {
+        self.key_id
+    }
+
+    /// Sign arbitrary bytes - deterministic Ed25519 (no noise)
+    pub(crate) fn sign(&self, message: &[u8]) -> [u8; SIGNATURE_SIZE] {
+        *self.key_pair.sk.sign(message, None)
+    }
+}
+
+//==============================================================================
+// TRUST STORE
+//==============================================================================
+
+/// Set of commander public keys whose signatures are accepted
+///
+/// Loaded once at `OVP` construction. A frame signed by a key that is not in
+/// the store is rejected exactly like a frame with a forged signature.
+#[derive(Clone)]
+pub struct TrustStore {
+    /// Authorized keys indexed by their short identifier
+    keys: HashMap<KeyId, PublicKey>,
+
+    /// Largest difference between a signed timestamp and our clock
+    max_age: Duration,
+}
+
+impl Default for TrustStore {
+    fn default() -> Self {
+        TrustStore { keys: HashMap::new(), max_age: DEFAULT_MAX_AGE }
+    }
+}
+
+impl TrustStore {
+    /// Create an empty trust store (rejects every signed frame)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Authorize a commander public key, returning its identifier
+    pub fn add(&mut self, public_key: [u8; PUBLIC_KEY_SIZE]) -> KeyId {
+        let key_id = key_id_of(&public_key);
+        self.keys.insert(key_id, PublicKey::new(public_key));
+        key_id
+    }
+
+    /// Revoke a previously authorized key
+    pub fn remove(&mut self, key_id: KeyId) -> bool {
+        self.keys.remove(&key_id).is_some()
+    }
+
+    /// Check whether a key is authorized
+    pub fn contains(&self, key_id: KeyId) -> bool {
+        self.keys.contains_key(&key_id)
+    }
+
+    /// Number of authorized keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// True if no keys are authorized
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Freshness window of signed frames (default 30 s)
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Accept signed frames whose timestamp is within `max_age` of our clock
+    ///
+    /// Shorter windows narrow replays; longer ones tolerate worse clocks and
+    /// slower multi-hop delivery.
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.max_age = max_age;
+    }
+
+    /// Parse a trust store from text: one hex-encoded public key per line
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    /// # fleet commander
+    /// 3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c
+    /// ```
+    pub fn from_hex_lines(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut store = TrustStore::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            store.add(decode_hex_key(line)?);
+        }
+        Ok(store)
+    }
+
+    /// Load a trust store file in the [`TrustStore::from_hex_lines`] format
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_hex_lines(&std::fs::read_to_string(path)?)
+    }
+
+    /// Verify the signature trailer of a parsed frame
+    ///
+    /// Returns the signing key's identifier and signed timestamp, or None if
+    /// the key is unknown or the signature does not cover the frame exactly as
+    /// received. Freshness is checked separately by a [`ReplayGuard`].
+    pub(crate) fn verify(&self, view: &FrameView<'_>) -> Option<(KeyId, u64)> {
+        let (key_id, timestamp, signature) = view.signature?;
+        let public_key = self.keys.get(&key_id)?;
+        let signature = Signature::from_slice(signature).ok()?;
+        public_key.verify(view.signed_region, &signature).ok()?;
+        Some((key_id, timestamp))
+    }
+}
+
+//==============================================================================
+// REPLAY PROTECTION
+//==============================================================================
+
+/// Current wall-clock time in microseconds since the Unix epoch
+pub(crate) fn now_micros() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_micros() as u64)
+}
+
+/// Timestamps of signed frames accepted within the freshness window, per key
+///
+/// Only frames whose signature verified are recorded, so the set is bounded
+/// by what trusted commanders sent during one window.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplayGuard {
+    /// Accepted timestamps by signing key
+    seen: HashMap<KeyId, BTreeSet<u64>>,
+}
+
+impl ReplayGuard {
+    /// Admit a verified frame once: false if its timestamp is more than
+    /// `max_age` from `now` or was already accepted from `key_id`
+    pub fn admit(&mut self, key_id: KeyId, timestamp: u64, now: u64, max_age: Duration) -> bool {
+        let max_age = max_age.as_micros() as u64;
+        if timestamp.abs_diff(now) > max_age {
+            return false;
+        }
+        let seen = self.seen.entry(key_id).or_default();
+        // Anything older than the window fails the age check on its own
+        *seen = seen.split_off(&now.saturating_sub(max_age));
+        seen.insert(timestamp)
+    }
+}
+
+/// Decode a 64-character hex string into a public key
+fn decode_hex_key(hex: &str) -> Result<[u8; PUBLIC_KEY_SIZE], Box<dyn std::error::Error>> {
+    // Checking the digits first also keeps the slicing below on char boundaries
+    if hex.len() != PUBLIC_KEY_SIZE * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("Trust store entry must be a 64-character hex public key".into());
+    }
+    let mut key = [0u8; PUBLIC_KEY_SIZE];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(key)
+}
+
+/// Append the signature trailer to a frame body already written in `buf[..len]`
+///
+/// The signature covers the header, targets, payload, key id and `timestamp`
+/// (microseconds, see `now_micros`), so neither the addressing nor the content
+/// can be altered, nor the frame replayed later, without detection.
+pub(crate) fn sign_frame(buf: &mut [u8], len: usize, key: &SigningKey, timestamp: u64) -> usize {
+    buf[len..len + 8].copy_from_slice(&key.key_id.to_le_bytes());
+    buf[len + 8..len + 16].copy_from_slice(&timestamp.to_le_bytes());
+    let signature = key.sign(&buf[..len + 16]);
+    buf[len + 16..len + SIGNATURE_TRAILER_SIZE].copy_from_slice(&signature);
+    len + SIGNATURE_TRAILER_SIZE
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{parse_frame, write_frame, FrameMeta, FLAG_SIGNED};
+
+    fn signed_frame(key: &SigningKey, targets: &[u64], payload: &[u8]) -> Vec<u8> {
+        let mut buf = [0u8; 256];
+        let meta = FrameMeta { flags: FLAG_SIGNED, ..FrameMeta::default() };
+        let len = write_frame(&mut buf, &meta, targets, payload).unwrap();
+        let len = sign_frame(&mut buf, len, key, 1_000_000);
+        buf[..len].to_vec()
+    }
+
+    /// Frames signed by a trusted commander verify and report the key id
+    #[test]
+    fn test_signed_frame_verifies() {
+        let commander = SigningKey::from_seed([7; 32]);
+        let mut store = TrustStore::new();
+        let key_id = store.add(commander.public_key());
+
+        let frame = signed_frame(&commander, &[42], b"EMERGENCY_STOP");
+        let view = parse_frame(&frame).unwrap();
+        assert_eq!(view.payload, b"EMERGENCY_STOP");
+        assert_eq!(store.verify(&view), Some((key_id, 1_000_000)));
+
+        // The legacy fast path still locates the payload of signed frames
+        assert_eq!(crate::parse_ovp_frame_fast(&frame, 42), Some(b"EMERGENCY_STOP".as_slice()));
+    }
+
+    /// Tampering, untrusted keys and revoked keys are all rejected
+    #[test]
+    fn test_forged_and_untrusted_frames_rejected() {
+        let commander = SigningKey::from_seed([7; 32]);
+        let rogue = SigningKey::from_seed([8; 32]);
+        let mut store = TrustStore::new();
+        store.add(commander.public_key());
+
+        let mut frame = signed_frame(&commander, &[], b"RTH");
+        frame[12] ^= 1;
+        assert_eq!(store.verify(&parse_frame(&frame).unwrap()), None);
+
+        let mut frame = signed_frame(&commander, &[3], b"RTH");
+        frame[12] = 4;
+        assert_eq!(store.verify(&parse_frame(&frame).unwrap()), None);
+
+        let frame = signed_frame(&rogue, &[], b"RTH");
+        assert_eq!(store.verify(&parse_frame(&frame).unwrap()), None);
+
+        // The timestamp is signed too
+        let mut frame = signed_frame(&commander, &[], b"RTH");
+        let at = frame.len() - SIGNATURE_SIZE - 8;
+        frame[at] ^= 1;
+        assert_eq!(store.verify(&parse_frame(&frame).unwrap()), None);
+
+        let frame = signed_frame(&commander, &[], b"RTH");
+        store.remove(commander.key_id());
+        assert_eq!(store.verify(&parse_frame(&frame).unwrap()), None);
+    }
+
+    /// Replays and frames outside the freshness window are refused
+    #[test]
+    fn test_replay_guard() {
+        let mut guard = ReplayGuard::default();
+        let max_age = Duration::from_secs(30);
+        let now = 100_000_000_000;
+        let second = 1_000_000;
+
+        assert!(guard.admit(1, now - 5 * second, now, max_age));
+        assert!(!guard.admit(1, now - 5 * second, now, max_age));
+        assert!(guard.admit(2, now - 5 * second, now, max_age));
+        assert!(guard.admit(1, now + 2 * second, now, max_age));
+        assert!(!guard.admit(1, now - 31 * second, now, max_age));
+        assert!(!guard.admit(1, now + 31 * second, now, max_age));
+
+        // Still refused later in the window; pruned once it has aged out
+        assert!(!guard.admit(1, now - 5 * second, now + 20 * second, max_age));
+        assert!(guard.admit(1, now + 60 * second, now + 60 * second, max_age));
+        assert_eq!(guard.seen[&1].len(), 1);
+        assert_eq!(TrustStore::new().max_age(), max_age);
+    }
+
+    /// Trust store text format accepts comments and rejects malformed keys
+    #[test]
+    fn test_trust_store_from_hex_lines() {
+        let commander = SigningKey::from_seed([1; 32]);
+        let hex: String = commander.public_key().iter().map(|b| format!("{b:02x}")).collect();
+
+        let store = TrustStore::from_hex_lines(&format!("# commander\n\n{hex}\n")).unwrap();
+        assert!(store.contains(commander.key_id()));
+        assert!(TrustStore::from_hex_lines("abcd").is_err());
+
+        // 64 bytes, but a multi-byte character straddles a digit pair
+        let non_ascii = format!("a{}", "é".repeat(31) + "b");
+        assert_eq!(non_ascii.len(), 64);
+        assert!(TrustStore::from_hex_lines(&non_ascii).is_err());
+        assert!(TrustStore::from_hex_lines(&format!("+{}", &hex[1..])).is_err());
+    }
+}
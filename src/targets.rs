@@ -0,0 +1,240 @@
+//! # Target Encodings
+//!
+//! A target list costs 8 bytes per drone, so a 1500-byte frame addresses at
+//! most ~185 drones, and receivers scan it linearly. Two compact encodings
+//! take over when they are smaller, chosen by the frame builder:
+//!
+//! ```text
+//! target field: [encoding:2][words:14]   (low 16 bits of the second header word)
+//!
+//! LIST   (0): [id:8] * words
+//! BITMAP (1): [base:8][bits:(words - 1) * 8]              bit i set = drone base + i
+//! BLOOM  (2): [probes:1][reserved:7][bits:(words - 1) * 8]
+//! ```
+//!
+//! The target section is `words * 8` bytes whatever the encoding, so section
+//! offsets are computed as before. Real target lists never come near 16384
+//! entries, so the two encoding bits were always zero on the wire; older
+//! builds read encoded frames as truncated and drop them.
+//!
+//! A bitmap is exact and is used whenever it is smaller than the list (IDs
+//! packed into a dense range). A Bloom filter is used for sets whose exact
+//! encoding would take more than `BLOOM_THRESHOLD_WORDS` words: it spends
+//! `BLOOM_BITS_PER_TARGET` bits per target with `BLOOM_PROBES` probes, for a
+//! false-positive rate of about 0.8% - roughly one drone in 120 that was not
+//! targeted accepts the frame anyway. Applications addressing that many
+//! drones should tolerate the occasional extra receiver (or use groups).
+//! Pseudonymous IDs are random, so ghost mode frames use lists or Bloom
+//! filters only. Bitmap and Bloom matches take constant time.
+
+use crate::DroneId;
+
+//==============================================================================
+// CONFIGURATION
+//==============================================================================
+
+/// Shift of the encoding bits within the target field
+const ENCODING_SHIFT: usize = 14;
+
+/// Mask selecting the word count from the target field
+pub(crate) const WORDS_MASK: usize = (1 << ENCODING_SHIFT) - 1;
+
+/// Exact encodings larger than this many words become Bloom filters
+const BLOOM_THRESHOLD_WORDS: usize = 64;
+
+/// Bloom filter bits spent per target
+const BLOOM_BITS_PER_TARGET: usize = 10;
+
+/// Bloom filter probes per ID
+const BLOOM_PROBES: u8 = 7;
+
+/// How a frame's target section is encoded
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum TargetEncoding {
+    /// Plain list of 8-byte IDs
+    List = 0,
+
+    /// Base ID and one bit per ID above it
+    Bitmap = 1,
+
+    /// Bloom filter over the IDs
+    Bloom = 2,
+}
+
+//==============================================================================
+// ENCODING
+//==============================================================================
+
+/// Encoding and size in 8-byte words the builder uses for `targets`
+///
+/// `pseudonymous` rules out bitmaps, since the IDs on the wire will be
+/// random pseudonyms rather than `targets`.
+pub(crate) fn choose(targets: &[DroneId], pseudonymous: bool) -> (TargetEncoding, usize) {
+    let mut best = (TargetEncoding::List, targets.len());
+    if !pseudonymous && let Some(words) = bitmap_words(targets) && words < best.1 {
+        best = (TargetEncoding::Bitmap, words);
+    }
+    let bloom = 1 + (targets.len() * BLOOM_BITS_PER_TARGET).div_ceil(64);
+    if best.1 > BLOOM_THRESHOLD_WORDS && bloom < best.1 {
+        best = (TargetEncoding::Bloom, bloom);
+    }
+    best
+}
+
+/// Size of a bitmap covering `targets`, if it fits the target field
+fn bitmap_words(targets: &[DroneId]) -> Option<usize> {
+    let (&min, &max) = (targets.iter().min()?, targets.iter().max()?);
+    let words = 1 + (max - min).checked_add(1)?.div_ceil(64);
+    usize::try_from(words).ok().filter(|&words| words <= WORDS_MASK)
+}
+
+/// Target field value for the second header word
+#[inline(always)]
+pub(crate) fn field(encoding: TargetEncoding, words: usize) -> usize {
+    (encoding as usize) << ENCODING_SHIFT | words
+}
+
+/// Split a target field into encoding and size in words (None = unknown encoding)
+#[inline(always)]
+pub(crate) fn split_field(field: usize) -> Option<(TargetEncoding, usize)> {
+    let encoding = match field >> ENCODING_SHIFT {
+        0 => TargetEncoding::List,
+        1 => TargetEncoding::Bitmap,
+        2 => TargetEncoding::Bloom,
+        _ => return None,
+    };
+    Some((encoding, field & WORDS_MASK))
+}
+
+/// Encode `targets` into `out`, which holds the `words * 8` bytes `choose` asked for
+pub(crate) fn write(out: &mut [u8], targets: &[DroneId], encoding: TargetEncoding) {
+    match encoding {
+        TargetEncoding::List => {
+            for (slot, &target) in out.chunks_exact_mut(8).zip(targets) {
+                slot.copy_from_slice(&target.to_le_bytes());
+            }
+        }
+        TargetEncoding::Bitmap => {
+            let base = targets.iter().copied().min().unwrap_or(0);
+            out.fill(0);
+            out[..8].copy_from_slice(&base.to_le_bytes());
+            for &target in targets {
+                let bit = (target - base) as usize;
+                out[8 + bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        TargetEncoding::Bloom => {
+            out.fill(0);
+            out[0] = BLOOM_PROBES;
+            let bits = (out.len() - 8) * 8;
+            for &target in targets {
+                for bit in probes(target, BLOOM_PROBES, bits) {
+                    out[8 + bit / 8] |= 1 << (bit % 8);
+                }
+            }
+        }
+    }
+}
+
+//==============================================================================
+// MATCHING
+//==============================================================================
+
+/// Check whether an encoded (non-list) target section includes `id`
+///
+/// Runs in constant time for both encodings. Malformed sections match nothing.
+#[inline(always)]
+pub(crate) fn matches(encoding: TargetEncoding, section: &[u8], id: DroneId) -> bool {
+    if section.len() < 16 {
+        return false;
+    }
+    let bits = &section[8..];
+    match encoding {
+        TargetEncoding::List => false,
+        TargetEncoding::Bitmap => {
+            let base = u64::from_le_bytes(section[..8].try_into().unwrap());
+            id.checked_sub(base)
+                .filter(|&offset| offset < bits.len() as u64 * 8)
+                .is_some_and(|offset| bits[offset as usize / 8] & (1 << (offset % 8)) != 0)
+        }
+        TargetEncoding::Bloom => {
+            let count = section[0];
+            if count == 0 || count > 32 {
+                return false;
+            }
+            probes(id, count, bits.len() * 8).fold(true, |all, bit| all & (bits[bit / 8] & (1 << (bit % 8)) != 0))
+        }
+    }
+}
+
+/// Bloom filter bit positions of `id` (double hashing over a 64-bit mix)
+#[inline(always)]
+fn probes(id: DroneId, count: u8, bits: usize) -> impl Iterator<Item = usize> {
+    let hash = mix(id);
+    let (h1, h2) = (hash & 0xFFFF_FFFF, (hash >> 32) | 1);
+    (0..count as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits as u64) as usize)
+}
+
+/// SplitMix64 finaliser - spreads clustered IDs over the whole filter
+#[inline(always)]
+fn mix(id: DroneId) -> u64 {
+    let mut z = id.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+//==============================================================================
+// UNIT TESTS
+//==============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode `targets` the way the builder would
+    fn encode(targets: &[DroneId], pseudonymous: bool) -> (TargetEncoding, Vec<u8>) {
+        let (encoding, words) = choose(targets, pseudonymous);
+        let mut out = vec![0xAA; words * 8];
+        write(&mut out, targets, encoding);
+        (encoding, out)
+    }
+
+    /// Small or scattered sets stay lists; dense ranges become exact bitmaps
+    #[test]
+    fn test_choose_list_and_bitmap() {
+        assert_eq!(choose(&[], false), (TargetEncoding::List, 0));
+        assert_eq!(choose(&[7, 9], false), (TargetEncoding::List, 2));
+        assert_eq!(choose(&[1, u64::MAX], false), (TargetEncoding::List, 2));
+
+        let squad: Vec<DroneId> = (1000..1300).filter(|id| id % 3 != 0).collect();
+        let (encoding, section) = encode(&squad, false);
+        assert_eq!(encoding, TargetEncoding::Bitmap);
+        assert_eq!(section.len(), 8 + 40);
+        for id in 990..1310 {
+            assert_eq!(matches(encoding, &section, id), squad.contains(&id), "{id}");
+        }
+        assert_eq!(field(encoding, 6), 1 << 14 | 6);
+        assert_eq!(split_field(field(encoding, 6)), Some((TargetEncoding::Bitmap, 6)));
+        assert_eq!(split_field(3 << 14), None);
+    }
+
+    /// Large scattered sets become Bloom filters near the documented false-positive rate
+    #[test]
+    fn test_bloom_filter() {
+        let fleet: Vec<DroneId> = (0..500u64).map(|i| mix(i) | 1).collect();
+        let (encoding, section) = encode(&fleet, false);
+        assert_eq!(encoding, TargetEncoding::Bloom);
+        assert!(section.len() < 700);
+        assert!(fleet.iter().all(|&id| matches(encoding, &section, id)));
+
+        let false_positives = (0..100_000u64).filter(|&id| matches(encoding, &section, id << 1)).count();
+        assert!(false_positives < 1500, "{false_positives}");
+
+        // Pseudonymous sets never use bitmaps
+        let dense: Vec<DroneId> = (0..100).collect();
+        assert_eq!(choose(&dense, false).0, TargetEncoding::Bitmap);
+        assert_eq!(choose(&dense, true).0, TargetEncoding::Bloom);
+        assert!(!matches(TargetEncoding::Bloom, &[0; 8], 1));
+    }
+}